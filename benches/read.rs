@@ -0,0 +1,38 @@
+//! Measures reading JSON text with `Reader`, and deserializing the same data from a `Value` tree with `Value::deserialize()`.
+//!
+//! Run with `cargo bench --bench read`.
+
+use nop_json::{Reader, TryFromJson, DebugToJson, Value, json};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 100;
+
+fn measure<F>(mut f: F) -> Duration where F: FnMut()
+{	f(); // warm up
+	let start = Instant::now();
+	for _ in 0 .. ITERATIONS
+	{	f();
+	}
+	start.elapsed() / ITERATIONS
+}
+
+fn bench<T>(name: &str, value: &T) where T: DebugToJson + TryFromJson
+{	let text = value.to_json_string();
+	let tree: Value = Reader::new(text.bytes()).read().unwrap();
+	let from_text = measure(|| {black_box(Reader::new(text.bytes()).read::<T>().unwrap());});
+	let from_tree = measure(|| {black_box(tree.deserialize::<T>().unwrap());});
+	println!("{:<12} text: {:>10.2?}   tree: {:>10.2?}", name, from_text, from_tree);
+}
+
+fn main()
+{	let integers: Vec<i64> = (0 .. 100_000).map(|i| i * 7919 - 300_000_000).collect();
+	let floats: Vec<f64> = (0 .. 100_000).map(|i| i as f64 / 7.0).collect();
+	let strings: Vec<String> = (0 .. 100_000).map(|i| format!("item \"{}\"\tname", i)).collect();
+	let value = Value::Array((0 .. 10_000).map(|i| json!({"id": i, "name": format!("item {}", i), "price": 0.5, "tags": ["a", "b"]})).collect());
+
+	bench("integers", &integers);
+	bench("floats", &floats);
+	bench("strings", &strings);
+	bench("value", &value);
+}
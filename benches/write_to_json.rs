@@ -0,0 +1,53 @@
+//! Compares native `WriteToJson` output with formatting the same data through `DebugToJson` and `fmt`.
+//!
+//! Run with `cargo bench --bench write_to_json`.
+
+use nop_json::{WriteToJson, DebugToJson, Value, json};
+use std::{fmt, io};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 200;
+
+/// Writes through `fmt::Formatter`, like `WriteToJson` implementations did before.
+fn write_with_fmt<W, T>(out: &mut W, value: &T) -> io::Result<()> where T: DebugToJson, W: io::Write
+{	struct Wrapper<'a, T: DebugToJson>
+	{	value: &'a T
+	}
+	impl<'a, T: DebugToJson> fmt::Display for Wrapper<'a, T>
+	{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+		{	DebugToJson::fmt(self.value, out)
+		}
+	}
+	write!(out, "{}", Wrapper {value})
+}
+
+fn measure<F>(mut f: F) -> Duration where F: FnMut(&mut Vec<u8>)
+{	let mut out = Vec::with_capacity(1 << 20);
+	f(&mut out); // warm up
+	let start = Instant::now();
+	for _ in 0 .. ITERATIONS
+	{	out.clear();
+		f(&mut out);
+		black_box(&out);
+	}
+	start.elapsed() / ITERATIONS
+}
+
+fn bench<T>(name: &str, value: &T) where T: DebugToJson + WriteToJson<Vec<u8>>
+{	let native = measure(|out| value.write_to_json(out).unwrap());
+	let with_fmt = measure(|out| write_with_fmt(out, value).unwrap());
+	println!("{:<12} native: {:>10.2?}   fmt: {:>10.2?}   x{:.2}", name, native, with_fmt, with_fmt.as_secs_f64() / native.as_secs_f64());
+}
+
+fn main()
+{	let integers: Vec<i64> = (0 .. 100_000).map(|i| i * 7919 - 300_000_000).collect();
+	let floats: Vec<f64> = (0 .. 100_000).map(|i| i as f64 / 7.0).collect();
+	let strings: Vec<String> = (0 .. 100_000).map(|i| format!("item \"{}\"\tname", i)).collect();
+	let value = Value::Array((0 .. 10_000).map(|i| json!({"id": i, "name": format!("item {}", i), "price": 0.5, "tags": ["a", "b"]})).collect());
+
+	bench("integers", &integers);
+	bench("floats", &floats);
+	bench("strings", &strings);
+	bench("value", &value);
+}
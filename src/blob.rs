@@ -0,0 +1,465 @@
+use crate::nop_json::{Reader, TryFromJson, Utf8Policy};
+use crate::write_to_json::WriteToJson;
+use crate::escape::{write_json_string, write_escaped, Escaping};
+
+use std::{fmt, io};
+use std::error::Error;
+use std::ops::{Deref, DerefMut};
+
+/// How many bytes [pipe_blob_from()](fn.pipe_blob_from.html) reads from the input at once.
+const PIPE_BUFFER_SIZE: usize = 8*1024;
+
+/// Writes binary data as JSON string, that [Reader::read_blob()](struct.Reader.html#method.read_blob) and [Reader::pipe_blob()](struct.Reader.html#method.pipe_blob) read back.
+///
+/// Bytes `00 - 1F`, `"` and `\` are escaped, and all the other bytes are written as they are (see [read_blob()](struct.Reader.html#method.read_blob) for explanation).
+///
+/// ```
+/// use nop_json::{write_blob, Reader};
+///
+/// let data = b"\x00\x80\x81\"";
+/// let mut json = Vec::new();
+/// write_blob(&mut json, data).unwrap();
+/// assert_eq!(json, b"\"\\u0000\x80\x81\\\"\"");
+///
+/// let mut reader = Reader::new(json.iter().copied());
+/// assert_eq!(reader.read_blob().unwrap(), data);
+/// ```
+pub fn write_blob<W>(out: &mut W, data: &[u8]) -> io::Result<()> where W: io::Write
+{	write_json_string(out, data)
+}
+
+/// Like [write_blob()](fn.write_blob.html), but reads the data from `input` until its end, so data of any size can be written without loading it to memory.
+///
+/// ```
+/// use nop_json::pipe_blob_from;
+///
+/// let mut json = b"{\"data\": ".to_vec();
+/// pipe_blob_from(&mut json, &b"\x01\x02\xFF"[..]).unwrap();
+/// json.push(b'}');
+/// assert_eq!(json, b"{\"data\": \"\\u0001\\u0002\xFF\"}");
+/// ```
+pub fn pipe_blob_from<W, R>(out: &mut W, mut input: R) -> io::Result<()> where W: io::Write, R: io::Read
+{	let mut buffer = [0u8; PIPE_BUFFER_SIZE];
+	let mut prefix: &[u8] = b"\"";
+	loop
+	{	let n = match input.read(&mut buffer)
+		{	Ok(0) => break,
+			Ok(n) => n,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		};
+		write_escaped(out, prefix, &buffer[.. n], b"", Escaping::Minimal)?;
+		prefix = b"";
+	}
+	write_escaped(out, prefix, b"", b"\"", Escaping::Minimal)
+}
+
+/// Binary data, that is serialized as JSON string, like [write_blob()](fn.write_blob.html) does, and deserialized with [Reader::read_blob()](struct.Reader.html#method.read_blob).
+/// `Vec<u8>` is serialized as array of numbers, and this wrapper allows to choose the compact representation for a field.
+///
+/// ```
+/// use nop_json::{Blob, Reader, WriteToJson};
+///
+/// let blob = Blob(b"\x01\xFF".to_vec());
+/// let mut json = Vec::new();
+/// blob.write_to_json(&mut json).unwrap();
+/// assert_eq!(json, b"\"\\u0001\xFF\"");
+///
+/// let mut reader = Reader::new(json.iter().copied());
+/// let blob_back: Blob = reader.read().unwrap();
+/// assert_eq!(blob_back, blob);
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct Blob(pub Vec<u8>);
+
+impl Deref for Blob
+{	type Target = Vec<u8>;
+
+	fn deref(&self) -> &Self::Target
+	{	&self.0
+	}
+}
+
+impl DerefMut for Blob
+{	fn deref_mut(&mut self) -> &mut Self::Target
+	{	&mut self.0
+	}
+}
+
+impl From<Vec<u8>> for Blob
+{	fn from(data: Vec<u8>) -> Self
+	{	Blob(data)
+	}
+}
+
+impl From<Blob> for Vec<u8>
+{	fn from(blob: Blob) -> Self
+	{	blob.0
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for Blob
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	write_blob(out, &self.0)
+	}
+}
+
+impl TryFromJson for Blob
+{	fn try_from_json<T>(reader: &mut Reader<T>) -> io::Result<Self> where T: Iterator<Item=u8>
+	{	reader.read_blob().map(Blob)
+	}
+}
+
+const BASE64_STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Size of the stack buffer that encoders and decoders collect output in.
+const CODEC_BUFFER_SIZE: usize = 256;
+
+/// Base64 alphabet to use in [write_blob_base64()](fn.write_blob_base64.html) and [encode_base64()](fn.encode_base64.html).
+/// [Reader::read_blob_base64()](struct.Reader.html#method.read_blob_base64) accepts both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Base64Alphabet
+{	/// RFC 4648 standard alphabet, with `+` and `/`.
+	#[default]
+	Standard,
+	/// RFC 4648 URL and filename safe alphabet, with `-` and `_`.
+	UrlSafe,
+}
+
+/// Encodes `data` to base64, passing the result to `out` part by part.
+fn encode_base64_parts<F, E>(data: &[u8], alphabet: Base64Alphabet, padding: bool, mut out: F) -> Result<(), E> where F: FnMut(&[u8]) -> Result<(), E>
+{	let table = match alphabet
+	{	Base64Alphabet::Standard => BASE64_STANDARD,
+		Base64Alphabet::UrlSafe => BASE64_URL_SAFE,
+	};
+	let mut buffer = [0u8; CODEC_BUFFER_SIZE];
+	let mut len = 0;
+	for group in data.chunks(3)
+	{	if len + 4 > CODEC_BUFFER_SIZE
+		{	out(&buffer[.. len])?;
+			len = 0;
+		}
+		let n = (group[0] as u32) << 16 | (*group.get(1).unwrap_or(&0) as u32) << 8 | *group.get(2).unwrap_or(&0) as u32;
+		let n_chars = group.len() + 1;
+		for i in 0 .. 4
+		{	if i < n_chars
+			{	buffer[len] = table[(n >> (18 - 6*i) & 0x3F) as usize];
+				len += 1;
+			}
+			else if padding
+			{	buffer[len] = b'=';
+				len += 1;
+			}
+		}
+	}
+	out(&buffer[.. len])
+}
+
+/// Encodes `data` to hex, passing the result to `out` part by part.
+fn encode_hex_parts<F, E>(data: &[u8], mut out: F) -> Result<(), E> where F: FnMut(&[u8]) -> Result<(), E>
+{	let mut buffer = [0u8; CODEC_BUFFER_SIZE];
+	for chunk in data.chunks(CODEC_BUFFER_SIZE / 2)
+	{	for (i, c) in chunk.iter().enumerate()
+		{	buffer[i*2] = HEX_DIGITS[(c >> 4) as usize];
+			buffer[i*2 + 1] = HEX_DIGITS[(c & 0xF) as usize];
+		}
+		out(&buffer[.. chunk.len()*2])?;
+	}
+	Ok(())
+}
+
+/// Encodes binary data to base64 string (without quotes).
+///
+/// ```
+/// use nop_json::{encode_base64, Base64Alphabet};
+///
+/// assert_eq!(encode_base64(b"\xFB\xFF", Base64Alphabet::Standard, true), "+/8=");
+/// assert_eq!(encode_base64(b"\xFB\xFF", Base64Alphabet::UrlSafe, false), "-_8");
+/// ```
+pub fn encode_base64(data: &[u8], alphabet: Base64Alphabet, padding: bool) -> String
+{	let mut result = Vec::with_capacity(data.len().div_ceil(3) * 4);
+	let _: Result<(), ()> = encode_base64_parts(data, alphabet, padding, |part| {result.extend_from_slice(part); Ok(())});
+	String::from_utf8(result).unwrap()
+}
+
+/// Encodes binary data to lowercase hex string (without quotes).
+pub fn encode_hex(data: &[u8]) -> String
+{	let mut result = Vec::with_capacity(data.len() * 2);
+	let _: Result<(), ()> = encode_hex_parts(data, |part| {result.extend_from_slice(part); Ok(())});
+	String::from_utf8(result).unwrap()
+}
+
+/// Writes binary data as base64 JSON string, that [Reader::read_blob_base64()](struct.Reader.html#method.read_blob_base64) reads back.
+/// Unlike [write_blob()](fn.write_blob.html), the output is valid UTF-8, so other JSON libraries will accept it.
+///
+/// ```
+/// use nop_json::{write_blob_base64, Base64Alphabet, Reader};
+///
+/// let mut json = Vec::new();
+/// write_blob_base64(&mut json, b"\x00\x80\xFF", Base64Alphabet::Standard, true).unwrap();
+/// assert_eq!(json, b"\"AID/\"");
+///
+/// let mut reader = Reader::new(json.iter().copied());
+/// assert_eq!(reader.read_blob_base64().unwrap(), b"\x00\x80\xFF");
+/// ```
+pub fn write_blob_base64<W>(out: &mut W, data: &[u8], alphabet: Base64Alphabet, padding: bool) -> io::Result<()> where W: io::Write
+{	out.write_all(b"\"")?;
+	encode_base64_parts(data, alphabet, padding, |part| out.write_all(part))?;
+	out.write_all(b"\"")
+}
+
+/// Writes binary data as hex JSON string, that [Reader::read_blob_hex()](struct.Reader.html#method.read_blob_hex) reads back.
+///
+/// ```
+/// use nop_json::{write_blob_hex, Reader};
+///
+/// let mut json = Vec::new();
+/// write_blob_hex(&mut json, b"\x00\x80\xFF").unwrap();
+/// assert_eq!(json, b"\"0080ff\"");
+///
+/// let mut reader = Reader::new(json.iter().copied());
+/// assert_eq!(reader.read_blob_hex().unwrap(), b"\x00\x80\xFF");
+/// ```
+pub fn write_blob_hex<W>(out: &mut W, data: &[u8]) -> io::Result<()> where W: io::Write
+{	out.write_all(b"\"")?;
+	encode_hex_parts(data, |part| out.write_all(part))?;
+	out.write_all(b"\"")
+}
+
+/// Error that [BlobDecoder] returns on invalid input, so the reader can distinguish it from errors of the underlying writer.
+#[derive(Debug)]
+struct DecodeError(&'static str);
+
+impl fmt::Display for DecodeError
+{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+	{	write!(out, "{}", self.0)
+	}
+}
+
+impl Error for DecodeError {}
+
+/// If the error was returned by [BlobDecoder] on invalid input, returns its message.
+pub fn decode_error_message(error: &io::Error) -> Option<&'static str>
+{	error.get_ref().and_then(|e| e.downcast_ref::<DecodeError>()).map(|e| e.0)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlobEncoding
+{	Base64,
+	Hex,
+}
+
+/// Writer that decodes base64 (both alphabets, padding optional) or hex, and writes the decoded bytes to `out`.
+/// Call [finish()](#method.finish) after the last byte.
+pub struct BlobDecoder<'a, W> where W: io::Write
+{	out: &'a mut W,
+	encoding: BlobEncoding,
+	limit: usize,
+	n_written: usize,
+	bits: u32,
+	n_digits: usize,
+	n_padding: usize,
+	buffer: [u8; CODEC_BUFFER_SIZE],
+	buffer_len: usize,
+}
+
+impl<'a, W> BlobDecoder<'a, W> where W: io::Write
+{	/// Returns error if more than `limit` bytes are decoded.
+	pub fn new(out: &'a mut W, encoding: BlobEncoding, limit: usize) -> Self
+	{	BlobDecoder {out, encoding, limit, n_written: 0, bits: 0, n_digits: 0, n_padding: 0, buffer: [0u8; CODEC_BUFFER_SIZE], buffer_len: 0}
+	}
+
+	fn invalid(&self) -> io::Error
+	{	let message = match self.encoding
+		{	BlobEncoding::Base64 => "Invalid base64 string",
+			BlobEncoding::Hex => "Invalid hex string",
+		};
+		io::Error::new(io::ErrorKind::InvalidData, DecodeError(message))
+	}
+
+	fn push(&mut self, c: u8) -> io::Result<()>
+	{	if self.n_written >= self.limit
+		{	return Err(io::Error::new(io::ErrorKind::InvalidData, DecodeError("Invalid JSON input: string or blob value is too large")));
+		}
+		if self.buffer_len >= CODEC_BUFFER_SIZE
+		{	self.out.write_all(&self.buffer)?;
+			self.buffer_len = 0;
+		}
+		self.buffer[self.buffer_len] = c;
+		self.buffer_len += 1;
+		self.n_written += 1;
+		Ok(())
+	}
+
+	/// Checks that the input was complete, and writes the rest of the output.
+	pub fn finish(mut self) -> io::Result<()>
+	{	match self.encoding
+		{	BlobEncoding::Base64 =>
+			{	if self.n_digits == 1 || self.n_padding != 0 && self.n_digits + self.n_padding != 4
+				{	return Err(self.invalid());
+				}
+				if self.n_digits >= 2
+				{	self.push((self.bits >> (self.n_digits*6 - 8)) as u8)?;
+				}
+				if self.n_digits == 3
+				{	self.push((self.bits >> 2) as u8)?;
+				}
+			}
+			BlobEncoding::Hex =>
+			{	if self.n_digits != 0
+				{	return Err(self.invalid());
+				}
+			}
+		}
+		self.out.write_all(&self.buffer[.. self.buffer_len])
+	}
+}
+
+impl<'a, W> io::Write for BlobDecoder<'a, W> where W: io::Write
+{	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{	for &c in buf
+		{	match self.encoding
+			{	BlobEncoding::Base64 =>
+				{	let digit = match c
+					{	b'A' ..= b'Z' => c - b'A',
+						b'a' ..= b'z' => c - b'a' + 26,
+						b'0' ..= b'9' => c - b'0' + 52,
+						b'+' | b'-' => 62,
+						b'/' | b'_' => 63,
+						b'=' if self.n_digits >= 2 && self.n_digits + self.n_padding < 4 =>
+						{	self.n_padding += 1;
+							continue;
+						}
+						_ => return Err(self.invalid())
+					};
+					if self.n_padding != 0
+					{	return Err(self.invalid());
+					}
+					self.bits = self.bits << 6 | digit as u32;
+					self.n_digits += 1;
+					if self.n_digits == 4
+					{	let bits = self.bits;
+						self.push((bits >> 16) as u8)?;
+						self.push((bits >> 8) as u8)?;
+						self.push(bits as u8)?;
+						self.bits = 0;
+						self.n_digits = 0;
+					}
+				}
+				BlobEncoding::Hex =>
+				{	let digit = match c
+					{	b'0' ..= b'9' => c - b'0',
+						b'a' ..= b'f' => c - b'a' + 10,
+						b'A' ..= b'F' => c - b'A' + 10,
+						_ => return Err(self.invalid())
+					};
+					self.bits = self.bits << 4 | digit as u32;
+					self.n_digits += 1;
+					if self.n_digits == 2
+					{	self.push(self.bits as u8)?;
+						self.bits = 0;
+						self.n_digits = 0;
+					}
+				}
+			}
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()>
+	{	Ok(())
+	}
+}
+
+/// Writer that checks that the bytes written to it form valid UTF-8 (characters can be split between `write()` calls), and passes them to `out`.
+/// With `Utf8Policy::Replace` each invalid sequence is replaced with `U+FFFD`, and with other policies it gives error.
+/// Call [finish()](#method.finish) after the last byte.
+pub struct Utf8Validator<'a, W> where W: io::Write
+{	out: &'a mut W,
+	invalid_utf8: Utf8Policy,
+	pending: [u8; 4],
+	pending_len: usize,
+}
+
+impl<'a, W> Utf8Validator<'a, W> where W: io::Write
+{	pub fn new(out: &'a mut W, invalid_utf8: Utf8Policy) -> Self
+	{	Utf8Validator {out, invalid_utf8, pending: [0u8; 4], pending_len: 0}
+	}
+
+	/// Called on invalid sequence.
+	fn invalid(&mut self) -> io::Result<()>
+	{	if self.invalid_utf8 != Utf8Policy::Replace
+		{	return Err(io::Error::new(io::ErrorKind::InvalidData, DecodeError("Invalid UTF-8 string")));
+		}
+		self.out.write_all("\u{FFFD}".as_bytes())
+	}
+
+	/// Checks that the input didn't end in the middle of a character.
+	pub fn finish(mut self) -> io::Result<()>
+	{	if self.pending_len != 0
+		{	self.invalid()?;
+		}
+		Ok(())
+	}
+}
+
+impl<'a, W> io::Write for Utf8Validator<'a, W> where W: io::Write
+{	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{	let mut data = buf;
+		// complete the character that was split by the previous call
+		while self.pending_len != 0
+		{	let c = match data.first()
+			{	Some(&c) => c,
+				None => return Ok(buf.len())
+			};
+			self.pending[self.pending_len] = c;
+			match std::str::from_utf8(&self.pending[.. self.pending_len+1])
+			{	Ok(_) =>
+				{	self.out.write_all(&self.pending[.. self.pending_len+1])?;
+					self.pending_len = 0;
+					data = &data[1 ..];
+				}
+				Err(e) if e.error_len().is_none() =>
+				{	self.pending_len += 1;
+					data = &data[1 ..];
+				}
+				Err(_) =>
+				{	// the pending bytes are invalid sequence, and `c` will be checked again
+					self.pending_len = 0;
+					self.invalid()?;
+				}
+			}
+		}
+		loop
+		{	match std::str::from_utf8(data)
+			{	Ok(_) =>
+				{	self.out.write_all(data)?;
+					break;
+				}
+				Err(e) =>
+				{	let valid_len = e.valid_up_to();
+					self.out.write_all(&data[.. valid_len])?;
+					match e.error_len()
+					{	Some(len) =>
+						{	self.invalid()?;
+							data = &data[valid_len+len ..];
+						}
+						None =>
+						{	// the data ends in the middle of a character
+							self.pending_len = data.len() - valid_len;
+							self.pending[.. self.pending_len].copy_from_slice(&data[valid_len ..]);
+							break;
+						}
+					}
+				}
+			}
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()>
+	{	self.out.flush()
+	}
+}
@@ -1,9 +1,13 @@
 pub use nop_json_derive::*;
-use crate::nop_json::{number_to_string, READER_BUFFER_SIZE};
+use crate::nop_json::{number_to_string, float_to_string, write_number, READER_BUFFER_SIZE};
 use crate::value::Value;
+use crate::value_writer::ValueWriter;
+use crate::pretty::Formatting;
+use crate::decimal::Decimal;
 use crate::escape::escape;
+use crate::json_sink::{JsonSink, integer_to_sink, float_to_sink, value_to_sink};
 
-use std::{char, fmt, f32, f64};
+use std::{char, fmt, f32, f64, io};
 use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, LinkedList, VecDeque};
 use numtoa::NumToA;
 
@@ -50,7 +54,7 @@ use numtoa::NumToA;
 /// }
 /// impl std::fmt::Debug for Point
 /// {	fn fmt(&self, out: &mut std::fmt::Formatter) -> std::fmt::Result
-/// 	{	DebugToJson::fmt(self, out)
+/// 	{	DebugToJson::debug_fmt(self, out)
 /// 	}
 /// }
 /// ```
@@ -88,26 +92,111 @@ pub trait DebugToJson
 		let w = Wrapper {value: self};
 		w.to_string()
 	}
+
+	/// Like [to_json_string()](#method.to_json_string), but formats the JSON according to the given [Formatting](struct.Formatting.html).
+	/// Built-in and derived types are formatted as they are written, through [to_sink()](#method.to_sink).
+	/// Returns error if this object produces invalid JSON (that is possible only with manual `DebugToJson` implementations),
+	/// or if canonical formatting is requested, and a number can't be represented in it.
+	/// ```
+	/// use nop_json::{DebugToJson, Formatting};
+	///
+	/// let v = vec![(1, true)];
+	/// assert_eq!(v.to_json_string_with(&Formatting::pretty()).unwrap(), "[\n  [\n    1,\n    true\n  ]\n]");
+	/// ```
+	fn to_json_string_with(&self, formatting: &Formatting) -> io::Result<String> where Self: std::marker::Sized
+	{	let mut writer = formatting.clone().build(Vec::new());
+		self.to_sink(&mut writer)?;
+		String::from_utf8(writer.finish()?).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8"))
+	}
+
+	/// Formats compact JSON, or pretty JSON ([Formatting::pretty()](struct.Formatting.html#method.pretty)) if alternate flag is given (`{:#?}`).
+	/// `Debug` implementations call this, including derived ones.
+	/// ```
+	/// use nop_json::{DebugToJson, Value};
+	///
+	/// let v = Value::Array(vec![Value::Null]);
+	/// assert_eq!(format!("{:?}", v), "[null]");
+	/// assert_eq!(format!("{:#?}", v), "[\n  null\n]");
+	/// ```
+	fn debug_fmt(&self, out: &mut fmt::Formatter) -> fmt::Result where Self: std::marker::Sized
+	{	if out.alternate()
+		{	out.write_str(&self.to_json_string_with(&Formatting::pretty()).map_err(|_| fmt::Error)?)
+		}
+		else
+		{	DebugToJson::fmt(self, out)
+		}
+	}
+
+	/// Passes this value to [JsonSink](trait.JsonSink.html) as structure, like [ValueWriter](struct.ValueWriter.html) that builds [Value](enum.Value.html) from it.
+	/// Built-in types and derived implementations override this method. The default implementation writes JSON text with `fmt()`
+	/// between `begin_raw()` and `end_raw()`.
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	struct Wrapper<'a, T: DebugToJson + ?Sized>
+		{	value: &'a T
+		}
+		impl<'a, T: DebugToJson + ?Sized> fmt::Display for Wrapper<'a, T>
+		{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+			{	DebugToJson::fmt(self.value, out)
+			}
+		}
+		out.begin_raw()?;
+		write!(out, "{}", Wrapper {value: self})?;
+		out.end_raw()
+	}
+
+	/// Converts to [Value](enum.Value.html) through [to_sink()](#method.to_sink), so built-in and derived types build the tree directly,
+	/// without producing JSON text. Numbers keep all their digits.
+	/// ```
+	/// use nop_json::{DebugToJson, Value};
+	///
+	/// let v = (1u64 << 63, "a".to_string()).to_value().unwrap();
+	/// assert_eq!(v, Value::Array(vec![Value::Number(1 << 63, 0, false), Value::String("a".to_string())]));
+	/// ```
+	fn to_value(&self) -> io::Result<Value> where Self: std::marker::Sized
+	{	let mut writer = ValueWriter::new();
+		self.to_sink(&mut writer)?;
+		writer.finish()
+	}
 }
 
 impl DebugToJson for ()
 {	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
 	{	write!(out, "null")
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.null()
+	}
 }
 
-impl DebugToJson for isize {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for i128  {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for i64   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for i32   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for i16   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for i8    {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for usize {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for u128  {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for u64   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for u32   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for u16   {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
-impl DebugToJson for u8    {fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {write!(out, "{}", self)}}
+macro_rules! impl_debug_integer
+{	($($t:ty),*) =>
+	{	$(
+			impl DebugToJson for $t
+			{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+				{	write!(out, "{}", self)
+				}
+
+				fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+				{	let value = *self as i128;
+					integer_to_sink(out, value.unsigned_abs(), 0, value < 0)
+				}
+			}
+		)*
+	}
+}
+
+impl_debug_integer!(isize, i128, i64, i32, i16, i8, usize, u64, u32, u16, u8);
+
+impl DebugToJson for u128
+{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+	{	write!(out, "{}", self)
+	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	integer_to_sink(out, *self, 0, false)
+	}
+}
 
 impl DebugToJson for f64
 {	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
@@ -121,9 +210,15 @@ impl DebugToJson for f64
 		{	write!(out, "\"NaN\"")
 		}
 		else
-		{	write!(out, "{}", self)
+		{	let mut buffer = [0u8; READER_BUFFER_SIZE];
+			let len = float_to_string(&mut buffer, *self).map_err(|_| fmt::Error {})?;
+			write!(out, "{}", String::from_utf8_lossy(&buffer[0 .. len]))
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	float_to_sink(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
 }
 impl DebugToJson for f32
 {	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
@@ -137,15 +232,25 @@ impl DebugToJson for f32
 		{	write!(out, "\"NaN\"")
 		}
 		else
-		{	write!(out, "{}", self)
+		{	let mut buffer = [0u8; READER_BUFFER_SIZE];
+			let len = float_to_string(&mut buffer, *self).map_err(|_| fmt::Error {})?;
+			write!(out, "{}", String::from_utf8_lossy(&buffer[0 .. len]))
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	float_to_sink(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
 }
 
 impl DebugToJson for bool
 {	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
 	{	write!(out, "{}", if *self {"true"} else {"false"})
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.bool(*self)
+	}
 }
 
 impl DebugToJson for char
@@ -153,12 +258,31 @@ impl DebugToJson for char
 	{	let mut buffer = [0u8; 4];
 		write!(out, "\"{}\"", escape(self.encode_utf8(&mut buffer)))
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	let mut buffer = [0u8; 4];
+		out.string(self.encode_utf8(&mut buffer))
+	}
 }
 
 impl DebugToJson for String
 {	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
 	{	write!(out, "\"{}\"", escape(&self))
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.string(self)
+	}
+}
+
+impl<const SCALE: u32> DebugToJson for Decimal<SCALE>
+{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+	{	fmt::Display::fmt(self, out)
+	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	integer_to_sink(out, self.mantissa().unsigned_abs(), -(SCALE as i16), self.mantissa() < 0)
+	}
 }
 
 impl DebugToJson for Value
@@ -174,6 +298,9 @@ impl DebugToJson for Value
 				let len = number_to_string(&mut buffer, mantissa.len(), exponent, is_negative).map_err(|_| fmt::Error {})?;
 				write!(out, "{}", String::from_utf8_lossy(&buffer[0 .. len]))
 			},
+			Value::BigNumber(ref digits, exponent, is_negative) =>
+			{	write_number(digits.as_bytes(), exponent, is_negative, |part| write!(out, "{}", String::from_utf8_lossy(part)))
+			},
 			Value::String(ref v) => write!(out, "\"{}\"", escape(v)),
 			Value::Array(ref v) =>
 			{	let mut c = '[';
@@ -205,6 +332,10 @@ impl DebugToJson for Value
 			}
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	value_to_sink(out, self)
+	}
 }
 
 impl<T> DebugToJson for Box<T> where T: DebugToJson
@@ -212,6 +343,10 @@ impl<T> DebugToJson for Box<T> where T: DebugToJson
 	{	let v: &T = &*self;
 		DebugToJson::fmt(v, out)
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	T::to_sink(self, out)
+	}
 }
 
 impl<T> DebugToJson for std::sync::RwLock<T> where T: DebugToJson
@@ -221,6 +356,13 @@ impl<T> DebugToJson for std::sync::RwLock<T> where T: DebugToJson
 			Err(_e) => Err(fmt::Error {})
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	match self.read()
+		{	Ok(v) => v.to_sink(out),
+			Err(e) => Err(io::Error::other(e.to_string())),
+		}
+	}
 }
 
 impl<T> DebugToJson for std::sync::Mutex<T> where T: DebugToJson
@@ -230,6 +372,13 @@ impl<T> DebugToJson for std::sync::Mutex<T> where T: DebugToJson
 			Err(_e) => Err(fmt::Error {})
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	match self.lock()
+		{	Ok(v) => v.to_sink(out),
+			Err(e) => Err(io::Error::other(e.to_string())),
+		}
+	}
 }
 
 impl<T> DebugToJson for std::rc::Rc<T> where T: DebugToJson
@@ -237,6 +386,10 @@ impl<T> DebugToJson for std::rc::Rc<T> where T: DebugToJson
 	{	let v: &T = &*self;
 		DebugToJson::fmt(v, out)
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	T::to_sink(self, out)
+	}
 }
 
 impl<T> DebugToJson for std::sync::Arc<T> where T: DebugToJson
@@ -244,6 +397,10 @@ impl<T> DebugToJson for std::sync::Arc<T> where T: DebugToJson
 	{	let v: &T = &*self;
 		DebugToJson::fmt(v, out)
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	T::to_sink(self, out)
+	}
 }
 
 impl<T> DebugToJson for Option<T> where T: DebugToJson
@@ -253,6 +410,13 @@ impl<T> DebugToJson for Option<T> where T: DebugToJson
 			None => write!(out, "null"),
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	match *self
+		{	Some(ref v) => v.to_sink(out),
+			None => out.null(),
+		}
+	}
 }
 
 impl<T> DebugToJson for Vec<T> where T: DebugToJson
@@ -270,6 +434,14 @@ impl<T> DebugToJson for Vec<T> where T: DebugToJson
 		{	write!(out, "]")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		for item in self
+		{	item.to_sink(out)?;
+		}
+		out.end_array()
+	}
 }
 
 impl<T> DebugToJson for HashSet<T> where T: DebugToJson
@@ -287,6 +459,14 @@ impl<T> DebugToJson for HashSet<T> where T: DebugToJson
 		{	write!(out, "]")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		for item in self
+		{	item.to_sink(out)?;
+		}
+		out.end_array()
+	}
 }
 
 impl<T> DebugToJson for LinkedList<T> where T: DebugToJson
@@ -304,6 +484,14 @@ impl<T> DebugToJson for LinkedList<T> where T: DebugToJson
 		{	write!(out, "]")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		for item in self
+		{	item.to_sink(out)?;
+		}
+		out.end_array()
+	}
 }
 
 impl<T> DebugToJson for VecDeque<T> where T: DebugToJson
@@ -321,6 +509,14 @@ impl<T> DebugToJson for VecDeque<T> where T: DebugToJson
 		{	write!(out, "]")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		for item in self
+		{	item.to_sink(out)?;
+		}
+		out.end_array()
+	}
 }
 
 impl<T> DebugToJson for BTreeSet<T> where T: DebugToJson
@@ -338,6 +534,14 @@ impl<T> DebugToJson for BTreeSet<T> where T: DebugToJson
 		{	write!(out, "]")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		for item in self
+		{	item.to_sink(out)?;
+		}
+		out.end_array()
+	}
 }
 
 impl<T> DebugToJson for HashMap<String, T> where T: DebugToJson
@@ -355,6 +559,15 @@ impl<T> DebugToJson for HashMap<String, T> where T: DebugToJson
 		{	write!(out, "}}")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_object()?;
+		for (key, item) in self
+		{	out.key(key)?;
+			item.to_sink(out)?;
+		}
+		out.end_object()
+	}
 }
 
 impl<T> DebugToJson for BTreeMap<String, T> where T: DebugToJson
@@ -372,6 +585,15 @@ impl<T> DebugToJson for BTreeMap<String, T> where T: DebugToJson
 		{	write!(out, "}}")
 		}
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_object()?;
+		for (key, item) in self
+		{	out.key(key)?;
+			item.to_sink(out)?;
+		}
+		out.end_object()
+	}
 }
 
 impl<A, B> DebugToJson for (A, B) where A: DebugToJson, B: DebugToJson
@@ -382,6 +604,13 @@ impl<A, B> DebugToJson for (A, B) where A: DebugToJson, B: DebugToJson
 		DebugToJson::fmt(&self.1, out)?;
 		write!(out, "]")
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		self.0.to_sink(out)?;
+		self.1.to_sink(out)?;
+		out.end_array()
+	}
 }
 
 impl<A, B, C> DebugToJson for (A, B, C) where A: DebugToJson, B: DebugToJson, C: DebugToJson
@@ -394,4 +623,12 @@ impl<A, B, C> DebugToJson for (A, B, C) where A: DebugToJson, B: DebugToJson, C:
 		DebugToJson::fmt(&self.2, out)?;
 		write!(out, "]")
 	}
+
+	fn to_sink(&self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	out.begin_array()?;
+		self.0.to_sink(out)?;
+		self.1.to_sink(out)?;
+		self.2.to_sink(out)?;
+		out.end_array()
+	}
 }
@@ -0,0 +1,185 @@
+use crate::nop_json::parse_number;
+use crate::value::{Value, ConversionError};
+
+use std::fmt;
+use std::str::FromStr;
+use numtoa::NumToA;
+
+/// What to do when a number has more fractional digits than [Decimal](struct.Decimal.html) scale allows.
+///
+/// Set it for a reader with [ReaderBuilder::decimal_rounding()](struct.ReaderBuilder.html#method.decimal_rounding).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Rounding
+{	/// Don't round, return error. This is the default.
+	#[default]
+	Error,
+	/// Drop extra digits, like `1.299` -> `1.29` and `-1.299` -> `-1.29`.
+	TowardZero,
+	/// Round to the nearest, and ties away from zero, like `1.295` -> `1.30` and `-1.295` -> `-1.30`.
+	HalfAwayFromZero,
+	/// Round to the nearest, and ties to even last digit (banker's rounding), like `1.285` -> `1.28` and `1.295` -> `1.30`.
+	HalfEven,
+}
+
+/// Fixed-point decimal number with `SCALE` digits after the point. It's stored as `i128` mantissa, and equals `mantissa / 10.pow(SCALE)`.
+///
+/// Use it for amounts like prices, that must never pass through `f64`. The number is read directly from the decimal digits of JSON input,
+/// and written back with exactly `SCALE` fractional digits.
+///
+/// ```
+/// use nop_json::{Reader, Decimal, DebugToJson};
+///
+/// let mut reader = Reader::new(r#" [19.99, "0.1", 7] "#.bytes());
+/// let prices: Vec<Decimal<2>> = reader.read().unwrap();
+/// assert_eq!(prices, vec![Decimal::new(1999), Decimal::new(10), Decimal::new(700)]);
+/// assert_eq!(prices.to_json_string(), "[19.99,0.10,7.00]");
+/// ```
+///
+/// If the input has more fractional digits than `SCALE`, reading fails by default.
+/// To round such numbers, set [Rounding](enum.Rounding.html) mode on [ReaderBuilder](struct.ReaderBuilder.html).
+///
+/// ```
+/// use nop_json::{Reader, ReaderBuilder, Decimal, Rounding};
+///
+/// let mut reader = Reader::new(r#" 0.125 "#.bytes());
+/// assert!(reader.read::<Decimal<2>>().is_err());
+///
+/// let mut reader = ReaderBuilder::new().decimal_rounding(Rounding::HalfEven).build(r#" 0.125 "#.bytes());
+/// let price: Decimal<2> = reader.read().unwrap();
+/// assert_eq!(price.to_string(), "0.12");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Decimal<const SCALE: u32>
+{	mantissa: i128
+}
+
+impl<const SCALE: u32> Decimal<SCALE>
+{	/// Creates decimal that equals `mantissa / 10.pow(SCALE)`. So `Decimal::<2>::new(1999)` is `19.99`.
+	pub const fn new(mantissa: i128) -> Self
+	{	Decimal {mantissa}
+	}
+
+	/// The number multiplied by `10.pow(SCALE)`.
+	pub const fn mantissa(&self) -> i128
+	{	self.mantissa
+	}
+
+	/// Number of digits after the point (`SCALE`).
+	pub const fn scale(&self) -> u32
+	{	SCALE
+	}
+
+	/// Converts `Value::Number` (or a string that contains a number) to decimal, rounding extra fractional digits as specified.
+	/// `TryFrom<Value>` does the same with `Rounding::Error`.
+	///
+	/// ```
+	/// use nop_json::{Value, Decimal, Rounding};
+	///
+	/// let v = Value::Number(12345, -3, false); // 12.345
+	/// assert_eq!(Decimal::<2>::from_value(v.clone(), Rounding::TowardZero), Ok(Decimal::new(1234)));
+	/// assert_eq!(Decimal::<2>::from_value(v.clone(), Rounding::HalfAwayFromZero), Ok(Decimal::new(1235)));
+	/// assert!(Decimal::<2>::from_value(v, Rounding::Error).is_err());
+	/// ```
+	pub fn from_value(value: Value, rounding: Rounding) -> Result<Self, ConversionError>
+	{	decimal_from_value(&value, SCALE, rounding).map(Self::new).map_err(ConversionError::new)
+	}
+}
+
+impl<const SCALE: u32> fmt::Display for Decimal<SCALE>
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	let digits = self.mantissa.unsigned_abs().to_string();
+		let scale = SCALE as usize;
+		if self.mantissa < 0
+		{	write!(f, "-")?;
+		}
+		if scale == 0
+		{	write!(f, "{}", digits)
+		}
+		else if digits.len() > scale
+		{	let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+			write!(f, "{}.{}", int_part, frac_part)
+		}
+		else
+		{	write!(f, "0.{:0>width$}", digits, width=scale)
+		}
+	}
+}
+
+impl<const SCALE: u32> fmt::Debug for Decimal<SCALE>
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	fmt::Display::fmt(self, f)
+	}
+}
+
+/// Parses a number in JSON format. Extra fractional digits are not allowed (`Rounding::Error`).
+impl<const SCALE: u32> FromStr for Decimal<SCALE>
+{	type Err = ConversionError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{	Self::from_value(Value::parse_number(s)?, Rounding::Error)
+	}
+}
+
+/// Calculates mantissa of decimal with given `scale` from `value`, that must be number, or string that contains a number.
+/// Null and booleans are converted like on reading integers.
+pub fn decimal_from_value(value: &Value, scale: u32, rounding: Rounding) -> Result<i128, &'static str>
+{	match *value
+	{	Value::Null => Ok(0),
+		Value::Bool(v) => decimal_from_digits(if v {b"1"} else {b""}, 0, false, scale, rounding),
+		Value::Number(mantissa, exponent, is_negative) =>
+		{	let mut buffer = [0u8; 24];
+			decimal_from_digits(mantissa.numtoa(10, &mut buffer), exponent, is_negative, scale, rounding)
+		},
+		Value::BigNumber(ref digits, exponent, is_negative) => decimal_from_digits(digits.as_bytes(), exponent, is_negative, scale, rounding),
+		Value::String(ref v) =>
+		{	let number = parse_number(v).map_err(|_| "Value must be number, not string")?;
+			decimal_from_value(&number, scale, rounding)
+		},
+		Value::Array(ref _v) => Err("Value must be number, not array"),
+		Value::Object(ref _v) => Err("Value must be number, not object"),
+	}
+}
+
+/// Calculates mantissa of decimal with given `scale`, from number `digits * 10.pow(exponent)`.
+pub fn decimal_from_digits(digits: &[u8], exponent: i16, is_negative: bool, scale: u32, rounding: Rounding) -> Result<i128, &'static str>
+{	let shift = exponent as i64 + scale as i64;
+	let (int_len, n_dropped_zeros) = if shift >= 0
+	{	(digits.len(), 0)
+	}
+	else
+	{	let n_dropped = shift.unsigned_abs() as usize;
+		(digits.len().saturating_sub(n_dropped), n_dropped.saturating_sub(digits.len()))
+	};
+	let mut magnitude = 0u128;
+	for &c in &digits[.. int_len]
+	{	magnitude = magnitude.checked_mul(10).and_then(|m| m.checked_add((c - b'0') as u128)).ok_or("Number is too big for decimal")?;
+	}
+	if shift > 0 && magnitude != 0
+	{	let mul = u32::try_from(shift).ok().and_then(|shift| 10u128.checked_pow(shift)).ok_or("Number is too big for decimal")?;
+		magnitude = magnitude.checked_mul(mul).ok_or("Number is too big for decimal")?;
+	}
+	// digits after the last one that fits the scale
+	let dropped = &digits[int_len ..];
+	if dropped.iter().any(|&c| c != b'0')
+	{	let first = if n_dropped_zeros > 0 {b'0'} else {dropped[0]};
+		let is_rest_nonzero = if n_dropped_zeros > 0 {true} else {dropped[1 ..].iter().any(|&c| c != b'0')};
+		let is_round_up = match rounding
+		{	Rounding::Error => return Err("Number has more fractional digits than decimal scale allows"),
+			Rounding::TowardZero => false,
+			Rounding::HalfAwayFromZero => first >= b'5',
+			Rounding::HalfEven => first > b'5' || first == b'5' && (is_rest_nonzero || magnitude % 2 == 1),
+		};
+		if is_round_up
+		{	magnitude = magnitude.checked_add(1).ok_or("Number is too big for decimal")?;
+		}
+	}
+	if !is_negative
+	{	i128::try_from(magnitude).map_err(|_| "Number is too big for decimal")
+	}
+	else if magnitude <= i128::MAX as u128 + 1
+	{	Ok((magnitude as i128).wrapping_neg())
+	}
+	else
+	{	Err("Number is too big for decimal")
+	}
+}
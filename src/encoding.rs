@@ -0,0 +1,263 @@
+use crate::nop_json::Utf8Policy;
+
+const INVALID: u32 = u32::MAX; // incomplete or out of range code unit, transcoded to invalid UTF-8 byte
+
+/// Encoding of the bytes that [Reader](struct.Reader.html) reads. Set it with [ReaderBuilder::encoding()](struct.ReaderBuilder.html#method.encoding).
+///
+/// Input in encodings other than UTF-8 is transcoded to UTF-8 on the fly. Code units that don't form a character
+/// (like lone UTF-16 surrogates) are handled according to [Utf8Policy](enum.Utf8Policy.html), like invalid UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum InputEncoding
+{	/// This is the default.
+	#[default]
+	Utf8,
+	Utf16Le,
+	Utf16Be,
+	Utf32Le,
+	Utf32Be,
+	/// Detect encoding from the byte order mark, or from the pattern of zero bytes in the first 4 bytes, as described in RFC 4627
+	/// (JSON text starts with 2 ASCII characters). The byte order mark is skipped.
+	Auto,
+}
+
+impl InputEncoding
+{	fn bom(self) -> &'static [u8]
+	{	match self
+		{	InputEncoding::Utf8 => &[0xEF, 0xBB, 0xBF],
+			InputEncoding::Utf16Le => &[0xFF, 0xFE],
+			InputEncoding::Utf16Be => &[0xFE, 0xFF],
+			InputEncoding::Utf32Le => &[0xFF, 0xFE, 0, 0],
+			InputEncoding::Utf32Be => &[0, 0, 0xFE, 0xFF],
+			InputEncoding::Auto => &[],
+		}
+	}
+
+	fn detect(start: &[u8]) -> Self
+	{	match start
+		{	[0xEF, 0xBB, 0xBF, ..] => InputEncoding::Utf8,
+			[0xFF, 0xFE, 0, 0] | [_, 0, 0, 0] => InputEncoding::Utf32Le,
+			[0, 0, 0xFE, 0xFF] | [0, 0, 0, _] => InputEncoding::Utf32Be,
+			[0xFF, 0xFE, ..] | [_, 0, ..] => InputEncoding::Utf16Le,
+			[0xFE, 0xFF, ..] | [0, _, ..] => InputEncoding::Utf16Be,
+			_ => InputEncoding::Utf8,
+		}
+	}
+
+	/// Whether more bytes can't change what `detect()` returns for `start`.
+	fn is_detected(start: &[u8]) -> bool
+	{	match start
+		{	[] | [_] => false,
+			[0xFF, 0xFE, ..] | [_, 0, ..] => start.len() >= 4,
+			_ => true,
+		}
+	}
+}
+
+/// The input iterator of [Reader](struct.Reader.html), that skips byte order mark and transcodes to UTF-8 if configured.
+pub struct Input<T> where T: Iterator<Item=u8>
+{	iter: T,
+	encoding: InputEncoding,
+	skip_bom: bool,
+	invalid_utf8: Utf8Policy,
+	is_started: bool,
+	is_utf8: bool, // started, and passes bytes through
+	raw: [u8; 4], // bytes read ahead on detecting encoding
+	raw_pos: usize,
+	raw_len: usize,
+	unit: Option<u32>, // UTF-16 code unit read after a lone surrogate
+	pending: [u8; 4], // UTF-8 bytes of the current character
+	pending_pos: usize,
+	pending_len: usize,
+}
+
+impl<T> Input<T> where T: Iterator<Item=u8>
+{	pub fn new(iter: T, encoding: InputEncoding, skip_bom: bool, invalid_utf8: Utf8Policy) -> Self
+	{	let is_started = encoding!=InputEncoding::Auto && !skip_bom;
+		Input
+		{	iter,
+			encoding,
+			skip_bom,
+			invalid_utf8,
+			is_started,
+			is_utf8: encoding==InputEncoding::Utf8 && is_started,
+			raw: [0u8; 4],
+			raw_pos: 0,
+			raw_len: 0,
+			unit: None,
+			pending: [0u8; 4],
+			pending_pos: 0,
+			pending_len: 0,
+		}
+	}
+
+	/// Returns the underlying iterator. Bytes that were read ahead and not used yet are lost: when encoding is not UTF-8,
+	/// this can be the rest of the current character.
+	pub fn unwrap(self) -> T
+	{	self.iter
+	}
+
+	fn next_slow(&mut self) -> Option<u8>
+	{	if !self.is_started
+		{	self.start();
+			if self.pending_pos < self.pending_len
+			{	self.pending_pos += 1;
+				return Some(self.pending[self.pending_pos - 1]);
+			}
+			if self.is_utf8
+			{	return self.iter.next();
+			}
+		}
+		let c = match self.encoding
+		{	InputEncoding::Utf16Le | InputEncoding::Utf16Be =>
+			{	let c = self.next_u16()?;
+				if (0xD800 ..= 0xDBFF).contains(&c)
+				{	match self.next_u16()
+					{	Some(cc) if (0xDC00 ..= 0xDFFF).contains(&cc) => 0x10000 + (((c-0xD800) << 10) | (cc-0xDC00)),
+						Some(cc) =>
+						{	self.unit = Some(cc); // lone surrogate, the next unit will be read again
+							c
+						}
+						None => c
+					}
+				}
+				else
+				{	c
+				}
+			}
+			_ => self.next_u32()?
+		};
+		if self.invalid_utf8 == Utf8Policy::Replace && (c == INVALID || (0xD800 ..= 0xDFFF).contains(&c))
+		{	return Some(self.encode_utf8(char::REPLACEMENT_CHARACTER as u32));
+		}
+		Some(self.encode_utf8(c))
+	}
+
+	/// Reads the first bytes, to detect encoding and skip byte order mark.
+	/// Reads as few bytes as needed, because the bytes read ahead are lost on `unwrap()`.
+	fn start(&mut self)
+	{	self.is_started = true;
+		if self.encoding == InputEncoding::Auto
+		{	while !InputEncoding::is_detected(&self.raw[.. self.raw_len])
+			{	if !self.read_raw()
+				{	break;
+				}
+			}
+			self.encoding = InputEncoding::detect(&self.raw[.. self.raw_len]);
+			self.skip_bom = true;
+		}
+		let bom = self.encoding.bom();
+		if self.skip_bom
+		{	// read while the bytes match byte order mark
+			while self.raw_len < bom.len() && self.raw[.. self.raw_len] == bom[.. self.raw_len]
+			{	if !self.read_raw()
+				{	break;
+				}
+			}
+			if self.raw[.. self.raw_len].starts_with(bom)
+			{	self.raw_pos = bom.len();
+			}
+		}
+		if self.encoding == InputEncoding::Utf8
+		{	// bytes after the byte order mark are already UTF-8
+			self.pending_len = self.raw_len - self.raw_pos;
+			self.pending[.. self.pending_len].copy_from_slice(&self.raw[self.raw_pos .. self.raw_len]);
+			self.raw_pos = self.raw_len;
+			self.is_utf8 = true;
+		}
+	}
+
+	/// Reads one byte ahead to `raw`. Returns false at the end of input.
+	fn read_raw(&mut self) -> bool
+	{	match self.iter.next()
+		{	Some(c) =>
+			{	self.raw[self.raw_len] = c;
+				self.raw_len += 1;
+				true
+			}
+			None => false
+		}
+	}
+
+	#[inline]
+	fn next_raw(&mut self) -> Option<u8>
+	{	if self.raw_pos < self.raw_len
+		{	self.raw_pos += 1;
+			Some(self.raw[self.raw_pos - 1])
+		}
+		else
+		{	self.iter.next()
+		}
+	}
+
+	fn next_u16(&mut self) -> Option<u32>
+	{	if let Some(c) = self.unit.take()
+		{	return Some(c);
+		}
+		let b0 = self.next_raw()? as u32;
+		let b1 = match self.next_raw()
+		{	Some(b) => b as u32,
+			None => return Some(INVALID)
+		};
+		Some(if self.encoding == InputEncoding::Utf16Le {b0 | (b1 << 8)} else {(b0 << 8) | b1})
+	}
+
+	fn next_u32(&mut self) -> Option<u32>
+	{	let mut bytes = [self.next_raw()? as u32, 0, 0, 0];
+		for b in bytes[1 ..].iter_mut()
+		{	*b = match self.next_raw()
+			{	Some(b) => b as u32,
+				None => return Some(INVALID)
+			};
+		}
+		let c = if self.encoding == InputEncoding::Utf32Le
+		{	bytes[0] | (bytes[1] << 8) | (bytes[2] << 16) | (bytes[3] << 24)
+		}
+		else
+		{	(bytes[0] << 24) | (bytes[1] << 16) | (bytes[2] << 8) | bytes[3]
+		};
+		Some(if c <= 0x10FFFF {c} else {INVALID})
+	}
+
+	/// Returns the first byte of UTF-8 encoding of `c`, and leaves the rest pending.
+	/// Surrogates are encoded like other characters (WTF-8), and `INVALID` becomes byte 0xFF, so they are not valid UTF-8.
+	fn encode_utf8(&mut self, c: u32) -> u8
+	{	if c <= 0x7F
+		{	return c as u8;
+		}
+		if c <= 0x7FF
+		{	self.pending[.. 2].copy_from_slice(&[(0xC0 | (c >> 6)) as u8, (0x80 | (c & 0x3F)) as u8]);
+			self.pending_len = 2;
+		}
+		else if c <= 0xFFFF
+		{	self.pending[.. 3].copy_from_slice(&[(0xE0 | (c >> 12)) as u8, (0x80 | ((c >> 6) & 0x3F)) as u8, (0x80 | (c & 0x3F)) as u8]);
+			self.pending_len = 3;
+		}
+		else if c <= 0x10FFFF
+		{	self.pending = [(0xF0 | (c >> 18)) as u8, (0x80 | ((c >> 12) & 0x3F)) as u8, (0x80 | ((c >> 6) & 0x3F)) as u8, (0x80 | (c & 0x3F)) as u8];
+			self.pending_len = 4;
+		}
+		else
+		{	return 0xFF;
+		}
+		self.pending_pos = 1;
+		self.pending[0]
+	}
+}
+
+impl<T> Iterator for Input<T> where T: Iterator<Item=u8>
+{	type Item = u8;
+
+	#[inline]
+	fn next(&mut self) -> Option<u8>
+	{	if self.pending_pos < self.pending_len
+		{	self.pending_pos += 1;
+			Some(self.pending[self.pending_pos - 1])
+		}
+		else if self.is_utf8
+		{	self.iter.next()
+		}
+		else
+		{	self.next_slow()
+		}
+	}
+}
@@ -1,7 +1,32 @@
 use std::borrow::Cow;
+use std::io;
 
 const HEX_DIGITS: [u8; 16] = [b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'A', b'B', b'C', b'D', b'E', b'F'];
 
+/// Which characters to escape in JSON strings, in addition to `"`, `\` and control characters (that are always escaped).
+///
+/// Use with [escape_with()](fn.escape_with.html), [escape_bytes_with()](fn.escape_bytes_with.html) and [Formatting::escaping()](struct.Formatting.html#method.escaping).
+///
+/// ```
+/// use nop_json::{escape_with, Escaping};
+///
+/// assert_eq!(escape_with("</script>", Escaping::Html), "\\u003C/script\\u003E");
+/// assert_eq!(escape_with("a\u{2028}b", Escaping::JavaScript), "a\\u2028b");
+/// assert_eq!(escape_with("€ 😀", Escaping::Ascii), "\\u20AC \\uD83D\\uDE00");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Escaping
+{	/// Only what JSON requires.
+	#[default]
+	Minimal,
+	/// All non-ASCII characters as `\uXXXX` (characters above `U+FFFF` as surrogate pairs), so the output is pure ASCII.
+	Ascii,
+	/// Also `<`, `>`, `&` and `'`, so the JSON can be embedded in HTML.
+	Html,
+	/// Like `Html`, and also `U+2028` and `U+2029` (that are line terminators in older JavaScript), so the JSON can be embedded in `<script>` tags.
+	JavaScript,
+}
+
 /// Adds slashes before `"` and `\` characters, converts `\t`, `\r`, `\n`, `\b`, `\f` characters as needed,
 /// and encodes characters with codes less than space (32) with `\u00XX`.
 /// If input string doesn't contain something that JSON standard wants us to escape, it just returns the input string
@@ -15,75 +40,166 @@ const HEX_DIGITS: [u8; 16] = [b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'
 /// assert_eq!(json_encoded, "{\"value\": \"Some \\\"quote\\\" and some \\\\.\"}");
 /// ```
 pub fn escape(s: &str) -> Cow<'_, str>
-{	let bytes = s.as_bytes();
-	if let Some(pos) = bytes.iter().position(|c| match *c {b'"' | b'\\' | 0..=31 => true, _ => false})
-	{	Cow::Owned(String::from_utf8(do_escape_bytes(bytes, pos)).unwrap())
-	}
-	else
-	{	Cow::Borrowed(s)
-	}
+{	escape_with(s, Escaping::Minimal)
 }
 
 /// Like [escape](fn.escape.html), but for `&[u8]`.
 pub fn escape_bytes(bytes: &[u8]) -> Cow<'_, [u8]>
-{	if let Some(pos) = bytes.iter().position(|c| match *c {b'"' | b'\\' | 0..=31 => true, _ => false})
-	{	Cow::Owned(do_escape_bytes(bytes, pos))
+{	escape_bytes_with(bytes, Escaping::Minimal)
+}
+
+/// Like [escape](fn.escape.html), but also escapes characters according to the given [Escaping](enum.Escaping.html) profile.
+pub fn escape_with(s: &str, escaping: Escaping) -> Cow<'_, str>
+{	match escape_bytes_with(s.as_bytes(), escaping)
+	{	Cow::Borrowed(_) => Cow::Borrowed(s),
+		Cow::Owned(v) => Cow::Owned(String::from_utf8(v).unwrap()),
+	}
+}
+
+/// Like [escape_bytes](fn.escape_bytes.html), but also escapes characters according to the given [Escaping](enum.Escaping.html) profile.
+/// Bytes that are not part of valid UTF-8 sequences are left as is.
+pub fn escape_bytes_with(bytes: &[u8], escaping: Escaping) -> Cow<'_, [u8]>
+{	if let Some(pos) = bytes.iter().position(|c| is_special(*c, escaping))
+	{	Cow::Owned(do_escape_bytes(bytes, pos, escaping))
 	}
 	else
 	{	Cow::Borrowed(bytes)
 	}
 }
 
-fn do_escape_bytes(bytes: &[u8], mut pos: usize) -> Vec<u8>
-{	let mut buffer = Vec::with_capacity(bytes.len() + 8);
-	let mut from = 0;
-	loop
-	{	buffer.extend_from_slice(&bytes[from .. pos]);
-		let c = bytes[pos];
-		if c >= 32
-		{	buffer.push(b'\\');
-			buffer.push(c);
+/// Whether the byte needs escaping, or (for non-ASCII) is the first byte of a character that may need escaping.
+fn is_special(c: u8, escaping: Escaping) -> bool
+{	match c
+	{	b'"' | b'\\' | 0..=31 => true,
+		b'<' | b'>' | b'&' | b'\'' => escaping == Escaping::Html || escaping == Escaping::JavaScript,
+		0x80..=0xFF => escaping == Escaping::Ascii || escaping == Escaping::JavaScript && c == 0xE2,
+		_ => false
+	}
+}
+
+/// Writes `\uXXXX` escape of UTF-16 code unit `c` to `seq`.
+fn put_unicode_escape(seq: &mut [u8], c: u16)
+{	seq[0] = b'\\';
+	seq[1] = b'u';
+	seq[2] = HEX_DIGITS[(c >> 12 & 0xF) as usize];
+	seq[3] = HEX_DIGITS[(c >> 8 & 0xF) as usize];
+	seq[4] = HEX_DIGITS[(c >> 4 & 0xF) as usize];
+	seq[5] = HEX_DIGITS[(c & 0xF) as usize];
+}
+
+/// Length of UTF-8 sequence that starts with byte `c`. Bytes that cannot start a sequence count as 1.
+pub(crate) fn utf8_len(c: u8) -> usize
+{	match c
+	{	0xC0..=0xDF => 2,
+		0xE0..=0xEF => 3,
+		0xF0..=0xF7 => 4,
+		_ => 1
+	}
+}
+
+/// Decodes UTF-8 character at the beginning of `bytes`. Returns the character and it's length, or `None` if the bytes are not valid UTF-8.
+fn decode_char(bytes: &[u8]) -> Option<(char, usize)>
+{	let len = utf8_len(bytes[0]);
+	let s = std::str::from_utf8(bytes.get(.. len)?).ok()?;
+	s.chars().next().map(|c| (c, len))
+}
+
+/// Escape sequence for ASCII character `c`, for which `is_special()` returned true.
+fn ascii_escape(c: u8, seq: &mut [u8; 12]) -> &[u8]
+{	match c
+	{	b'"' => b"\\\"",
+		b'\\' => b"\\\\",
+		9 => b"\\t",
+		13 => b"\\r",
+		10 => b"\\n",
+		8 => b"\\b",
+		12 => b"\\f",
+		_ =>
+		{	put_unicode_escape(seq, c as u16);
+			&seq[.. 6]
 		}
-		else
-		{	match c
-			{	9 =>
-				{	buffer.push(b'\\');
-					buffer.push(b't');
-				}
-				13 =>
-				{	buffer.push(b'\\');
-					buffer.push(b'r');
-				}
-				10 =>
-				{	buffer.push(b'\\');
-					buffer.push(b'n');
-				}
-				8 =>
-				{	buffer.push(b'\\');
-					buffer.push(b'b');
-				}
-				12 =>
-				{	buffer.push(b'\\');
-					buffer.push(b'f');
-				}
-				_ =>
-				{	buffer.push(b'\\');
-					buffer.push(b'u');
-					buffer.push(b'0');
-					buffer.push(b'0');
-					buffer.push(HEX_DIGITS[(c >> 4) as usize]);
-					buffer.push(HEX_DIGITS[(c & 0xF) as usize]);
-				}
+	}
+}
+
+/// For the character at the beginning of `bytes`, for which `is_special()` returned true, returns what to write instead of it, and how many bytes of input it takes.
+/// Non-ASCII characters are escaped only if `escaping` requires, and bytes that are not valid UTF-8 are left as is.
+fn escape_char<'a>(bytes: &'a [u8], escaping: Escaping, seq: &'a mut [u8; 12]) -> (&'a [u8], usize)
+{	let c = bytes[0];
+	if c < 0x80
+	{	return (ascii_escape(c, seq), 1);
+	}
+	match decode_char(bytes)
+	{	Some((c, len)) if escaping == Escaping::Ascii || c == '\u{2028}' || c == '\u{2029}' =>
+		{	let mut utf16 = [0; 2];
+			let units = c.encode_utf16(&mut utf16);
+			for (i, unit) in units.iter().enumerate()
+			{	put_unicode_escape(&mut seq[i*6 ..], *unit);
 			}
+			(&seq[.. units.len()*6], len)
 		}
-		from = pos + 1;
-		if let Some(new_pos) = &bytes[from ..].iter().position(|c| match *c {b'"' | b'\\' | 0..=31 => true, _ => false})
-		{	pos = from + *new_pos;
-		}
-		else
-		{	buffer.extend_from_slice(&bytes[from .. ]);
-			break;
-		}
+		Some((_, len)) => (&bytes[.. len], len),
+		None => (&bytes[.. 1], 1)
 	}
+}
+
+fn do_escape_bytes(bytes: &[u8], pos: usize, escaping: Escaping) -> Vec<u8>
+{	let mut buffer = Vec::with_capacity(bytes.len() + 8);
+	buffer.extend_from_slice(&bytes[.. pos]);
+	push_escaped(&mut buffer, &bytes[pos ..], escaping);
 	buffer
 }
+
+/// Appends `bytes` escaped according to `escaping` to `buffer`.
+pub(crate) fn push_escaped(buffer: &mut Vec<u8>, bytes: &[u8], escaping: Escaping)
+{	let mut from = 0;
+	while let Some(pos) = bytes[from ..].iter().position(|c| is_special(*c, escaping))
+	{	let pos = from + pos;
+		buffer.extend_from_slice(&bytes[from .. pos]);
+		let mut seq = [0u8; 12];
+		let (part, len) = escape_char(&bytes[pos ..], escaping, &mut seq);
+		buffer.extend_from_slice(part);
+		from = pos + len;
+	}
+	buffer.extend_from_slice(&bytes[from ..]);
+}
+
+/// Size of the stack buffer that [write_json_string()] collects output in, before passing it to the writer.
+const WRITE_BUFFER_SIZE: usize = 256;
+
+/// Writes `bytes` as a quoted JSON string, escaped like [escape_bytes()](fn.escape_bytes.html) does, directly to `out`, without allocating memory.
+/// The output is collected in a stack buffer, so a short string is passed to the writer in one call.
+pub fn write_json_string<W>(out: &mut W, bytes: &[u8]) -> io::Result<()> where W: io::Write
+{	write_escaped(out, b"\"", bytes, b"\"", Escaping::Minimal)
+}
+
+/// Writes `prefix`, then `bytes` escaped like [escape_bytes_with()](fn.escape_bytes_with.html) does, and then `suffix`, collecting the output in a stack buffer.
+pub fn write_escaped<W>(out: &mut W, prefix: &[u8], bytes: &[u8], suffix: &[u8], escaping: Escaping) -> io::Result<()> where W: io::Write
+{	let mut buffer = [0u8; WRITE_BUFFER_SIZE];
+	let mut len = 0;
+	{	let mut push = |out: &mut W, part: &[u8]| -> io::Result<()>
+		{	if len + part.len() > WRITE_BUFFER_SIZE
+			{	out.write_all(&buffer[.. len])?;
+				len = 0;
+				if part.len() > WRITE_BUFFER_SIZE
+				{	return out.write_all(part);
+				}
+			}
+			buffer[len .. len+part.len()].copy_from_slice(part);
+			len += part.len();
+			Ok(())
+		};
+		push(out, prefix)?;
+		let mut from = 0;
+		while let Some(pos) = bytes[from ..].iter().position(|c| is_special(*c, escaping))
+		{	let pos = from + pos;
+			push(out, &bytes[from .. pos])?;
+			let mut seq = [0u8; 12];
+			let (part, len) = escape_char(&bytes[pos ..], escaping, &mut seq);
+			push(out, part)?;
+			from = pos + len;
+		}
+		push(out, &bytes[from ..])?;
+		push(out, suffix)?;
+	}
+	out.write_all(&buffer[.. len])
+}
@@ -0,0 +1,120 @@
+/// Builds [Value](enum.Value.html) from JSON-like literal.
+///
+/// Any Rust expression that can be converted to `Value` (with `TryFrom`) can be interpolated in place of a value,
+/// and expression in parentheses can be used as object key.
+///
+/// ```
+/// use nop_json::{json, Value};
+///
+/// let name = "John";
+/// let age = 30;
+/// let key = "tags";
+/// let v = json!
+/// ({	"name": name,
+/// 	"age": age + 1,
+/// 	"height": 1.85,
+/// 	"balance": -12.5,
+/// 	"admin": false,
+/// 	"spouse": null,
+/// 	(key): ["a", "b"],
+/// 	"address": {"city": "Haifa", "zip": [3, 1, 0]},
+/// });
+/// assert_eq!(v["name"], Value::String("John".to_string()));
+/// assert_eq!(v["age"], Value::Number(31, 0, false));
+/// assert_eq!(v["balance"], Value::Number(125, -1, true));
+/// assert_eq!(v["tags"][1], Value::String("b".to_string()));
+/// assert_eq!(v["address"]["zip"].to_string(), "[3,1,0]");
+/// ```
+///
+/// # Panics
+///
+/// If interpolated expression cannot be converted to `Value`.
+#[macro_export]
+macro_rules! json
+{	// Array: [$($elems,)*] are already built elements, and the rest are tokens yet to parse.
+	(@array [$($elems:expr,)*]) =>
+	{	vec![$($elems,)*]
+	};
+	(@array [$($elems:expr),*]) =>
+	{	vec![$($elems),*]
+	};
+	(@array [$($elems:expr,)*] null $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!(null)] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] true $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!(true)] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] false $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!(false)] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!([$($array)*])] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!({$($object)*})] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!($next),] $($rest)*)
+	};
+	(@array [$($elems:expr,)*] $last:expr) =>
+	{	$crate::json!(@array [$($elems,)* $crate::json!($last)])
+	};
+	(@array [$($elems:expr),*] , $($rest:tt)*) =>
+	{	$crate::json!(@array [$($elems,)*] $($rest)*)
+	};
+
+	// Object: insert entries to $object one by one.
+	(@object $object:ident ()) => {};
+	(@object $object:ident ($key:tt : null $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!(null)) ($($($rest)*)?));
+	};
+	(@object $object:ident ($key:tt : true $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!(true)) ($($($rest)*)?));
+	};
+	(@object $object:ident ($key:tt : false $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!(false)) ($($($rest)*)?));
+	};
+	(@object $object:ident ($key:tt : [$($array:tt)*] $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!([$($array)*])) ($($($rest)*)?));
+	};
+	(@object $object:ident ($key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!({$($inner)*})) ($($($rest)*)?));
+	};
+	(@object $object:ident ($key:tt : $value:expr $(, $($rest:tt)*)?)) =>
+	{	$crate::json!(@insert $object $key ($crate::json!($value)) ($($($rest)*)?));
+	};
+	(@insert $object:ident $key:tt ($value:expr) ($($rest:tt)*)) =>
+	{	$object.insert(::std::string::ToString::to_string(&$key), $value);
+		$crate::json!(@object $object ($($rest)*));
+	};
+
+	(null) =>
+	{	$crate::Value::Null
+	};
+	(true) =>
+	{	$crate::Value::Bool(true)
+	};
+	(false) =>
+	{	$crate::Value::Bool(false)
+	};
+	([]) =>
+	{	$crate::Value::Array(::std::vec::Vec::new())
+	};
+	([ $($tt:tt)+ ]) =>
+	{	$crate::Value::Array($crate::json!(@array [] $($tt)+))
+	};
+	({}) =>
+	{	$crate::Value::Object($crate::Map::new())
+	};
+	({ $($tt:tt)+ }) =>
+	{	{	let mut object = $crate::Map::new();
+			$crate::json!(@object object ($($tt)+));
+			$crate::Value::Object(object)
+		}
+	};
+	($other:expr) =>
+	{	{	let value: ::std::option::Option<$crate::Value> = ::std::convert::TryFrom::try_from($other).ok();
+			value.expect("json!: value cannot be converted to Value")
+		}
+	};
+}
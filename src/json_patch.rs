@@ -0,0 +1,266 @@
+use crate::value::{Value, unescape_pointer_token, parse_pointer_index};
+use crate::value_map::Map;
+
+use std::fmt;
+use std::error::Error;
+
+/// Error returned by [Value::apply_patch()](enum.Value.html#method.apply_patch).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError
+{	index: usize,
+	message: String,
+}
+
+impl PatchError
+{	/// Index of the failed operation in the patch array.
+	pub fn index(&self) -> usize
+	{	self.index
+	}
+
+	/// Description of the problem.
+	pub fn message(&self) -> &str
+	{	&self.message
+	}
+}
+
+impl fmt::Display for PatchError
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	write!(f, "Patch operation {}: {}", self.index, self.message)
+	}
+}
+
+impl Error for PatchError {}
+
+impl Value
+{	/// Applies JSON Patch ([RFC 6902](https://tools.ietf.org/html/rfc6902)) to this value.
+	///
+	/// The `patch` must be an array of operations: `add`, `remove`, `replace`, `move`, `copy` and `test`, with paths in JSON Pointer format.
+	/// The patch is applied atomically: if any operation fails, this value remains unchanged, and the returned error tells the operation index.
+	///
+	/// ```
+	/// use nop_json::json;
+	///
+	/// let mut doc = json!({"name": "John", "tags": ["a"]});
+	/// let patch = json!
+	/// ([	{"op": "replace", "path": "/name", "value": "Jane"},
+	/// 	{"op": "add", "path": "/tags/-", "value": "b"},
+	/// ]);
+	/// doc.apply_patch(&patch).unwrap();
+	/// assert_eq!(doc, json!({"name": "Jane", "tags": ["a", "b"]}));
+	///
+	/// let patch = json!
+	/// ([	{"op": "remove", "path": "/tags/0"},
+	/// 	{"op": "test", "path": "/name", "value": "John"},
+	/// ]);
+	/// let error = doc.apply_patch(&patch).unwrap_err();
+	/// assert_eq!(error.index(), 1);
+	/// assert_eq!(doc, json!({"name": "Jane", "tags": ["a", "b"]})); // unchanged
+	/// ```
+	pub fn apply_patch(&mut self, patch: &Value) -> Result<(), PatchError>
+	{	let operations = match *patch
+		{	Value::Array(ref v) => v,
+			_ => return Err(PatchError {index: 0, message: format!("Patch must be array, not {}", patch.type_name())})
+		};
+		let mut result = self.clone();
+		for (index, operation) in operations.iter().enumerate()
+		{	result.apply_patch_operation(operation).map_err(|message| PatchError {index, message})?;
+		}
+		*self = result;
+		Ok(())
+	}
+
+	fn apply_patch_operation(&mut self, operation: &Value) -> Result<(), String>
+	{	let op = patch_member(operation, "op")?.as_str().ok_or_else(|| "Member \"op\" must be string".to_string())?;
+		let path = patch_member(operation, "path")?.as_str().ok_or_else(|| "Member \"path\" must be string".to_string())?;
+		match op
+		{	"add" =>
+			{	let value = patch_member(operation, "value")?.clone();
+				self.patch_add(path, value)
+			}
+			"remove" =>
+			{	self.patch_remove(path).map(|_| ())
+			}
+			"replace" =>
+			{	let value = patch_member(operation, "value")?.clone();
+				let target = self.pointer_mut(path).ok_or_else(|| format!("Path \"{}\" doesn't exist", path))?;
+				*target = value;
+				Ok(())
+			}
+			"move" =>
+			{	let from = patch_member(operation, "from")?.as_str().ok_or_else(|| "Member \"from\" must be string".to_string())?;
+				if from != path
+				{	if path.len() > from.len() && path.starts_with(from) && path.as_bytes()[from.len()] == b'/'
+					{	return Err(format!("Cannot move \"{}\" to it's child \"{}\"", from, path));
+					}
+					let value = self.patch_remove(from)?;
+					self.patch_add(path, value)?;
+				}
+				else if self.pointer(from).is_none()
+				{	return Err(format!("Path \"{}\" doesn't exist", from));
+				}
+				Ok(())
+			}
+			"copy" =>
+			{	let from = patch_member(operation, "from")?.as_str().ok_or_else(|| "Member \"from\" must be string".to_string())?;
+				let value = self.pointer(from).ok_or_else(|| format!("Path \"{}\" doesn't exist", from))?.clone();
+				self.patch_add(path, value)
+			}
+			"test" =>
+			{	let value = patch_member(operation, "value")?;
+				let target = self.pointer(path).ok_or_else(|| format!("Path \"{}\" doesn't exist", path))?;
+				if target != value
+				{	return Err(format!("Test failed: value at \"{}\" is {}, not {}", path, target, value));
+				}
+				Ok(())
+			}
+			_ => Err(format!("Unknown operation \"{}\"", op))
+		}
+	}
+
+	fn patch_add(&mut self, path: &str, value: Value) -> Result<(), String>
+	{	let (parent, token) = match path.rfind('/')
+		{	None if path.is_empty() =>
+			{	*self = value;
+				return Ok(());
+			}
+			None => return Err(format!("Invalid JSON Pointer \"{}\"", path)),
+			Some(pos) => (&path[.. pos], &path[pos+1 ..])
+		};
+		let target = self.pointer_mut(parent).ok_or_else(|| format!("Path \"{}\" doesn't exist", parent))?;
+		match *target
+		{	Value::Object(ref mut v) =>
+			{	v.insert(unescape_pointer_token(token).into_owned(), value);
+			}
+			Value::Array(ref mut v) =>
+			{	if token == "-"
+				{	v.push(value);
+				}
+				else
+				{	match parse_pointer_index(token)
+					{	Some(index) if index <= v.len() => v.insert(index, value),
+						_ => return Err(format!("Invalid array index in \"{}\"", path))
+					}
+				}
+			}
+			_ => return Err(format!("Cannot add to {} at \"{}\"", target.type_name(), parent))
+		}
+		Ok(())
+	}
+
+	fn patch_remove(&mut self, path: &str) -> Result<Value, String>
+	{	let (parent, token) = match path.rfind('/')
+		{	None => return Err(format!("Cannot remove \"{}\"", path)),
+			Some(pos) => (&path[.. pos], &path[pos+1 ..])
+		};
+		let target = self.pointer_mut(parent).ok_or_else(|| format!("Path \"{}\" doesn't exist", parent))?;
+		let removed = match *target
+		{	Value::Object(ref mut v) => v.remove(unescape_pointer_token(token).as_ref()),
+			Value::Array(ref mut v) => parse_pointer_index(token).filter(|&index| index < v.len()).map(|index| v.remove(index)),
+			_ => None
+		};
+		removed.ok_or_else(|| format!("Path \"{}\" doesn't exist", path))
+	}
+
+	/// Produces JSON Patch ([RFC 6902](https://tools.ietf.org/html/rfc6902)), that turns `old` into `new` when applied with
+	/// [apply_patch()](enum.Value.html#method.apply_patch). Objects are compared recursively, and arrays - element by element,
+	/// so inserting in the beginning of array produces a change for each element (the patch is correct, but not always the shortest).
+	///
+	/// ```
+	/// use nop_json::{json, Value};
+	///
+	/// let old = json!({"a": 1, "b": [1, 2, 3], "c": "x"});
+	/// let new = json!({"a": 2, "b": [1, 2], "d": "x"});
+	/// let patch = Value::diff_patch(&old, &new);
+	/// assert_eq!
+	/// (	patch,
+	/// 	json!
+	/// 	([	{"op": "replace", "path": "/a", "value": 2},
+	/// 		{"op": "remove", "path": "/b/2"},
+	/// 		{"op": "remove", "path": "/c"},
+	/// 		{"op": "add", "path": "/d", "value": "x"},
+	/// 	])
+	/// );
+	///
+	/// let mut doc = old.clone();
+	/// doc.apply_patch(&patch).unwrap();
+	/// assert_eq!(doc, new);
+	/// ```
+	pub fn diff_patch(old: &Value, new: &Value) -> Value
+	{	let mut operations = Vec::new();
+		let mut path = String::new();
+		diff_patch_into(old, new, &mut path, &mut operations);
+		Value::Array(operations)
+	}
+}
+
+fn patch_member<'a>(operation: &'a Value, name: &str) -> Result<&'a Value, String>
+{	match *operation
+	{	Value::Object(ref v) => v.get(name).ok_or_else(|| format!("Member \"{}\" is missing", name)),
+		_ => Err(format!("Operation must be object, not {}", operation.type_name()))
+	}
+}
+
+fn patch_operation(op: &str, path: &str, value: Option<&Value>) -> Value
+{	let mut obj = Map::with_capacity(3);
+	obj.insert("op".to_string(), Value::String(op.to_string()));
+	obj.insert("path".to_string(), Value::String(path.to_string()));
+	if let Some(value) = value
+	{	obj.insert("value".to_string(), value.clone());
+	}
+	Value::Object(obj)
+}
+
+/// Appends JSON Pointer reference token to `path`, encoding `~` as `~0` and `/` as `~1`.
+pub fn push_pointer_token(path: &mut String, token: &str)
+{	path.push('/');
+	for c in token.chars()
+	{	match c
+		{	'~' => path.push_str("~0"),
+			'/' => path.push_str("~1"),
+			_ => path.push(c)
+		}
+	}
+}
+
+fn diff_patch_into(old: &Value, new: &Value, path: &mut String, operations: &mut Vec<Value>)
+{	if old == new
+	{	return;
+	}
+	let path_len = path.len();
+	match (old, new)
+	{	(Value::Object(old), Value::Object(new)) =>
+		{	let mut keys: Vec<&String> = old.keys().chain(new.keys().filter(|key| !old.contains_key(key))).collect();
+			keys.sort_unstable();
+			for key in keys
+			{	push_pointer_token(path, key);
+				match (old.get(key), new.get(key))
+				{	(Some(old_value), Some(new_value)) => diff_patch_into(old_value, new_value, path, operations),
+					(Some(_), None) => operations.push(patch_operation("remove", path, None)),
+					(None, Some(new_value)) => operations.push(patch_operation("add", path, Some(new_value))),
+					(None, None) => {}
+				}
+				path.truncate(path_len);
+			}
+		}
+		(Value::Array(old), Value::Array(new)) =>
+		{	for (i, (old_value, new_value)) in old.iter().zip(new.iter()).enumerate()
+			{	push_pointer_token(path, &i.to_string());
+				diff_patch_into(old_value, new_value, path, operations);
+				path.truncate(path_len);
+			}
+			for i in (new.len() .. old.len()).rev()
+			{	push_pointer_token(path, &i.to_string());
+				operations.push(patch_operation("remove", path, None));
+				path.truncate(path_len);
+			}
+			for (i, new_value) in new.iter().enumerate().skip(old.len())
+			{	push_pointer_token(path, &i.to_string());
+				operations.push(patch_operation("add", path, Some(new_value)));
+				path.truncate(path_len);
+			}
+		}
+		_ =>
+		{	operations.push(patch_operation("replace", path, Some(new)));
+		}
+	}
+}
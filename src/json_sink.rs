@@ -0,0 +1,107 @@
+use crate::value::Value;
+use crate::nop_json::{float_to_digits, READER_BUFFER_SIZE};
+
+use std::{fmt, io};
+use numtoa::NumToA;
+
+/// Writer that receives JSON as structure (arrays, objects, keys and scalar values), not as text.
+///
+/// [ValueWriter](struct.ValueWriter.html) implements it to build [Value](enum.Value.html) without parsing.
+/// [WriteToJson::write_to_sink()](trait.WriteToJson.html#method.write_to_sink) and [DebugToJson::to_sink()](trait.DebugToJson.html#method.to_sink)
+/// call these methods for all built-in types, and for types with `#[derive(WriteToJson)]` and `#[derive(DebugToJson)]`.
+/// Array elements and object properties are separated by the sink itself.
+///
+/// Types that have only `write_to_json()` or `fmt()` are written as text between `begin_raw()` and `end_raw()`,
+/// and the sink must accept one complete JSON value through its `io::Write` implementation in between.
+///
+/// ```
+/// use nop_json::{JsonSink, ValueWriter, json};
+/// use std::io::Write;
+///
+/// let mut writer = ValueWriter::new();
+/// writer.begin_object().unwrap();
+/// writer.key("a").unwrap();
+/// writer.number(b"15", -1, true).unwrap();
+/// writer.key("b").unwrap();
+/// writer.begin_raw().unwrap();
+/// writer.write_all(b"[true, null]").unwrap();
+/// writer.end_raw().unwrap();
+/// writer.end_object().unwrap();
+/// assert_eq!(writer.finish().unwrap(), json!({"a": -1.5, "b": [true, null]}));
+/// ```
+pub trait JsonSink: io::Write
+{	fn begin_array(&mut self) -> io::Result<()>;
+
+	fn end_array(&mut self) -> io::Result<()>;
+
+	fn begin_object(&mut self) -> io::Result<()>;
+
+	/// Key of the next object property. Its value must follow.
+	fn key(&mut self, key: &str) -> io::Result<()>;
+
+	fn end_object(&mut self) -> io::Result<()>;
+
+	fn null(&mut self) -> io::Result<()>;
+
+	fn bool(&mut self, value: bool) -> io::Result<()>;
+
+	/// Number that equals `(if is_negative {-1} else {1}) * digits * 10.pow(exponent)`, where `digits` is the mantissa written in decimal
+	/// (like in [Value::Number](enum.Value.html#variant.Number)). Empty `digits` mean `0`.
+	fn number(&mut self, digits: &[u8], exponent: i16, is_negative: bool) -> io::Result<()>;
+
+	fn string(&mut self, value: &str) -> io::Result<()>;
+
+	/// After this call, one JSON value is written as text through `io::Write`, and then `end_raw()` is called.
+	fn begin_raw(&mut self) -> io::Result<()>;
+
+	fn end_raw(&mut self) -> io::Result<()>;
+}
+
+/// Passes integer with given magnitude and sign to `out.number()`.
+pub(crate) fn integer_to_sink(out: &mut dyn JsonSink, magnitude: u128, exponent: i16, is_negative: bool) -> io::Result<()>
+{	let mut buffer = [0u8; 40];
+	if let Ok(magnitude) = u64::try_from(magnitude)
+	{	return out.number(magnitude.numtoa(10, &mut buffer), exponent, is_negative);
+	}
+	out.number(magnitude.numtoa(10, &mut buffer), exponent, is_negative)
+}
+
+/// Passes float to `out.number()`, in the shortest form that reads back to the same bits. Infinity and NaN become strings, like in `write_to_json()`.
+pub(crate) fn float_to_sink<F>(out: &mut dyn JsonSink, value: F, is_infinite: bool, is_negative: bool, is_nan: bool) -> io::Result<()> where F: fmt::LowerExp
+{	if is_nan
+	{	out.string("NaN")
+	}
+	else if is_infinite
+	{	out.string(if is_negative {"-Infinity"} else {"Infinity"})
+	}
+	else
+	{	let mut buffer = [0u8; READER_BUFFER_SIZE];
+		let (len, exponent, is_negative) = float_to_digits(&mut buffer, value).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Cannot format number"))?;
+		out.number(&buffer[.. len], exponent, is_negative)
+	}
+}
+
+pub(crate) fn value_to_sink(out: &mut dyn JsonSink, value: &Value) -> io::Result<()>
+{	match *value
+	{	Value::Null => out.null(),
+		Value::Bool(v) => out.bool(v),
+		Value::Number(mantissa, exponent, is_negative) => integer_to_sink(out, mantissa as u128, exponent, is_negative),
+		Value::BigNumber(ref digits, exponent, is_negative) => out.number(digits.as_bytes(), exponent, is_negative),
+		Value::String(ref v) => out.string(v),
+		Value::Array(ref v) =>
+		{	out.begin_array()?;
+			for item in v
+			{	value_to_sink(out, item)?;
+			}
+			out.end_array()
+		}
+		Value::Object(ref v) =>
+		{	out.begin_object()?;
+			for (key, item) in v
+			{	out.key(key)?;
+				value_to_sink(out, item)?;
+			}
+			out.end_object()
+		}
+	}
+}
@@ -0,0 +1,186 @@
+use crate::write_to_json::WriteToJson;
+use crate::pretty::{Formatting, FormatWriter};
+use crate::escape::write_json_string;
+use crate::nop_json::is_single_value;
+
+use std::io;
+use std::io::Write;
+
+enum Level
+{	Array {is_empty: bool},
+	Object {is_empty: bool, has_key: bool},
+}
+
+/// Writes JSON step by step: begin and end arrays and objects, and write keys and values in between.
+///
+/// Use it to produce JSON that doesn't correspond to a Rust type, like rows coming from a database cursor.
+/// The writer puts commas and colons, and returns error on misuse: value in object without key, key outside object, `end_*()` that doesn't match `begin_*()`,
+/// second top-level value, or [finish()](#method.finish) without a value or with unclosed arrays or objects. On error nothing is written.
+///
+/// ```
+/// use nop_json::{JsonWriter, Formatting};
+///
+/// let mut writer = JsonWriter::new(Vec::new());
+/// writer.begin_object().unwrap();
+/// writer.key("rows").unwrap();
+/// writer.begin_array().unwrap();
+/// for (id, name) in [(1, "one"), (2, "two")]
+/// {	writer.begin_object().unwrap();
+/// 	writer.key("id").unwrap();
+/// 	writer.value(&id).unwrap();
+/// 	writer.key("name").unwrap();
+/// 	writer.value(&name.to_string()).unwrap();
+/// 	writer.end_object().unwrap();
+/// }
+/// writer.end_array().unwrap();
+/// writer.key("total").unwrap();
+/// writer.raw("2").unwrap();
+/// assert!(writer.value(&3).is_err()); // no key
+/// writer.end_object().unwrap();
+/// let out = writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), r#"{"rows":[{"id":1,"name":"one"},{"id":2,"name":"two"}],"total":2}"#);
+///
+/// let mut writer = JsonWriter::with_formatting(Vec::new(), Formatting::pretty());
+/// writer.begin_array().unwrap();
+/// writer.value(&true).unwrap();
+/// writer.end_array().unwrap();
+/// assert_eq!(writer.finish().unwrap(), b"[\n  true\n]");
+/// ```
+pub struct JsonWriter<W: io::Write>
+{	out: FormatWriter<W>,
+	stack: Vec<Level>,
+	has_root: bool,
+}
+
+impl<W: io::Write> JsonWriter<W>
+{	/// Writer that produces compact JSON.
+	pub fn new(out: W) -> Self
+	{	Self::with_formatting(out, Formatting::compact())
+	}
+
+	/// Writer that formats JSON according to the given settings, like [FormatWriter](struct.FormatWriter.html) does.
+	pub fn with_formatting(out: W, formatting: Formatting) -> Self
+	{	JsonWriter {out: formatting.build(out), stack: Vec::new(), has_root: false}
+	}
+
+	/// Checks that a top-level value was written, and all arrays and objects are closed, and returns the underlying writer.
+	pub fn finish(self) -> io::Result<W>
+	{	if !self.has_root
+		{	return Err(misuse("Nothing was written"));
+		}
+		if !self.stack.is_empty()
+		{	return Err(misuse("Not all arrays and objects are closed"));
+		}
+		self.out.finish()
+	}
+
+	/// Checks that a value can be written here, and writes comma if needed.
+	fn begin_value(&mut self) -> io::Result<()>
+	{	if self.check_value()?
+		{	self.out.write_all(b",")?;
+		}
+		self.commit_value();
+		Ok(())
+	}
+
+	/// Checks that a value can be written here, without changing the state. Returns whether comma must be written before the value.
+	fn check_value(&self) -> io::Result<bool>
+	{	match self.stack.last()
+		{	None if self.has_root => Err(misuse("Only one top-level value can be written")),
+			None => Ok(false),
+			Some(Level::Array {is_empty}) => Ok(!*is_empty),
+			Some(Level::Object {has_key: false, ..}) => Err(misuse("Value in object must follow a key")),
+			Some(Level::Object {has_key: true, ..}) => Ok(false),
+		}
+	}
+
+	/// Records that a value was written.
+	fn commit_value(&mut self)
+	{	match self.stack.last_mut()
+		{	None => self.has_root = true,
+			Some(Level::Array {is_empty}) => *is_empty = false,
+			Some(Level::Object {has_key, ..}) => *has_key = false,
+		}
+	}
+
+	/// Begins array. Then write its elements, and call [end_array()](#method.end_array).
+	pub fn begin_array(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.out.write_all(b"[")?;
+		self.stack.push(Level::Array {is_empty: true});
+		Ok(())
+	}
+
+	pub fn end_array(&mut self) -> io::Result<()>
+	{	match self.stack.last()
+		{	Some(Level::Array {..}) => {},
+			_ => return Err(misuse("end_array() without matching begin_array()"))
+		}
+		self.stack.pop();
+		self.out.write_all(b"]")
+	}
+
+	/// Begins object. Then write pairs of [key()](#method.key) and value, and call [end_object()](#method.end_object).
+	pub fn begin_object(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.out.write_all(b"{")?;
+		self.stack.push(Level::Object {is_empty: true, has_key: false});
+		Ok(())
+	}
+
+	pub fn end_object(&mut self) -> io::Result<()>
+	{	match self.stack.last()
+		{	Some(Level::Object {has_key: false, ..}) => {},
+			Some(Level::Object {has_key: true, ..}) => return Err(misuse("Key without value")),
+			_ => return Err(misuse("end_object() without matching begin_object()"))
+		}
+		self.stack.pop();
+		self.out.write_all(b"}")
+	}
+
+	/// Writes object key. Must be followed by a value.
+	pub fn key(&mut self, key: &str) -> io::Result<()>
+	{	match self.stack.last_mut()
+		{	Some(Level::Object {is_empty, has_key}) =>
+			{	if *has_key
+				{	return Err(misuse("Key without value"));
+				}
+				if !*is_empty
+				{	self.out.write_all(b",")?;
+				}
+				*is_empty = false;
+				*has_key = true;
+				write_json_string(&mut self.out, key.as_bytes())?;
+				self.out.write_all(b":")
+			}
+			_ => Err(misuse("Key can be written only in object"))
+		}
+	}
+
+	/// Writes any value that implements [WriteToJson](trait.WriteToJson.html), as array element, object property value, or top-level value.
+	pub fn value<T>(&mut self, value: &T) -> io::Result<()> where T: WriteToJson<FormatWriter<W>>
+	{	self.begin_value()?;
+		value.write_to_sink(&mut self.out)
+	}
+
+	/// Writes already serialized JSON value. The `json` must be exactly one complete value, like `[1, 2]` or `"abc"`, and it's reformatted
+	/// according to the writer's [Formatting](struct.Formatting.html), like the rest of the output, so whitespace in it doesn't matter.
+	///
+	/// The `json` is checked before anything is written, and if it's not a single value, the call returns error and can be repeated with a correct value.
+	/// The writer's state changes only after the value was written successfully.
+	pub fn raw(&mut self, json: &str) -> io::Result<()>
+	{	if !is_single_value(json)
+		{	return Err(misuse("raw() requires exactly one complete JSON value"));
+		}
+		if self.check_value()?
+		{	self.out.write_all(b",")?;
+		}
+		self.out.write_all(json.as_bytes())?;
+		self.commit_value();
+		Ok(())
+	}
+}
+
+fn misuse(message: &str) -> io::Error
+{	io::Error::new(io::ErrorKind::InvalidInput, message)
+}
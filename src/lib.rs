@@ -146,6 +146,15 @@
 //! # assert_eq!(format!("{:?}", the_true), "true")
 //! ```
 //!
+//! Or build it with the [json!](macro.json.html) macro.
+//! ```
+//! use nop_json::json;
+//!
+//! let user = "John";
+//! let doc = json!({"user": user, "scores": [1, -2, 3.5], "admin": null});
+//! assert_eq!(doc["scores"].to_string(), "[1,-2,3.5]");
+//! ```
+//!
 //! ## Skipping a value from stream
 //!
 //! To skip current value without storing it (and allocating memory), read it to the `()` type.
@@ -161,7 +170,22 @@
 //! ```
 //!
 //! ## Reading binary data
-//! See [read_blob](struct.Reader.html#method.read_blob).
+//! See [read_blob](struct.Reader.html#method.read_blob) for the raw encoding, that is compact, but produces invalid UTF-8 for bytes `80 - FF`.
+//! Write it with [write_blob](fn.write_blob.html) or the [Blob](struct.Blob.html) wrapper.
+//!
+//! For data that other JSON libraries must accept, use base64 ([write_blob_base64](fn.write_blob_base64.html), [read_blob_base64](struct.Reader.html#method.read_blob_base64))
+//! or hex ([write_blob_hex](fn.write_blob_hex.html), [read_blob_hex](struct.Reader.html#method.read_blob_hex)).
+//!
+//! ```
+//! use nop_json::{Reader, write_blob_base64, Base64Alphabet};
+//!
+//! let mut json = Vec::new();
+//! write_blob_base64(&mut json, b"\x80\x81", Base64Alphabet::Standard, true).unwrap();
+//! assert_eq!(json, b"\"gIE=\"");
+//!
+//! let mut reader = Reader::new(json.into_iter());
+//! assert_eq!(reader.read_blob_base64().unwrap(), b"\x80\x81");
+//! ```
 //!
 //! ## Null, NaN, infinity and -0
 //!
@@ -199,6 +223,20 @@
 //! assert!(mzero==0.0 && mzero.is_sign_negative());
 //! ```
 //!
+//! ## Exact decimal numbers
+//!
+//! Amounts like prices can be read to [Decimal](struct.Decimal.html), a fixed-point number with given count of digits after the point,
+//! so they never pass through `f64`.
+//!
+//! ```
+//! use nop_json::{Reader, Decimal};
+//!
+//! let mut reader = Reader::new(r#" 19.99 "#.bytes());
+//! let price: Decimal<2> = reader.read().unwrap();
+//! assert_eq!(price.mantissa(), 1999);
+//! assert_eq!(format!("{:?}", price), "19.99");
+//! ```
+//!
 //! # The JSON dialect
 //!
 //! `nop-json` reads and writes the JSON grammar of ECMA-404, with a few JavaScript-inspired
@@ -210,12 +248,18 @@
 //! - Non-finite floats travel as JSON **strings**: `f32`/`f64` infinities and NaN serialize as
 //!   `"Infinity"`, `"-Infinity"` and `"NaN"`, and reading those strings (or `"-0"`) yields the matching
 //!   value, as shown in the section above.
+//! - Finite floats are written in the shortest form that reads back to exactly the same value, with
+//!   exponent notation for very large and very small magnitudes (`1e300`, `1e-20`).
+//! - Numbers of any length are kept exactly in [Value](enum.Value.html): a mantissa that doesn't fit `u64`
+//!   is stored as `Value::BigNumber` with all its digits.
+//! - Objects in [Value](enum.Value.html) keep their properties in the order they were read or inserted (see [Map](struct.Map.html)).
+//!   If a key repeats, the last value wins, at the place of the first one.
 //! - This is **not** JSON5 — comments, single-quoted strings, unquoted keys, hexadecimal numbers and
 //!   bare `Infinity`/`NaN` are not accepted.
 //!
 //! # Reading untrusted input
 //!
-//! A [Reader](struct.Reader.html) enforces two limits so that hostile input cannot exhaust the stack
+//! A [Reader](struct.Reader.html) enforces three limits so that hostile input cannot exhaust the stack
 //! or memory. To use non-default limits, build the reader with [ReaderBuilder](struct.ReaderBuilder.html):
 //!
 //! ```
@@ -224,6 +268,7 @@
 //! let mut reader = ReaderBuilder::new()
 //!     .depth_limit(64)            // max array/object nesting; default 256
 //!     .value_size_limit(1 << 20)  // max bytes of one string or blob; default 1 GiB
+//!     .number_length_limit(100)   // max digits of one number; default 1024
 //!     .build(r#" [1, 2, 3] "#.bytes());
 //! let arr: Vec<i32> = reader.read().unwrap();
 //! assert_eq!(arr, vec![1, 2, 3]);
@@ -231,18 +276,51 @@
 //!
 //! `depth_limit` bounds parser recursion, so input nested deeper than the limit returns an error
 //! instead of overflowing the stack. `value_size_limit` caps the size of a single in-memory string or
-//! blob (it does not limit [pipe_blob](struct.Reader.html#method.pipe_blob), which streams).
+//! blob (it does not limit [pipe_blob](struct.Reader.html#method.pipe_blob) and the other `pipe_*` functions, which stream).
+//! `number_length_limit` caps the digits of a number that is kept whole (read to [Value](enum.Value.html), `Decimal` or `String`),
+//! so a huge literal can't be used to make later conversions slow.
+//!
+//! Strings that are not valid UTF-8, and `\u` escapes of lone UTF-16 surrogates (like `"\uD800"`, that JavaScript can produce),
+//! return error by default. [ReaderBuilder::invalid_utf8()](struct.ReaderBuilder.html#method.invalid_utf8) can replace them with `U+FFFD`,
+//! or preserve them for byte types (see [Utf8Policy](enum.Utf8Policy.html)).
+//!
+//! Input that starts with a byte order mark, or that is encoded in UTF-16 or UTF-32 (like files exported from Windows tools),
+//! can be read with [ReaderBuilder::skip_bom()](struct.ReaderBuilder.html#method.skip_bom) and [ReaderBuilder::encoding()](struct.ReaderBuilder.html#method.encoding).
+//! `InputEncoding::Auto` detects the encoding as described in RFC 4627.
 
 mod nop_json;
 mod value;
+mod value_map;
 mod debug_to_json;
 mod write_to_json;
 mod validate_json;
 mod escape;
+mod decimal;
+mod json_macro;
+mod merge_patch;
+mod json_patch;
+mod value_diff;
+mod value_reader;
+mod value_writer;
+mod json_sink;
+mod pretty;
+mod json_writer;
+mod blob;
+mod encoding;
 
-pub use crate::nop_json::{Reader, ReaderBuilder, TryFromJson};
+pub use crate::nop_json::{Reader, ReaderBuilder, TryFromJson, StringReader, Utf8Policy};
 pub use crate::debug_to_json::DebugToJson;
 pub use crate::write_to_json::WriteToJson;
 pub use crate::validate_json::ValidateJson;
-pub use crate::escape::{escape, escape_bytes};
-pub use value::Value;
+pub use crate::escape::{escape, escape_bytes, escape_with, escape_bytes_with, Escaping};
+pub use value::{Value, ValueIndex, ConversionError, Digits};
+pub use value_map::{Map, MapIter, MapIterMut, MapIntoIter};
+pub use decimal::{Decimal, Rounding};
+pub use json_patch::PatchError;
+pub use value_diff::Change;
+pub use value_writer::{ValueWriter, to_value};
+pub use json_sink::JsonSink;
+pub use pretty::{Formatting, FormatWriter, to_canonical_json};
+pub use json_writer::JsonWriter;
+pub use blob::{Blob, write_blob, pipe_blob_from, write_blob_base64, write_blob_hex, encode_base64, encode_hex, Base64Alphabet};
+pub use encoding::InputEncoding;
@@ -0,0 +1,88 @@
+use crate::value::Value;
+use crate::value_map::Map;
+
+impl Value
+{	/// Applies JSON Merge Patch ([RFC 7396](https://tools.ietf.org/html/rfc7396)) to this value.
+	///
+	/// If `patch` is an object, it's properties are merged to this value recursively (after converting this value to empty object, if it's not object),
+	/// and `null` properties delete the corresponding properties. Any other `patch` replaces this value.
+	///
+	/// ```
+	/// use nop_json::json;
+	///
+	/// let mut doc = json!({"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"]});
+	/// doc.merge_patch(&json!({"title": "Hello!", "author": {"familyName": null}, "tags": ["example"], "phoneNumber": "+01-123-456-7890"}));
+	/// assert_eq!(doc, json!({"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "phoneNumber": "+01-123-456-7890"}));
+	/// ```
+	pub fn merge_patch(&mut self, patch: &Value)
+	{	match *patch
+		{	Value::Object(ref patch) =>
+			{	if !self.is_object()
+				{	*self = Value::Object(Map::with_capacity(patch.len()));
+				}
+				if let Value::Object(ref mut target) = *self
+				{	for (key, value) in patch
+					{	if value.is_null()
+						{	target.remove(key);
+						}
+						else if let Some(target_value) = target.get_mut(key)
+						{	target_value.merge_patch(value);
+						}
+						else
+						{	let mut target_value = Value::Null;
+							target_value.merge_patch(value);
+							target.insert(key.clone(), target_value);
+						}
+					}
+				}
+			}
+			_ =>
+			{	*self = patch.clone();
+			}
+		}
+	}
+
+	/// Produces minimal JSON Merge Patch ([RFC 7396](https://tools.ietf.org/html/rfc7396)), that turns `old` into `new` when applied with
+	/// [merge_patch()](enum.Value.html#method.merge_patch). If both values are equal objects, the result is empty object.
+	///
+	/// Merge patch cannot set object property to `null`, because `null` in patch means deletion. So properties of `new` that are `null`
+	/// will be missing after applying the patch.
+	///
+	/// ```
+	/// use nop_json::{json, Value};
+	///
+	/// let old = json!({"a": "b", "c": {"d": "e", "f": "g"}, "h": [1, 2]});
+	/// let new = json!({"a": "z", "c": {"d": "e"}, "h": [1, 2]});
+	/// let patch = Value::diff_merge_patch(&old, &new);
+	/// assert_eq!(patch, json!({"a": "z", "c": {"f": null}}));
+	///
+	/// let mut doc = old.clone();
+	/// doc.merge_patch(&patch);
+	/// assert_eq!(doc, new);
+	/// ```
+	pub fn diff_merge_patch(old: &Value, new: &Value) -> Value
+	{	match (old, new)
+		{	(Value::Object(old), Value::Object(new)) =>
+			{	let mut patch = Map::new();
+				for key in old.keys()
+				{	if !new.contains_key(key)
+					{	patch.insert(key.clone(), Value::Null);
+					}
+				}
+				for (key, new_value) in new
+				{	match old.get(key)
+					{	Some(old_value) if old_value == new_value => {},
+						Some(old_value @ Value::Object(_)) if new_value.is_object() =>
+						{	patch.insert(key.clone(), Value::diff_merge_patch(old_value, new_value));
+						}
+						_ =>
+						{	patch.insert(key.clone(), new_value.clone());
+						}
+					}
+				}
+				Value::Object(patch)
+			}
+			_ => new.clone()
+		}
+	}
+}
@@ -1,9 +1,16 @@
 pub use nop_json_derive::*;
-use crate::value::Value;
+use crate::value::{Value, Digits};
+use crate::value_map::Map;
+use crate::decimal::{Decimal, Rounding, decimal_from_digits, decimal_from_value};
+use crate::blob::{BlobDecoder, BlobEncoding, Utf8Validator, decode_error_message};
+use crate::encoding::{InputEncoding, Input};
+use crate::escape::escape;
+use crate::json_sink::JsonSink;
 
 use std::{io, io::Write, char, fmt};
 use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, LinkedList, VecDeque};
 use std::convert::TryInto;
+use std::str::FromStr;
 use numtoa::NumToA;
 
 pub const READER_BUFFER_SIZE: usize = 128;
@@ -12,6 +19,9 @@ const MAX_VALUE_SIZE_LIMIT: usize = 1024 * 1024 * 1024; // 1 GiB, can be overrid
 const FORMAT_NUM_WIDTH: usize = 10;
 const FORMAT_NUM_WIDTH_Z: [u8; FORMAT_NUM_WIDTH] = [b'0'; FORMAT_NUM_WIDTH];
 const FORMAT_NUM_WIDTH_0Z: &[u8] = b"0.0000000000";
+const FLOAT_DIGITS_BUFFER_SIZE: usize = 40; // enough for the shortest representation of any f64, longer numbers spill to heap
+const FLOAT_DIGITS_LIMIT: usize = 800; // more significant digits can't change the correctly rounded f64 (the rest only matters as zero or nonzero)
+const DEFAULT_NUMBER_LENGTH_LIMIT: usize = 1024; // digits of one number that is kept whole (like in Value); can be overridden via ReaderBuilder::number_length_limit
 
 macro_rules! read_int
 {	($self:expr, $T:ty, $is_unsigned:expr) =>
@@ -336,8 +346,7 @@ macro_rules! read_float
 					{	let mut is_negative = false;
 						let mut exponent = 0i32;
 						let mut is_after_dot = 0;
-						let mut result = 0 as $T;
-						let mut ten = 1;
+						let mut digits = FloatDigits::new();
 						let mut is_error = false;
 						if c == b'-'
 						{	is_negative = true;
@@ -354,26 +363,8 @@ macro_rules! read_float
 						}
 						loop
 						{	match c
-							{	b'0' =>
-								{	exponent += is_after_dot;
-									ten += 1;
-								}
-								b'1'..= b'9' =>
-								{	exponent += is_after_dot;
-									result *= match ten
-									{	1 => 10.0,
-										2 => 100.0,
-										3 => 1000.0,
-										4 => 10000.0,
-										5 => 100000.0,
-										6 => 1000000.0,
-										7 => 10000000.0,
-										8 => 100000000.0,
-										9 => 1000000000.0,
-										_ => (10 as $T).powi(ten)
-									};
-									result += (c - b'0') as $T;
-									ten = 1;
+							{	b'0'..= b'9' =>
+								{	exponent += is_after_dot + digits.push(c);
 								}
 								b'.' => {is_after_dot = -1}
 								b'e' | b'E' =>
@@ -445,20 +436,7 @@ macro_rules! read_float
 							}
 							return Ok($nan);
 						}
-						exponent += ten - 1;
-						match exponent
-						{	0 => {},
-							1 => result *= 10.0,
-							2 => result *= 100.0,
-							3 => result *= 1000.0,
-							4 => result *= 10000.0,
-							5 => result *= 100000.0,
-							6 => result *= 1000000.0,
-							7 => result *= 10000000.0,
-							8 => result *= 100000000.0,
-							9 => result *= 1000000000.0,
-							_ => result *= (10 as $T).powi(exponent)
-						};
+						let mut result: $T = digits.parse(exponent).unwrap_or($nan);
 						if is_negative
 						{	result = -result;
 						}
@@ -624,6 +602,61 @@ macro_rules! read_float
 /// let obj_0: Geometry = reader.read().unwrap();
 /// assert_eq!(obj_0, Geometry::Point(Point {x: 0, y: 0, comments: String::new()}));
 /// ```
+///
+/// To convert all the names, that are not given explicitly, to another case convention, specify `#[json(rename_all = "...")]` on the struct or the enum.
+/// Supported conventions are "camelCase", "PascalCase", "kebab-case", "SCREAMING_SNAKE_CASE" and "lowercase".
+/// This applies to struct fields, and to enum variant names, that the enum field (like `#[json(type)]`) contains.
+///
+/// ```
+/// use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson};
+///
+/// #[derive(TryFromJson, ValidateJson, DebugToJson, PartialEq, Default)]
+/// #[json(rename_all = "camelCase")]
+/// struct User {user_id: u32, #[json(name)] user_name: String}
+///
+/// #[derive(TryFromJson, ValidateJson, DebugToJson, PartialEq)]
+/// #[json(type, rename_all = "kebab-case")]
+/// enum Event {#[json(user)] UserCreated(User), ServerStopped}
+///
+/// let mut reader = Reader::new(r#" {"type": "user-created", "user": {"userId": 1, "name": "Ann"}} "#.bytes());
+/// let event: Event = reader.read().unwrap();
+/// assert_eq!(event, Event::UserCreated(User {user_id: 1, user_name: "Ann".to_string()}));
+/// assert_eq!(Event::ServerStopped.to_json_string(), r#"{"type":"server-stopped"}"#);
+/// ```
+///
+/// Missing fields get `Default::default()`. To use another value, specify `#[json(default = ...)]`. String is a path of function to call (`default = "default_port"`),
+/// or an expression (`default = "String::from(\"localhost\")"`), and other literals are used as they are (`default = 8080`).
+/// `#[json(default)]` on the struct takes missing fields from the struct's own `Default` implementation.
+/// Fields that have default from one of these attributes don't need to implement `Default`.
+///
+/// ```
+/// use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson};
+///
+/// fn default_port() -> u16 {8080}
+///
+/// #[derive(TryFromJson, ValidateJson, DebugToJson, PartialEq)]
+/// struct Server {#[json(default = "default_port")] port: u16, #[json(default = 3)] retries: u32, name: String}
+///
+/// let mut reader = Reader::new(r#" {"name": "main"} "#.bytes());
+/// let server: Server = reader.read().unwrap();
+/// assert_eq!(server, Server {port: 8080, retries: 3, name: "main".to_string()});
+/// ```
+///
+/// `Vec<u8>` fields are arrays of numbers by default. Mark them with `#[json(base64)]` or `#[json(hex)]` to serialize them as base64 or hex strings
+/// (see [read_blob_base64()](struct.Reader.html#method.read_blob_base64) and [read_blob_hex()](struct.Reader.html#method.read_blob_hex)).
+/// This can be combined with a name: `#[json(digest, hex)]`. A field called "base64" or "hex" needs the string form of name: `#[json("hex")]`.
+///
+/// ```
+/// use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson};
+///
+/// #[derive(TryFromJson, ValidateJson, DebugToJson, PartialEq)]
+/// struct File {#[json(base64)] data: Vec<u8>, #[json(digest, hex)] sha: Vec<u8>}
+///
+/// let mut reader = Reader::new(r#" {"data": "gIE=", "digest": "0abc"} "#.bytes());
+/// let file: File = reader.read().unwrap();
+/// assert_eq!(file, File {data: b"\x80\x81".to_vec(), sha: b"\x0A\xBC".to_vec()});
+/// assert_eq!(file.to_json_string(), r#"{"data":"gIE=","digest":"0abc"}"#);
+/// ```
 /// It's possible to validate object right after deserialization. To do so implement [ValidateJson](trait.ValidateJson.html).
 /// ```
 /// use nop_json::{Reader, TryFromJson, ValidateJson};
@@ -859,6 +892,10 @@ impl TryFromJson for Value
 {	fn try_from_json<T>(reader: &mut Reader<T>) -> io::Result<Self> where T: Iterator<Item=u8> {reader.read_value()}
 }
 
+impl<const SCALE: u32> TryFromJson for Decimal<SCALE>
+{	fn try_from_json<T>(reader: &mut Reader<T>) -> io::Result<Self> where T: Iterator<Item=u8> {reader.read_decimal(SCALE).map(Decimal::new)}
+}
+
 impl<U> TryFromJson for Box<U> where U: TryFromJson
 {	fn try_from_json<T>(reader: &mut Reader<T>) -> io::Result<Self> where T: Iterator<Item=u8>
 	{	Ok(Box::new(U::try_from_json(reader)?))
@@ -1142,6 +1179,27 @@ enum PathItem
 	Index(usize),
 }
 
+/// Appends `.key` to path, or `["key"]` if the key is not an identifier.
+pub(crate) fn push_path_key(path: &mut String, key: &str)
+{	let mut chars = key.chars();
+	let is_identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+	if is_identifier
+	{	path.push('.');
+		path.push_str(key);
+	}
+	else
+	{	path.push_str("[\"");
+		path.push_str(&escape(key));
+		path.push_str("\"]");
+	}
+}
+
+/// Appends `[index]` to path.
+pub(crate) fn push_path_index(path: &mut String, index: usize)
+{	use fmt::Write;
+	write!(path, "[{}]", index).ok();
+}
+
 pub fn number_to_string(buffer: &mut [u8; READER_BUFFER_SIZE], mut len: usize, mut exponent: i16, is_negative: bool) -> Result<usize, ()>
 {	if len == 0
 	{	buffer[0] = b'0';
@@ -1157,9 +1215,12 @@ pub fn number_to_string(buffer: &mut [u8; READER_BUFFER_SIZE], mut len: usize, m
 		buffer[0] = b'-';
 		pos = 1;
 	}
+	let mut buffer_2 = [0u8; 24];
+	let exponent_len = exponent.numtoa(10, &mut buffer_2).len();
+	// plain notation is used for numbers not wider than FORMAT_NUM_WIDTH, and wherever it's not longer than exponent notation
 	if exponent >= 0
 	{	let e = exponent as usize;
-		if len+e <= FORMAT_NUM_WIDTH
+		if (len+e <= FORMAT_NUM_WIDTH || e <= exponent_len+1) && pos+len+e <= buffer.len()
 		{	// append zeroes according to exponent
 			buffer[pos+len .. pos+len+e].copy_from_slice(&FORMAT_NUM_WIDTH_Z[0 .. e]);
 			return Ok(pos + len + e);
@@ -1173,15 +1234,14 @@ pub fn number_to_string(buffer: &mut [u8; READER_BUFFER_SIZE], mut len: usize, m
 			buffer[pos+len-e] = b'.';
 			return Ok(pos + len + 1);
 		}
-		if e <= FORMAT_NUM_WIDTH
+		if (e <= FORMAT_NUM_WIDTH || e+2 <= len+1+exponent_len) && pos+e+2 <= buffer.len()
 		{	// prepend with 0.000...
 			buffer.copy_within(pos .. pos+len, pos+e-len+2);
 			buffer[pos .. pos+e-len+2].copy_from_slice(&FORMAT_NUM_WIDTH_0Z[0 .. e-len+2]);
-			return Ok(e + 2);
+			return Ok(pos + e + 2);
 		}
 	}
 	len += pos;
-	let mut buffer_2 = [0u8; 24];
 	loop
 	{	let exponent_str = exponent.numtoa(10, &mut buffer_2);
 		if len+1+exponent_str.len() > buffer.len()
@@ -1199,15 +1259,230 @@ pub fn number_to_string(buffer: &mut [u8; READER_BUFFER_SIZE], mut len: usize, m
 	}
 }
 
+/// Reads `Value::Number` or `Value::BigNumber` from a string that contains a JSON number and nothing else (except surrounding whitespace).
+pub fn parse_number(s: &str) -> Result<Value, ()>
+{	let mut reader = Reader::new(s.bytes());
+	match reader.next_token()
+	{	Ok(Token::Number(exponent, is_negative)) =>
+		{	if !reader.get_next_char().is_ascii_whitespace()
+			{	return Err(());
+			}
+			Ok(reader.number_to_value(exponent, is_negative))
+		}
+		_ => Err(())
+	}
+}
+
+/// Whether `s` is exactly one complete JSON value, with optional whitespace around it. The value is skipped, not stored.
+pub fn is_single_value(s: &str) -> bool
+{	let mut reader = ReaderBuilder::new().value_size_limit(usize::MAX).build(s.bytes());
+	reader.read::<()>().is_ok() && reader.get_next_char().is_ascii_whitespace()
+}
+
+/// Like `number_to_string()`, but takes any number of `digits`, and passes the formatted number to `out` part by part.
+pub fn write_number<F, E>(digits: &[u8], exponent: i16, is_negative: bool, mut out: F) -> Result<(), E> where F: FnMut(&[u8]) -> Result<(), E>
+{	// leading zeroes are not allowed in JSON
+	let digits = match digits.iter().position(|&c| c != b'0')
+	{	Some(pos) => &digits[pos ..],
+		None => b"",
+	};
+	let len = digits.len();
+	if is_negative
+	{	out(b"-")?;
+	}
+	if len == 0
+	{	return out(b"0");
+	}
+	let mut buffer = [0u8; 24];
+	let exponent_str = exponent.numtoa(10, &mut buffer);
+	// same rules as in number_to_string()
+	if exponent >= 0
+	{	let e = exponent as usize;
+		if len+e <= FORMAT_NUM_WIDTH || e <= exponent_str.len()+1
+		{	// append zeroes according to exponent
+			out(digits)?;
+			return out(&FORMAT_NUM_WIDTH_Z[0 .. e]);
+		}
+	}
+	else
+	{	let e = exponent.unsigned_abs() as usize;
+		if e < len
+		{	// insert dot in the middle of number
+			out(&digits[0 .. len-e])?;
+			out(b".")?;
+			return out(&digits[len-e ..]);
+		}
+		if e <= FORMAT_NUM_WIDTH || e+2 <= len+1+exponent_str.len()
+		{	// prepend with 0.000...
+			out(&FORMAT_NUM_WIDTH_0Z[0 .. e-len+2])?;
+			return out(digits);
+		}
+	}
+	out(digits)?;
+	out(b"e")?;
+	out(exponent_str)
+}
+
+/// Formats a finite float to the shortest string that reads back (with `read_f64()` or `read_f32()`) to the same bits.
+/// The digits are laid out like in `number_to_string()`, so very large and very small numbers get exponent notation.
+pub fn float_to_string<F>(buffer: &mut [u8; READER_BUFFER_SIZE], value: F) -> Result<usize, ()> where F: fmt::LowerExp
+{	let (len, exponent, is_negative) = float_to_digits(buffer, value)?;
+	if len == 0
+	{	// keep the sign of -0
+		if !is_negative
+		{	buffer[0] = b'0';
+			return Ok(1);
+		}
+		buffer[0 .. 2].copy_from_slice(b"-0");
+		return Ok(2);
+	}
+	number_to_string(buffer, len, exponent, is_negative)
+}
+
+/// Stores to `buffer` the digits of the shortest round-trip representation of a finite float (that `{:e}` produces),
+/// and returns the number of digits (0 for zero), the exponent and the sign, like in `Value::Number`.
+pub fn float_to_digits<F>(buffer: &mut [u8; READER_BUFFER_SIZE], value: F) -> Result<(usize, i16, bool), ()> where F: fmt::LowerExp
+{	struct Sci
+	{	len: usize,
+		buffer: [u8; FLOAT_DIGITS_BUFFER_SIZE],
+	}
+	impl fmt::Write for Sci
+	{	fn write_str(&mut self, s: &str) -> fmt::Result
+		{	let end = self.len + s.len();
+			if end > self.buffer.len()
+			{	return Err(fmt::Error {});
+			}
+			self.buffer[self.len .. end].copy_from_slice(s.as_bytes());
+			self.len = end;
+			Ok(())
+		}
+	}
+	let mut sci = Sci {len: 0, buffer: [0u8; FLOAT_DIGITS_BUFFER_SIZE]};
+	fmt::Write::write_fmt(&mut sci, format_args!("{:e}", value)).map_err(|_| ())?;
+	// like "-1.2345e-20"
+	let mut sci = &sci.buffer[0 .. sci.len];
+	let is_negative = sci.first() == Some(&b'-');
+	if is_negative
+	{	sci = &sci[1 ..];
+	}
+	let e_pos = sci.iter().position(|c| *c == b'e').ok_or(())?;
+	let mut exponent: i16 = std::str::from_utf8(&sci[e_pos+1 ..]).map_err(|_| ())?.parse().map_err(|_| ())?;
+	let mut len = 0;
+	let mut is_after_dot = false;
+	for c in &sci[0 .. e_pos]
+	{	if *c == b'.'
+		{	is_after_dot = true;
+		}
+		else
+		{	buffer[len] = *c;
+			len += 1;
+			if is_after_dot
+			{	exponent = exponent.checked_sub(1).ok_or(())?;
+			}
+		}
+	}
+	if len==1 && buffer[0]==b'0'
+	{	len = 0;
+	}
+	Ok((len, exponent, is_negative))
+}
+
+/// Significant digits of a number that is being read to a float. At the end they're converted with `str::parse()`, which
+/// rounds correctly, so the result doesn't suffer from floating-point error accumulated while reading digit by digit.
+struct FloatDigits
+{	len: usize,
+	buffer: [u8; FLOAT_DIGITS_BUFFER_SIZE],
+	more: Vec<u8>,
+	is_truncated: bool,
+}
+
+impl FloatDigits
+{	fn new() -> Self
+	{	FloatDigits {len: 0, buffer: [0u8; FLOAT_DIGITS_BUFFER_SIZE], more: Vec::new(), is_truncated: false}
+	}
+
+	/// Appends a digit, skipping leading zeroes. Returns 1 if the digit was dropped, because there're already enough
+	/// digits (so the caller must increment the exponent), or 0 otherwise.
+	fn push(&mut self, c: u8) -> i32
+	{	if self.len < FLOAT_DIGITS_BUFFER_SIZE
+		{	if c!=b'0' || self.len!=0
+			{	self.buffer[self.len] = c;
+				self.len += 1;
+			}
+			0
+		}
+		else if self.len + self.more.len() < FLOAT_DIGITS_LIMIT
+		{	self.more.push(c);
+			0
+		}
+		else
+		{	if c != b'0'
+			{	self.is_truncated = true;
+			}
+			1
+		}
+	}
+
+	/// Converts `digits * 10.pow(exponent)` to float.
+	fn parse<F>(mut self, mut exponent: i32) -> Option<F> where F: FromStr
+	{	if self.len == 0
+		{	return "0".parse().ok();
+		}
+		if self.is_truncated
+		{	// one nonzero digit stands for all the dropped ones, so halfway cases round the right way
+			self.more.push(b'1');
+			exponent = exponent.checked_sub(1)?;
+		}
+		let mut buffer_2 = [0u8; 24];
+		let exponent = exponent.numtoa(10, &mut buffer_2);
+		if self.more.is_empty()
+		{	let mut buffer = [0u8; FLOAT_DIGITS_BUFFER_SIZE + 24];
+			let len = self.len;
+			buffer[0 .. len].copy_from_slice(&self.buffer[0 .. len]);
+			buffer[len] = b'e';
+			buffer[len+1 .. len+1+exponent.len()].copy_from_slice(exponent);
+			std::str::from_utf8(&buffer[0 .. len+1+exponent.len()]).ok()?.parse().ok()
+		}
+		else
+		{	let mut vec = Vec::with_capacity(self.len + self.more.len() + 1 + exponent.len());
+			vec.extend_from_slice(&self.buffer[0 .. self.len]);
+			vec.extend_from_slice(&self.more);
+			vec.push(b'e');
+			vec.extend_from_slice(exponent);
+			std::str::from_utf8(&vec).ok()?.parse().ok()
+		}
+	}
+}
+
+/// What [Reader](struct.Reader.html) does with strings that are not valid UTF-8, or that contain lone UTF-16 surrogates (like `"\uD800"`),
+/// that JavaScript producers can emit. Set it with [ReaderBuilder::invalid_utf8()](struct.ReaderBuilder.html#method.invalid_utf8).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Utf8Policy
+{	/// Return error. This is the default.
+	#[default]
+	Reject,
+	/// Replace each invalid sequence and each lone surrogate with `U+FFFD` (replacement character) in `String`, `char`, `Value` and object keys.
+	/// In byte types (like [read_blob()](struct.Reader.html#method.read_blob)) lone surrogates are replaced, and other bytes are kept as they are.
+	Replace,
+	/// Encode lone surrogates like UTF-8 encodes other characters (WTF-8), so the data can be read to byte types (like with [read_blob()](struct.Reader.html#method.read_blob))
+	/// without loss. `String`, `char`, `Value` and object keys can not hold such data, so reading them returns error, like with `Reject`.
+	Preserve,
+}
+
 #[derive(Clone, Copy)]
 struct Options
 {	depth_limit: usize,
 	value_size_limit: usize,
+	number_length_limit: usize,
+	decimal_rounding: Rounding,
+	invalid_utf8: Utf8Policy,
+	encoding: InputEncoding,
+	skip_bom: bool,
 }
 
 impl Default for Options
 {	fn default() -> Self
-	{	Options {depth_limit: DEFAULT_DEPTH_LIMIT, value_size_limit: MAX_VALUE_SIZE_LIMIT}
+	{	Options {depth_limit: DEFAULT_DEPTH_LIMIT, value_size_limit: MAX_VALUE_SIZE_LIMIT, number_length_limit: DEFAULT_NUMBER_LENGTH_LIMIT, decimal_rounding: Rounding::Error, invalid_utf8: Utf8Policy::Reject, encoding: InputEncoding::Utf8, skip_bom: false}
 	}
 }
 
@@ -1245,16 +1520,100 @@ impl ReaderBuilder
 		self
 	}
 
+	/// Maximum count of digits in a number (not counting the exponent), that is kept whole: read to [Value](enum.Value.html),
+	/// [Decimal](struct.Decimal.html) or `String`, or skipped. Longer numbers are rejected with an error. Default: 1024.
+	/// (Integer types are limited by their range, and `f32` and `f64` take only the digits they need.)
+	///
+	/// ```
+	/// use nop_json::{ReaderBuilder, Value};
+	///
+	/// let json = "1".repeat(30);
+	/// assert!(ReaderBuilder::new().number_length_limit(20).build(json.bytes()).read::<Value>().is_err());
+	/// ```
+	pub fn number_length_limit(mut self, number_length_limit: usize) -> Self
+	{	self.options.number_length_limit = number_length_limit;
+		self
+	}
+
+	/// What to do when a number read to [Decimal](struct.Decimal.html) has more fractional digits than the decimal scale allows.
+	/// Default: `Rounding::Error`.
+	pub fn decimal_rounding(mut self, decimal_rounding: Rounding) -> Self
+	{	self.options.decimal_rounding = decimal_rounding;
+		self
+	}
+
+	/// What to do with strings that are not valid UTF-8, or that contain lone UTF-16 surrogates. Default: `Utf8Policy::Reject`.
+	///
+	/// ```
+	/// use nop_json::{ReaderBuilder, Utf8Policy};
+	///
+	/// let json = b" \"a\\uD800b\xFF\" \"\\uDE00\" ";
+	/// let mut reader = ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(json.iter().copied());
+	/// assert_eq!(reader.read::<String>().unwrap(), "a\u{FFFD}b\u{FFFD}");
+	/// assert_eq!(reader.read::<char>().unwrap(), '\u{FFFD}');
+	///
+	/// let mut reader = ReaderBuilder::new().invalid_utf8(Utf8Policy::Preserve).build(json.iter().copied());
+	/// assert_eq!(reader.read_blob().unwrap(), b"a\xED\xA0\x80b\xFF");
+	/// assert!(reader.read::<String>().is_err());
+	/// ```
+	pub fn invalid_utf8(mut self, invalid_utf8: Utf8Policy) -> Self
+	{	self.options.invalid_utf8 = invalid_utf8;
+		self
+	}
+
+	/// Encoding of the input bytes. Input in other encodings than UTF-8 is transcoded to UTF-8 as it's read. Default: `InputEncoding::Utf8`.
+	///
+	/// `InputEncoding::Auto` detects encoding from the first 4 bytes, so it reads them ahead before returning the first value.
+	///
+	/// ```
+	/// use nop_json::{ReaderBuilder, InputEncoding};
+	///
+	/// let json: Vec<u8> = "\u{FEFF}[\"€\"]".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+	/// let mut reader = ReaderBuilder::new().encoding(InputEncoding::Auto).build(json.into_iter());
+	/// assert_eq!(reader.read::<Vec<String>>().unwrap(), vec!["€".to_string()]);
+	/// ```
+	pub fn encoding(mut self, encoding: InputEncoding) -> Self
+	{	self.options.encoding = encoding;
+		self
+	}
+
+	/// Skip byte order mark (like UTF-8 `EF BB BF`) at the beginning of input, if there is one. `InputEncoding::Auto` always skips it. Default: `false`.
+	///
+	/// ```
+	/// use nop_json::ReaderBuilder;
+	///
+	/// let mut reader = ReaderBuilder::new().skip_bom(true).build(b"\xEF\xBB\xBF true".iter().copied());
+	/// assert_eq!(reader.read::<bool>().unwrap(), true);
+	/// ```
+	pub fn skip_bom(mut self, skip_bom: bool) -> Self
+	{	self.options.skip_bom = skip_bom;
+		self
+	}
+
+	/// Read the text that `ValueTokens` produces from a `Value` tree.
+	/// The tree is already in memory, so `value_size_limit` and `number_length_limit` don't apply.
+	pub(crate) fn value_tree(mut self) -> Self
+	{	self.options.value_size_limit = usize::MAX;
+		self.options.number_length_limit = usize::MAX;
+		self.options.encoding = InputEncoding::Utf8;
+		self.options.skip_bom = false;
+		self
+	}
+
 	/// Create a [Reader](struct.Reader.html) that reads from `iter` using the configured limits.
 	pub fn build<T>(self, iter: T) -> Reader<T> where T: Iterator<Item=u8>
 	{	Reader
-		{	iter,
+		{	iter: Input::new(iter, self.options.encoding, self.options.skip_bom, self.options.invalid_utf8),
 			lookahead: b' ',
 			path: Vec::new(),
 			last_index: 0,
 			buffer_len: 0,
 			buffer: [0u8; READER_BUFFER_SIZE],
+			number_tail: Vec::new(),
 			depth: 0,
+			unread: [0u8; 6],
+			unread_pos: 0,
+			unread_len: 0,
 			options: self.options,
 		}
 	}
@@ -1275,13 +1634,17 @@ impl ReaderBuilder
 /// assert_eq!(reader.read::<Vec<i32>>().unwrap(), vec![3, 4]);
 /// ```
 pub struct Reader<T> where T: Iterator<Item=u8>
-{	iter: T,
+{	iter: Input<T>,
 	lookahead: u8,
 	path: Vec<PathItem>,
 	last_index: usize,
 	buffer_len: usize,
 	buffer: [u8; READER_BUFFER_SIZE], // must be at least 48 bytes for correct number reading
+	number_tail: Vec<u8>, // digits of the last read number that didn't fit to buffer
 	depth: usize,
+	unread: [u8; 6], // string bytes that were read after a lone surrogate, and must be read again
+	unread_pos: usize,
+	unread_len: usize,
 	options: Options,
 }
 impl<T> Reader<T> where T: Iterator<Item=u8>
@@ -1332,8 +1695,20 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	}
 
 	/// Destroy this reader, unwrapping the underlying iterator that was passed to constructor when this object created.
+	///
+	/// The reader looks one byte ahead, so the byte that follows the last value read (usually whitespace) is already taken from the iterator.
+	/// With UTF-16 and UTF-32 input ([ReaderBuilder::encoding()](struct.ReaderBuilder.html#method.encoding)), the iterator can also be in the middle of a character,
+	/// and the bytes of that character that were already taken are lost. With UTF-8 input nothing else is lost, also with [skip_bom()](struct.ReaderBuilder.html#method.skip_bom).
+	///
+	/// ```
+	/// use nop_json::ReaderBuilder;
+	///
+	/// let mut reader = ReaderBuilder::new().skip_bom(true).build(b"\xEF\xBB\xBF1 2 3".iter().copied());
+	/// assert_eq!(reader.read::<i32>().unwrap(), 1);
+	/// assert_eq!(reader.unwrap().collect::<Vec<u8>>(), b"2 3");
+	/// ```
 	pub fn unwrap(self) -> T
-	{	self.iter
+	{	self.iter.unwrap()
 	}
 
 	/// Read one JSON value from the stream.
@@ -1355,6 +1730,26 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		result
 	}
 
+	/// Like [read_prop()](struct.Reader.html#method.read_prop), but reads the value with the given function, like [read_blob_base64()](struct.Reader.html#method.read_blob_base64).
+	/// Derived `TryFromJson` uses it for fields marked `#[json(base64)]` and `#[json(hex)]`.
+	///
+	/// ```
+	/// use nop_json::Reader;
+	///
+	/// let mut reader = Reader::new(r#" {"data": "AAE="} {"data": "@"} "#.bytes());
+	/// let mut data = Vec::new();
+	/// reader.read_object_use_buffer(|reader| {data = reader.read_prop_with("data", |reader| reader.read_blob_base64())?; Ok(())}).unwrap();
+	/// assert_eq!(data, b"\x00\x01");
+	/// let err = reader.read_object_use_buffer(|reader| reader.read_prop_with("data", |reader| reader.read_blob_base64()).map(|_| ())).unwrap_err();
+	/// assert_eq!(err.to_string(), "$.data: Invalid base64 string");
+	/// ```
+	pub fn read_prop_with<U, F>(&mut self, prop: &'static str, read: F) -> io::Result<U> where F: FnOnce(&mut Self) -> io::Result<U>
+	{	self.path.push(PathItem::Prop(prop));
+		let result = read(self);
+		self.path.pop();
+		result
+	}
+
 	/// This method is intended for use in cases when you want to implement [TryFromJson](trait.TryFromJson.html) manually.
 	/// Use it when you read an array with [read_array()](struct.Reader.html#method.read_array).
 	/// It works exactly like `read()`, but if error occures, the error message will contain index number in array.
@@ -1371,8 +1766,8 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	{	let mut s = "$".to_string();
 		for i in &self.path
 		{	match i
-			{	PathItem::Prop(prop) => {s.push('.'); s.push_str(prop)}
-				PathItem::Index(index) => s.push_str(&format!("[{}]", index))
+			{	PathItem::Prop(prop) => push_path_key(&mut s, prop),
+				PathItem::Index(index) => push_path_index(&mut s, *index),
 			}
 		}
 		s
@@ -1504,6 +1899,7 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 					let mut is_after_dot = 0;
 					let mut pos = 0;
 					let mut n_trailing_zeroes = 0;
+					self.number_tail.clear();
 					if c == b'-'
 					{	is_negative = true;
 						c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
@@ -1514,19 +1910,15 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 							{	exponent += is_after_dot;
 								if pos > 0
 								{	n_trailing_zeroes += 1;
-									if pos < self.buffer.len()
-									{	self.buffer[pos] = b'0';
-										pos += 1;
-									}
+									self.push_number_digit(pos, b'0')?;
+									pos += 1;
 								}
 							}
 							b'1'..= b'9' =>
 							{	exponent += is_after_dot;
 								n_trailing_zeroes = 0;
-								if pos < self.buffer.len()
-								{	self.buffer[pos] = c;
-									pos += 1;
-								}
+								self.push_number_digit(pos, c)?;
+								pos += 1;
 							}
 							b'.' => {is_after_dot = -1}
 							b'e' | b'E' =>
@@ -1605,7 +1997,8 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 							break;
 						}
 					}
-					self.buffer_len = pos;
+					self.number_tail.truncate(pos.saturating_sub(self.buffer.len()));
+					self.buffer_len = pos.min(self.buffer.len());
 					return match exponent.try_into()
 					{	Ok(exponent) => Ok(Token::Number(exponent, is_negative)),
 						Err(_) => Err(self.number_error())
@@ -1651,16 +2044,39 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		}
 	}
 
+	/// Stores digit number `pos` of the number being read by `next_token()`: to `buffer`, or if it's full, to `number_tail`.
+	fn push_number_digit(&mut self, pos: usize, c: u8) -> io::Result<()>
+	{	if pos >= self.options.number_length_limit
+		{	self.lookahead = b' ';
+			return Err(self.format_error("Invalid JSON input: Number is too long"));
+		}
+		if pos < self.buffer.len()
+		{	self.buffer[pos] = c;
+		}
+		else
+		{	self.number_tail.push(c);
+		}
+		Ok(())
+	}
+
+	/// All the digits of the number that `next_token()` has just read.
+	fn get_number_digits(&self) -> Vec<u8>
+	{	let mut digits = Vec::with_capacity(self.buffer_len + self.number_tail.len());
+		digits.extend_from_slice(&self.buffer[0 .. self.buffer_len]);
+		digits.extend_from_slice(&self.number_tail);
+		digits
+	}
+
 	fn skip_string(&mut self) -> io::Result<()>
 	{	self.lookahead = b' ';
 		loop
-		{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+		{	let c = self.next_string_byte()?;
 			match c
 			{	b'"' =>
 				{	break;
 				}
 				b'\\' =>
-				{	self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+				{	self.next_string_byte()?;
 				}
 				_ => {}
 			}
@@ -1668,12 +2084,43 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		Ok(())
 	}
 
+	/// Next byte of string contents: one of the bytes put back with [unread()](#method.unread), or the next byte from the input.
+	#[inline]
+	fn next_string_byte(&mut self) -> io::Result<u8>
+	{	if self.unread_pos < self.unread_len
+		{	let c = self.unread[self.unread_pos];
+			self.unread_pos += 1;
+			return Ok(c);
+		}
+		self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))
+	}
+
+	/// Puts back string bytes, that were read after a lone surrogate, so they will be read again.
+	fn unread(&mut self, bytes: &[u8])
+	{	self.unread[.. bytes.len()].copy_from_slice(bytes);
+		self.unread_pos = 0;
+		self.unread_len = bytes.len();
+	}
+
+	fn read_u_escape_code(&mut self) -> io::Result<u32>
+	{	let c0 = self.next_string_byte()?;
+		let c1 = self.next_string_byte()?;
+		let c2 = self.next_string_byte()?;
+		let c3 = self.next_string_byte()?;
+		Ok((self.hex_to_u32(c0)? << 12) | (self.hex_to_u32(c1)? << 8) | (self.hex_to_u32(c2)? << 4) | self.hex_to_u32(c3)?)
+	}
+
+	/// Handles `\u` escape of UTF-16 surrogate `c`, that is not part of a pair, according to [Utf8Policy](enum.Utf8Policy.html).
+	fn lone_surrogate_to_utf8(&mut self, buf_pos: usize, c: u32, error: &str) -> io::Result<usize>
+	{	match self.options.invalid_utf8
+		{	Utf8Policy::Reject => Err(self.format_error(error)),
+			Utf8Policy::Replace => Ok((&mut self.buffer[buf_pos ..]).write("\u{FFFD}".as_bytes()).unwrap()),
+			Utf8Policy::Preserve => Ok((&mut self.buffer[buf_pos ..]).write(&[(0xE0 | (c >> 12)) as u8, (0x80 | ((c >> 6) & 0x3F)) as u8, (0x80 | (c & 0x3F)) as u8]).unwrap()),
+		}
+	}
+
 	fn u_escape_to_utf8(&mut self, buf_pos: usize) -> io::Result<usize>
-	{	let c0 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-		let c1 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-		let c2 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-		let c3 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-		let c = (self.hex_to_u32(c0)? << 12) | (self.hex_to_u32(c1)? << 8) | (self.hex_to_u32(c2)? << 4) | self.hex_to_u32(c3)?;
+	{	let c = self.read_u_escape_code()?;
 		if c <= 0x7F
 		{	if buf_pos == self.buffer.len()
 			{	Ok(0)
@@ -1706,26 +2153,30 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		}
 		else if c <= 0xDBFF
 		{	// UTF-16 surrogate pairs: the high surrogate must be followed by a `\u` low surrogate
-			let bs = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			let u = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			if bs != b'\\' || u != b'u'
-			{	return Err(self.format_error("Invalid UTF-16 surrogate pair"));
+			// if it's not, the bytes read after it are put back, to be read as usual
+			let bs = self.next_string_byte()?;
+			if bs != b'\\'
+			{	self.unread(&[bs]);
+				return self.lone_surrogate_to_utf8(buf_pos, c, "Invalid UTF-16 surrogate pair");
+			}
+			let u = self.next_string_byte()?;
+			if u != b'u'
+			{	self.unread(&[bs, u]);
+				return self.lone_surrogate_to_utf8(buf_pos, c, "Invalid UTF-16 surrogate pair");
 			}
-			let c0 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			let c1 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			let c2 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			let c3 = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
-			let cc = (self.hex_to_u32(c0)? << 12) | (self.hex_to_u32(c1)? << 8) | (self.hex_to_u32(c2)? << 4) | self.hex_to_u32(c3)?;
+			let cc = self.read_u_escape_code()?;
 			if cc >= 0xDC00 && cc <= 0xDFFF
 			{	let c = 0x10000 + (((c-0xD800) << 10) | (cc-0xDC00));
 				Ok((&mut self.buffer[buf_pos ..]).write(&[(0xF0 | (c >> 18)) as u8, (0x80 | ((c >> 12) & 0x3F)) as u8, (0x80 | ((c >> 6) & 0x3F)) as u8, (0x80 | (c & 0x3F)) as u8]).unwrap())
 			}
 			else
-			{	Err(self.format_error("Invalid UTF-16 surrogate pair"))
+			{	let hex = |d: u32| b"0123456789ABCDEF"[(d & 0xF) as usize];
+				self.unread(&[b'\\', b'u', hex(cc >> 12), hex(cc >> 8), hex(cc >> 4), hex(cc)]);
+				self.lone_surrogate_to_utf8(buf_pos, c, "Invalid UTF-16 surrogate pair")
 			}
 		}
 		else
-		{	Err(self.format_error("Escape sequence doesn't map to UTF-8"))
+		{	self.lone_surrogate_to_utf8(buf_pos, c, "Escape sequence doesn't map to UTF-8")
 		}
 	}
 
@@ -1740,7 +2191,11 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	}
 
 	fn read_string_contents(&mut self) -> io::Result<String>
-	{	String::from_utf8(self.read_blob_contents()?).map_err(|_| self.format_error("Invalid UTF-8 string"))
+	{	match String::from_utf8(self.read_blob_contents()?)
+		{	Ok(s) => Ok(s),
+			Err(e) if self.options.invalid_utf8 == Utf8Policy::Replace => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+			Err(_) => Err(self.format_error("Invalid UTF-8 string")),
+		}
 	}
 
 	fn read_blob_contents(&mut self) -> io::Result<Vec<u8>>
@@ -1749,11 +2204,11 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		{	if bytes.len() > self.options.value_size_limit
 			{	return Err(self.format_error("Invalid JSON input: string or blob value is too large"));
 			}
-			let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+			let c = self.next_string_byte()?;
 			match c
 			{	b'"' => break,
 				b'\\' =>
-				{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+				{	let c = self.next_string_byte()?;
 					match c
 					{	b'r' => bytes.push(b'\r'),
 						b'n' => bytes.push(b'\n'),
@@ -1777,14 +2232,14 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	fn read_string_contents_as_bytes(&mut self) -> io::Result<()>
 	{	let mut len = 0;
 		loop
-		{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+		{	let c = self.next_string_byte()?;
 			match c
 			{	b'"' =>
 				{	self.lookahead = b' ';
 					break;
 				}
 				b'\\' =>
-				{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+				{	let c = self.next_string_byte()?;
 					match c
 					{	b'r' =>
 						{	if len < self.buffer.len() {self.buffer[len] = b'\r'; len += 1}
@@ -1819,14 +2274,14 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	fn pipe_blob_contents<U>(&mut self, writer: &mut U) -> io::Result<()> where U: io::Write
 	{	let mut len = 0;
 		loop
-		{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+		{	let c = self.next_string_byte()?;
 			let c = match c
 			{	b'"' =>
 				{	self.lookahead = b' ';
 					break;
 				}
 				b'\\' =>
-				{	let c = self.iter.next().ok_or_else(|| self.format_error("Invalid JSON: unexpected end of input"))?;
+				{	let c = self.next_string_byte()?;
 					match c
 					{	b'r' => b'\r',
 						b'n' => b'\n',
@@ -1983,7 +2438,7 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 						Token::ArrayBegin => return Err(self.format_error("Invalid JSON input: unexpected '['")),
 						Token::ArrayEnd => return Err(self.format_error("Invalid JSON input: unexpected ']'")),
 						Token::ObjectBegin => return Err(self.format_error("Invalid JSON input: unexpected '{'")),
-						Token::ObjectEnd => return Err(self.format_error("Invalid JSON input: unexpected '}'")),
+						Token::ObjectEnd => break,
 						Token::Comma => State::AtKey,
 						Token::Colon => return Err(self.format_error("Invalid JSON input: unexpected ':'")),
 					}
@@ -2090,6 +2545,35 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	{	read_float!(self, f32, std::f32::NAN, std::f32::INFINITY, std::f32::NEG_INFINITY)
 	}
 
+	/// Use read::<Decimal<SCALE>>() to read decimal numbers.
+	fn read_decimal(&mut self, scale: u32) -> io::Result<i128>
+	{	let rounding = self.options.decimal_rounding;
+		let result = match self.next_token()?
+		{	Token::Null => Ok(0),
+			Token::False => Ok(0),
+			Token::True => decimal_from_digits(b"1", 0, false, scale, rounding),
+			Token::Number(exponent, is_negative) =>
+			{	if self.number_tail.is_empty()
+				{	decimal_from_digits(&self.buffer[.. self.buffer_len], exponent, is_negative, scale, rounding)
+				}
+				else
+				{	decimal_from_digits(&self.get_number_digits(), exponent, is_negative, scale, rounding)
+				}
+			},
+			Token::Quote =>
+			{	let s = self.read_string_contents()?;
+				decimal_from_value(&Value::String(s), scale, rounding)
+			},
+			Token::ArrayBegin => Err("Value must be number, not array"),
+			Token::ArrayEnd => Err("Invalid JSON input: unexpected ']'"),
+			Token::ObjectBegin => Err("Value must be number, not object"),
+			Token::ObjectEnd => Err("Invalid JSON input: unexpected '}'"),
+			Token::Comma => Err("Invalid JSON input: unexpected ','"),
+			Token::Colon => Err("Invalid JSON input: unexpected ':'"),
+		};
+		result.map_err(|msg| self.format_error(msg))
+	}
+
 	fn read_and_discard(&mut self) -> io::Result<()>
 	{	match self.next_token()?
 		{	Token::Null => Ok(()),
@@ -2113,8 +2597,15 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 			Token::False => Ok("false".to_string()),
 			Token::True => Ok("true".to_string()),
 			Token::Number(exponent, is_negative) =>
-			{	let len = number_to_string(&mut self.buffer, self.buffer_len, exponent, is_negative).map_err(|_| self.number_error())?;
-				Ok(String::from_utf8_lossy(&self.buffer[0 .. len]).into_owned())
+			{	if self.number_tail.is_empty()
+				{	let len = number_to_string(&mut self.buffer, self.buffer_len, exponent, is_negative).map_err(|_| self.number_error())?;
+					Ok(String::from_utf8_lossy(&self.buffer[0 .. len]).into_owned())
+				}
+				else
+				{	let mut bytes = Vec::new();
+					write_number(&self.get_number_digits(), exponent, is_negative, |part| bytes.write_all(part))?;
+					Ok(String::from_utf8_lossy(&bytes).into_owned())
+				}
 			},
 			Token::Quote => self.read_string_contents(),
 			Token::ArrayBegin => Err(self.format_error("Value must be string, not array")),
@@ -2143,7 +2634,9 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 				Ok(&self.buffer[0 .. 4])
 			},
 			Token::Number(exponent, is_negative) =>
-			{	let len = number_to_string(&mut self.buffer, self.buffer_len, exponent, is_negative).map_err(|_| self.number_error())?;
+			{	// digits that didn't fit the buffer are dropped
+				let exponent = i16::try_from(self.number_tail.len()).ok().and_then(|n| exponent.checked_add(n)).ok_or_else(|| self.number_error())?;
+				let len = number_to_string(&mut self.buffer, self.buffer_len, exponent, is_negative).map_err(|_| self.number_error())?;
 				Ok(&self.buffer[0 .. len])
 			},
 			Token::Quote =>
@@ -2183,16 +2676,13 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 	/// assert_eq!(data, b"\x80\x81");
 	/// ```
 	///
-	/// Encoding (and decoding back) example:
+	/// Encoding (and decoding back) example. To write blobs, use [write_blob()](fn.write_blob.html), [pipe_blob_from()](fn.pipe_blob_from.html) or the [Blob](struct.Blob.html) wrapper type:
 	/// ```
-	/// use nop_json::{escape_bytes, Reader};
-	/// use std::io::Write;
+	/// use nop_json::{write_blob, Reader};
 	///
 	/// let data = b"\x80\x81";
 	/// let mut json_container = Vec::with_capacity(100);
-	/// json_container.push(b'"');
-	/// json_container.write_all(escape_bytes(data).as_ref()).unwrap();
-	/// json_container.push(b'"');
+	/// write_blob(&mut json_container, data).unwrap();
 	/// assert_eq!(json_container, vec![b'"', b'\x80', b'\x81', b'"']);
 	///
 	/// let mut reader = Reader::new(json_container.iter().map(|i| *i));
@@ -2232,6 +2722,210 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		}
 	}
 
+	/// Reads a JSON string and writes it, decoded, to the provided writer. Unlike [reading](struct.Reader.html#method.read) to `String`,
+	/// the string is not loaded to memory, so its size is not limited by [ReaderBuilder::value_size_limit()](struct.ReaderBuilder.html#method.value_size_limit).
+	/// Unlike [pipe_blob()](struct.Reader.html#method.pipe_blob), the data is validated to be UTF-8, and on invalid input an error is returned
+	/// (parts of the string before the invalid character can be already written).
+	///
+	/// Other scalar values are converted to string like reading them to `String` does (`null` becomes `"null"`).
+	///
+	/// ```
+	/// use nop_json::Reader;
+	///
+	/// let mut reader = Reader::new(r#" "Gr\u00FC\u00dfe, \ud83d\ude00" "#.bytes());
+	/// let mut text = Vec::new();
+	/// reader.pipe_string(&mut text).unwrap();
+	/// assert_eq!(String::from_utf8(text).unwrap(), "Grüße, 😀");
+	/// ```
+	pub fn pipe_string<U>(&mut self, writer: &mut U) -> io::Result<()> where U: io::Write
+	{	match self.next_token()?
+		{	Token::Null => writer.write_all(b"null"),
+			Token::False => writer.write_all(b"false"),
+			Token::True => writer.write_all(b"true"),
+			Token::Number(exponent, is_negative) =>
+			{	if self.number_tail.is_empty()
+				{	let len = number_to_string(&mut self.buffer, self.buffer_len, exponent, is_negative).map_err(|_| self.number_error())?;
+					writer.write_all(&self.buffer[0 .. len])
+				}
+				else
+				{	write_number(&self.get_number_digits(), exponent, is_negative, |part| writer.write_all(part))
+				}
+			},
+			Token::Quote =>
+			{	let mut validator = Utf8Validator::new(writer, self.options.invalid_utf8);
+				self.pipe_blob_contents(&mut validator).and_then(|_| validator.finish()).map_err
+				(	|e| match decode_error_message(&e)
+					{	Some(message) => self.format_error(message),
+						None => e
+					}
+				)
+			}
+			Token::ArrayBegin => Err(self.format_error("Value must be string, not array")),
+			Token::ArrayEnd => Err(self.format_error("Invalid JSON input: unexpected ']'")),
+			Token::ObjectBegin => Err(self.format_error("Value must be string, not object")),
+			Token::ObjectEnd => Err(self.format_error("Invalid JSON input: unexpected '}'")),
+			Token::Comma => Err(self.format_error("Invalid JSON input: unexpected ','")),
+			Token::Colon => Err(self.format_error("Invalid JSON input: unexpected ':'")),
+		}
+	}
+
+	/// Returns [io::Read](https://doc.rust-lang.org/std/io/trait.Read.html) object that reads decoded bytes of the current JSON string (or blob),
+	/// till the closing quote. This allows to pass a string of any size to a consumer that pulls data, like a hasher or a decompressor.
+	/// JSON `null` gives empty data. Like with [pipe_blob()](struct.Reader.html#method.pipe_blob), the data is not validated to be UTF-8.
+	///
+	/// If the returned object is dropped before reaching the end of the string, the rest of the string is skipped,
+	/// so this reader is positioned after the string anyway.
+	///
+	/// ```
+	/// use nop_json::Reader;
+	/// use std::io::Read;
+	///
+	/// let mut reader = Reader::new(r#" ["Hello\nall", "skip me", 1] "#.bytes());
+	/// let mut texts = Vec::new();
+	/// let mut n = 0;
+	/// reader.read_array
+	/// (	|reader|
+	/// 	{	n += 1;
+	/// 		if n == 3
+	/// 		{	let _: i32 = reader.read()?;
+	/// 			return Ok(());
+	/// 		}
+	/// 		let mut string_reader = reader.string_reader()?;
+	/// 		let mut text = [0u8; 5];
+	/// 		string_reader.read_exact(&mut text)?;
+	/// 		texts.push(text);
+	/// 		Ok(())
+	/// 	}
+	/// ).unwrap();
+	/// assert_eq!(texts, [*b"Hello", *b"skip "]);
+	/// ```
+	pub fn string_reader(&mut self) -> io::Result<StringReader<'_, T>>
+	{	let is_done = match self.next_token()?
+		{	Token::Null => true,
+			Token::False => return Err(self.format_error("Value must be string, not boolean")),
+			Token::True => return Err(self.format_error("Value must be string, not boolean")),
+			Token::Number(_exponent, _is_negative) => return Err(self.format_error("Value must be string, not number")),
+			Token::Quote => false,
+			Token::ArrayBegin => return Err(self.format_error("Value must be string, not array")),
+			Token::ArrayEnd => return Err(self.format_error("Invalid JSON input: unexpected ']'")),
+			Token::ObjectBegin => return Err(self.format_error("Value must be string, not object")),
+			Token::ObjectEnd => return Err(self.format_error("Invalid JSON input: unexpected '}'")),
+			Token::Comma => return Err(self.format_error("Invalid JSON input: unexpected ','")),
+			Token::Colon => return Err(self.format_error("Invalid JSON input: unexpected ':'")),
+		};
+		Ok(StringReader {reader: self, is_done, pending_from: 0, pending_to: 0})
+	}
+
+	/// Reads string contents till the closing quote to `buf`, decoding escapes. Bytes of decoded `\u` escapes that don't fit `buf` are left in `self.buffer[*pending_from .. *pending_to]`.
+	/// Returns number of bytes stored to `buf`, and whether the closing quote was reached.
+	fn read_string_contents_to(&mut self, buf: &mut [u8], pending_from: &mut usize, pending_to: &mut usize) -> io::Result<(usize, bool)>
+	{	let mut len = (*pending_to - *pending_from).min(buf.len());
+		buf[.. len].copy_from_slice(&self.buffer[*pending_from .. *pending_from+len]);
+		*pending_from += len;
+		while len < buf.len()
+		{	let c = self.next_string_byte()?;
+			buf[len] = match c
+			{	b'"' =>
+				{	self.lookahead = b' ';
+					return Ok((len, true));
+				}
+				b'\\' =>
+				{	let c = self.next_string_byte()?;
+					match c
+					{	b'r' => b'\r',
+						b'n' => b'\n',
+						b't' => b'\t',
+						b'b' => 8,
+						b'f' => 12,
+						b'u' =>
+						{	let n = self.u_escape_to_utf8(0)?;
+							let n_fit = n.min(buf.len() - len);
+							buf[len .. len+n_fit].copy_from_slice(&self.buffer[.. n_fit]);
+							len += n_fit;
+							*pending_from = n_fit;
+							*pending_to = n;
+							if n_fit < n
+							{	break;
+							}
+							continue;
+						}
+						_ => c
+					}
+				}
+				_ => c
+			};
+			len += 1;
+		}
+		Ok((len, false))
+	}
+
+	/// Reads base64-encoded binary data from JSON string. Both standard and URL-safe alphabets are accepted, and padding is optional.
+	/// Like [read_blob()](struct.Reader.html#method.read_blob), `null` is read as empty data, and the decoded data size is limited by [ReaderBuilder::value_size_limit()](struct.ReaderBuilder.html#method.value_size_limit).
+	///
+	/// ```
+	/// use nop_json::Reader;
+	///
+	/// let mut reader = Reader::new(r#" "AID/" "AID_" "AIA" "#.bytes());
+	/// assert_eq!(reader.read_blob_base64().unwrap(), b"\x00\x80\xFF");
+	/// assert_eq!(reader.read_blob_base64().unwrap(), b"\x00\x80\xFF");
+	/// assert_eq!(reader.read_blob_base64().unwrap(), b"\x00\x80");
+	/// ```
+	pub fn read_blob_base64(&mut self) -> io::Result<Vec<u8>>
+	{	let mut bytes = Vec::new();
+		self.pipe_encoded_blob(&mut bytes, BlobEncoding::Base64, self.options.value_size_limit)?;
+		Ok(bytes)
+	}
+
+	/// Like [read_blob_base64()](struct.Reader.html#method.read_blob_base64), but pipes decoded data to the provided writer, so data of any size can be read.
+	pub fn pipe_blob_base64<U>(&mut self, writer: &mut U) -> io::Result<()> where U: io::Write
+	{	self.pipe_encoded_blob(writer, BlobEncoding::Base64, usize::MAX)
+	}
+
+	/// Reads hex-encoded binary data from JSON string. Digits can be lowercase or uppercase.
+	///
+	/// ```
+	/// use nop_json::Reader;
+	///
+	/// let mut reader = Reader::new(r#" "0080ff" "0080FF" "008" "#.bytes());
+	/// assert_eq!(reader.read_blob_hex().unwrap(), b"\x00\x80\xFF");
+	/// assert_eq!(reader.read_blob_hex().unwrap(), b"\x00\x80\xFF");
+	/// assert_eq!(reader.read_blob_hex().unwrap_err().to_string(), "$: Invalid hex string");
+	/// ```
+	pub fn read_blob_hex(&mut self) -> io::Result<Vec<u8>>
+	{	let mut bytes = Vec::new();
+		self.pipe_encoded_blob(&mut bytes, BlobEncoding::Hex, self.options.value_size_limit)?;
+		Ok(bytes)
+	}
+
+	/// Like [read_blob_hex()](struct.Reader.html#method.read_blob_hex), but pipes decoded data to the provided writer.
+	pub fn pipe_blob_hex<U>(&mut self, writer: &mut U) -> io::Result<()> where U: io::Write
+	{	self.pipe_encoded_blob(writer, BlobEncoding::Hex, usize::MAX)
+	}
+
+	fn pipe_encoded_blob<U>(&mut self, writer: &mut U, encoding: BlobEncoding, limit: usize) -> io::Result<()> where U: io::Write
+	{	match self.next_token()?
+		{	Token::Null => Ok(()),
+			Token::False => Err(self.format_error("Value must be string, not boolean")),
+			Token::True => Err(self.format_error("Value must be string, not boolean")),
+			Token::Number(_exponent, _is_negative) => Err(self.format_error("Value must be string, not number")),
+			Token::Quote =>
+			{	let mut decoder = BlobDecoder::new(writer, encoding, limit);
+				self.pipe_blob_contents(&mut decoder).and_then(|_| decoder.finish()).map_err
+				(	|e| match decode_error_message(&e)
+					{	Some(message) => self.format_error(message),
+						None => e
+					}
+				)
+			}
+			Token::ArrayBegin => Err(self.format_error("Value must be string, not array")),
+			Token::ArrayEnd => Err(self.format_error("Invalid JSON input: unexpected ']'")),
+			Token::ObjectBegin => Err(self.format_error("Value must be string, not object")),
+			Token::ObjectEnd => Err(self.format_error("Invalid JSON input: unexpected '}'")),
+			Token::Comma => Err(self.format_error("Invalid JSON input: unexpected ','")),
+			Token::Colon => Err(self.format_error("Invalid JSON input: unexpected ':'")),
+		}
+	}
+
 	fn read_char(&mut self) -> io::Result<char>
 	{	self.read_bytes()?;
 		if self.buffer_len == 0
@@ -2244,22 +2938,29 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		else if c&0xE0 == 0xC0 // 110xxxxx
 		{	if self.buffer_len >= 2
 			{	let c = (self.buffer[1] as u32) & 0x3F | ((c & 0x1F) << 6);
-				return char::from_u32(c).ok_or_else(|| self.format_error("Invalid UTF-8 string"));
+				return char::from_u32(c).map_or_else(|| self.invalid_char(), Ok);
 			}
 		}
 		else if c&0xF0 == 0xE0 // 1110xxxx
 		{	if self.buffer_len >= 3
 			{	let c = (self.buffer[2] as u32) & 0x3F | (((self.buffer[1] as u32) & 0x3F) << 6) | ((c & 0xF) << 12);
-				return char::from_u32(c).ok_or_else(|| self.format_error("Invalid UTF-8 string"));
+				return char::from_u32(c).map_or_else(|| self.invalid_char(), Ok);
 			}
 		}
 		else if c&0xF8 == 0xF0 // 11110xxx
 		{	if self.buffer_len >= 4
 			{	let c = (self.buffer[3] as u32) & 0x3F | (((self.buffer[2] as u32) & 0x3F) << 6) | (((self.buffer[1] as u32) & 0x3F) << 12) | ((c & 0x7) << 18);
-				return char::from_u32(c).ok_or_else(|| self.format_error("Invalid UTF-8 string"));
+				return char::from_u32(c).map_or_else(|| self.invalid_char(), Ok);
 			}
 		}
-		return Err(self.format_error("Invalid UTF-8 string"));
+		return self.invalid_char();
+	}
+
+	fn invalid_char(&self) -> io::Result<char>
+	{	match self.options.invalid_utf8
+		{	Utf8Policy::Replace => Ok(char::REPLACEMENT_CHARACTER),
+			_ => Err(self.format_error("Invalid UTF-8 string"))
+		}
 	}
 
 	/// This method is intended for use in cases when you want to implement [TryFromJson](trait.TryFromJson.html) manually.
@@ -2531,19 +3232,81 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		}
 	}
 
+	/// Converts the number that `next_token()` has just read to `Value::Number`, or to `Value::BigNumber` if the mantissa doesn't fit `u64`.
+	fn number_to_value(&self, exponent: i16, is_negative: bool) -> Value
+	{	if self.number_tail.is_empty()
+		{	let mut mantissa = 0u64;
+			let mut is_big = false;
+			for c in &self.buffer[.. self.buffer_len]
+			{	match mantissa.checked_mul(10).and_then(|m| m.checked_add((*c - b'0') as u64))
+				{	Some(m) => mantissa = m,
+					None => {is_big = true; break}
+				}
+			}
+			if !is_big
+			{	return Value::Number(mantissa, exponent, is_negative);
+			}
+		}
+		// next_token() stores only ASCII digits
+		Value::BigNumber(Digits::new_unchecked(String::from_utf8(self.get_number_digits()).unwrap_or_default()), exponent, is_negative)
+	}
+
+	/// Reads next value, and passes it to `out` while reading, so it's never collected as a whole. Errors from `out` get path of the value.
+	pub(crate) fn pipe_to_sink(&mut self, out: &mut dyn JsonSink) -> io::Result<()>
+	{	match self.get_next_char()
+		{	b'[' =>
+			{	out.begin_array()?;
+				let mut index = 0;
+				self.read_array
+				(	|reader|
+					{	if let Some(p) = reader.path.last_mut()
+						{	*p = PathItem::Index(index);
+						}
+						index += 1;
+						reader.pipe_to_sink(out)
+					}
+				)?;
+				out.end_array()
+			}
+			b'{' =>
+			{	out.begin_object()?;
+				self.read_object(|reader, key| {out.key(&key)?; reader.pipe_to_sink(out)})?;
+				out.end_object()
+			}
+			_ =>
+			{	let result = match self.next_token()?
+				{	Token::Null => out.null(),
+					Token::False => out.bool(false),
+					Token::True => out.bool(true),
+					Token::Number(exponent, is_negative) =>
+					{	if self.number_tail.is_empty()
+						{	out.number(&self.buffer[.. self.buffer_len], exponent, is_negative)
+						}
+						else
+						{	out.number(&self.get_number_digits(), exponent, is_negative)
+						}
+					}
+					Token::Quote =>
+					{	let s = self.read_string_contents()?;
+						out.string(&s)
+					}
+					Token::ArrayEnd => return Err(self.format_error("Invalid JSON input: unexpected ']'")),
+					Token::ObjectEnd => return Err(self.format_error("Invalid JSON input: unexpected '}'")),
+					Token::Comma => return Err(self.format_error("Invalid JSON input: unexpected ','")),
+					Token::Colon => return Err(self.format_error("Invalid JSON input: unexpected ':'")),
+					Token::ArrayBegin | Token::ObjectBegin => unreachable!(),
+				};
+				result.map_err(|e| self.format_error(&e.to_string()))
+			}
+		}
+	}
+
 	fn read_value(&mut self) -> io::Result<Value>
 	{	match self.next_token()?
 		{	Token::Null => Ok(Value::Null),
 			Token::False => Ok(Value::Bool(false)),
 			Token::True => Ok(Value::Bool(true)),
-			Token::Number(exponent, is_negative) =>
-			{	let mut mantissa = 0u64;
-				for c in &self.buffer[.. self.buffer_len]
-				{	mantissa = mantissa.checked_mul(10).ok_or_else(|| self.number_error())?;
-					mantissa = mantissa.checked_add((*c - b'0') as u64).ok_or_else(|| self.number_error())?;
-				}
-				Ok(Value::Number(mantissa, exponent, is_negative))
-			},
+			Token::Number(exponent, is_negative) => Ok(self.number_to_value(exponent, is_negative)),
 			Token::Quote => Ok(Value::String(self.read_string_contents()?)),
 			Token::ArrayBegin =>
 			{	let mut vec = Vec::new();
@@ -2573,7 +3336,7 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 			}
 			Token::ArrayEnd => Err(self.format_error("Invalid JSON input: unexpected ']'")),
 			Token::ObjectBegin =>
-			{	let mut obj = HashMap::new();
+			{	let mut obj = Map::new();
 				if self.get_next_char() == b'}'
 				{	self.lookahead = b' ';
 					self.leave(); // empty object: the '}' is consumed here, not via next_token
@@ -2631,3 +3394,31 @@ impl<T> Reader<T> where T: Iterator<Item=u8>
 		}
 	}
 }
+
+/// Object that [Reader::string_reader()](struct.Reader.html#method.string_reader) returns.
+pub struct StringReader<'a, T> where T: Iterator<Item=u8>
+{	reader: &'a mut Reader<T>,
+	is_done: bool,
+	pending_from: usize,
+	pending_to: usize,
+}
+
+impl<'a, T> io::Read for StringReader<'a, T> where T: Iterator<Item=u8>
+{	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+	{	if buf.is_empty() || self.is_done && self.pending_from == self.pending_to
+		{	return Ok(0);
+		}
+		let (len, is_done) = self.reader.read_string_contents_to(buf, &mut self.pending_from, &mut self.pending_to)?;
+		self.is_done = is_done;
+		Ok(len)
+	}
+}
+
+impl<'a, T> Drop for StringReader<'a, T> where T: Iterator<Item=u8>
+{	fn drop(&mut self)
+	{	if !self.is_done
+		{	let _ = self.reader.skip_string();
+		}
+	}
+}
+
@@ -0,0 +1,890 @@
+use crate::nop_json::{Reader, float_to_digits, write_number, READER_BUFFER_SIZE};
+use crate::debug_to_json::DebugToJson;
+use crate::escape::{Escaping, push_escaped, utf8_len};
+use crate::json_sink::JsonSink;
+
+use std::io;
+use numtoa::NumToA;
+
+/// Settings for [FormatWriter](struct.FormatWriter.html): indentation, spaces and key order.
+///
+/// [compact()](#method.compact) settings produce the same JSON as `DebugToJson` and `WriteToJson` do, and [pretty()](#method.pretty) puts each
+/// array element and object property on it's own line.
+///
+/// ```
+/// use nop_json::{Formatting, DebugToJson};
+/// use std::collections::HashMap;
+///
+/// let mut map = HashMap::new();
+/// map.insert("b".to_string(), vec![1, 2]);
+/// map.insert("a".to_string(), vec![]);
+///
+/// let formatting = Formatting::pretty().indent_tabs(1).sort_keys(true);
+/// assert_eq!(map.to_json_string_with(&formatting).unwrap(), "{\n\t\"a\": [],\n\t\"b\": [\n\t\t1,\n\t\t2\n\t]\n}");
+///
+/// let formatting = Formatting::compact().space_after_colon(true).sort_keys(true);
+/// assert_eq!(map.to_json_string_with(&formatting).unwrap(), r#"{"a": [],"b": [1,2]}"#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Formatting
+{	indent: String,
+	newline: String,
+	space_after_colon: bool,
+	sort_keys: bool,
+	canonical: bool,
+	escaping: Escaping,
+}
+
+impl Formatting
+{	/// No whitespace at all.
+	pub fn compact() -> Self
+	{	Formatting {indent: String::new(), newline: String::new(), space_after_colon: false, sort_keys: false, canonical: false, escaping: Escaping::Minimal}
+	}
+
+	/// Each array element and object property on new line, indented with 2 spaces, and a space after colon.
+	pub fn pretty() -> Self
+	{	Formatting {indent: "  ".to_string(), newline: "\n".to_string(), space_after_colon: true, sort_keys: false, canonical: false, escaping: Escaping::Minimal}
+	}
+
+	/// Canonical JSON, as defined by [RFC 8785](https://tools.ietf.org/html/rfc8785) (JSON Canonicalization Scheme): no whitespace,
+	/// object properties sorted by key, numbers formatted like ECMAScript does (so they're converted to `f64`, and `1.50` becomes `1.5`, and `1e2` becomes `100`),
+	/// and strings escaped minimally. The same data always produces the same bytes, so the result can be hashed or signed.
+	///
+	/// Numbers that `f64` can't hold (like `1e400`) give error. Also lone UTF-16 surrogates in strings give error.
+	///
+	/// ```
+	/// use nop_json::{Formatting, json, to_canonical_json};
+	///
+	/// let v = json!({"b": [1.50, 1e21, 1e-7, -0.0], "a": "\u{20ac}\n", "\u{1F600}": 0, "\u{FB33}": 0});
+	/// assert_eq!(to_canonical_json(&v).unwrap(), "{\"a\":\"\u{20ac}\\n\",\"b\":[1.5,1e+21,1e-7,0],\"\u{1F600}\":0,\"\u{FB33}\":0}");
+	/// ```
+	pub fn canonical() -> Self
+	{	Formatting {indent: String::new(), newline: String::new(), space_after_colon: false, sort_keys: true, canonical: true, escaping: Escaping::Minimal}
+	}
+
+	/// Indent each nesting level with `width` spaces.
+	pub fn indent_spaces(mut self, width: usize) -> Self
+	{	self.indent = " ".repeat(width);
+		self
+	}
+
+	/// Indent each nesting level with `count` tab characters.
+	pub fn indent_tabs(mut self, count: usize) -> Self
+	{	self.indent = "\t".repeat(count);
+		self
+	}
+
+	/// Line separator, like `"\n"` or `"\r\n"`. Empty string puts everything on one line (and then indent is not used).
+	pub fn newline(mut self, newline: &str) -> Self
+	{	self.newline = newline.to_string();
+		self
+	}
+
+	/// Whether to put a space between object key and value.
+	pub fn space_after_colon(mut self, space_after_colon: bool) -> Self
+	{	self.space_after_colon = space_after_colon;
+		self
+	}
+
+	/// Whether to sort object properties by key. Properties are sorted on all nesting levels, by bytes of the keys as they are written (escape sequences are not decoded).
+	/// Canonical JSON sorts by UTF-16 code units of the keys (like JavaScript sorts strings).
+	/// To sort, each object is collected in memory before it's written.
+	pub fn sort_keys(mut self, sort_keys: bool) -> Self
+	{	self.sort_keys = sort_keys;
+		self
+	}
+
+	/// Which characters to escape in strings. Strings are escaped as they are written, without collecting them in memory.
+	/// Canonical JSON always uses minimal escaping. Escape sequences in the input are kept, and bytes that are not valid UTF-8 (like in blobs) are left as is.
+	///
+	/// ```
+	/// use nop_json::{Formatting, Escaping, DebugToJson};
+	///
+	/// let v = vec!["<b>\u{2028}".to_string()];
+	/// assert_eq!(v.to_json_string_with(&Formatting::compact().escaping(Escaping::JavaScript)).unwrap(), r#"["\u003Cb\u003E\u2028"]"#);
+	/// ```
+	pub fn escaping(mut self, escaping: Escaping) -> Self
+	{	self.escaping = escaping;
+		self
+	}
+
+	/// Create a [FormatWriter](struct.FormatWriter.html) that writes to `out` using these settings.
+	pub fn build<W>(self, out: W) -> FormatWriter<W> where W: io::Write
+	{	FormatWriter
+		{	out,
+			formatting: self,
+			stack: Vec::new(),
+			buffer: Vec::new(),
+			in_string: false,
+			in_escape: false,
+			unicode_digits: 0,
+			in_scalar: false,
+			expect: Expect::Root,
+			started: false,
+			token: Vec::new(),
+			raw_depth: None,
+			raw_values: 0,
+		}
+	}
+}
+
+impl Default for Formatting
+{	fn default() -> Self
+	{	Self::compact()
+	}
+}
+
+/// What [FormatWriter](struct.FormatWriter.html) accepts next.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Expect
+{	/// Top-level value.
+	Root,
+	/// Array element after `,`.
+	Value,
+	/// Object property value after `:`.
+	PropertyValue,
+	/// Array element or `]`, after `[`.
+	FirstValue,
+	/// Object key after `,`.
+	Key,
+	/// Object key or `}`, after `{`.
+	FirstKey,
+	Colon,
+	/// `,` or end of array or object, after a value.
+	Comma,
+}
+
+struct Frame
+{	is_object: bool,
+	is_empty: bool,
+	/// When sorting keys, object properties collected so far: (key as JSON string, key-value pair).
+	members: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+	in_key: bool,
+}
+
+/// [io::Write](https://doc.rust-lang.org/std/io/trait.Write.html) adapter, that reformats JSON written to it according to [Formatting](struct.Formatting.html),
+/// and writes the result to the underlying writer.
+///
+/// It's also a [JsonSink](trait.JsonSink.html), so built-in containers and derived types are formatted as they are written with
+/// [write_to_sink()](trait.WriteToJson.html#method.write_to_sink) or [to_sink()](trait.DebugToJson.html#method.to_sink), without parsing their output.
+/// Anything that implements [WriteToJson](trait.WriteToJson.html) can also be written through it as text.
+/// Call [finish()](#method.finish) at the end, to check that the JSON was complete, and to get back the underlying writer.
+/// Bytes that don't form valid JSON (like `[1 2]`) give `io::ErrorKind::InvalidData` error.
+///
+/// ```
+/// use nop_json::{Formatting, WriteToJson};
+/// use std::collections::BTreeMap;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert("id".to_string(), (1, "one".to_string()));
+///
+/// let mut writer = Formatting::pretty().indent_spaces(4).build(Vec::new());
+/// map.write_to_sink(&mut writer).unwrap();
+/// let out = writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "{\n    \"id\": [\n        1,\n        \"one\"\n    ]\n}");
+/// ```
+pub struct FormatWriter<W: io::Write>
+{	out: W,
+	formatting: Formatting,
+	stack: Vec<Frame>,
+	buffer: Vec<u8>,
+	in_string: bool,
+	in_escape: bool,
+	/// Number of hex digits expected after `\u` in a string.
+	unicode_digits: u8,
+	in_scalar: bool,
+	expect: Expect,
+	started: bool,
+	/// Number or literal being collected, string in canonical mode, or bytes of a non-ASCII character that may need escaping.
+	token: Vec<u8>,
+	/// Nesting depth where `JsonSink::begin_raw()` was called.
+	raw_depth: Option<usize>,
+	/// Number of values written at `raw_depth` since `begin_raw()`.
+	raw_values: usize,
+}
+
+impl<W: io::Write> FormatWriter<W>
+{	/// Checks that complete JSON was written, and returns the underlying writer.
+	pub fn finish(mut self) -> io::Result<W>
+	{	if self.in_scalar
+		{	self.end_scalar()?;
+		}
+		if self.in_string || !self.stack.is_empty() || self.raw_depth.is_some()
+		{	return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid JSON input: unexpected end of input"));
+		}
+		self.out.write_all(&self.buffer)?;
+		self.out.flush()?;
+		Ok(self.out)
+	}
+
+	/// Sends bytes to the innermost object that collects it's members for sorting, or to the output buffer.
+	fn emit(&mut self, bytes: &[u8])
+	{	for frame in self.stack.iter_mut().rev()
+		{	if let Some(ref mut members) = frame.members
+			{	if frame.in_key
+				{	members.last_mut().unwrap().0.extend_from_slice(bytes);
+				}
+				members.last_mut().unwrap().1.extend_from_slice(bytes);
+				return;
+			}
+		}
+		self.buffer.extend_from_slice(bytes);
+	}
+
+	fn emit_newline(&mut self, depth: usize)
+	{	if !self.formatting.newline.is_empty()
+		{	let mut line = self.formatting.newline.clone().into_bytes();
+			for _ in 0 .. depth
+			{	line.extend_from_slice(self.formatting.indent.as_bytes());
+			}
+			self.emit(&line);
+		}
+	}
+
+	/// Called before the first byte of array element, object property, or top-level value.
+	fn begin_item(&mut self)
+	{	let depth = self.stack.len();
+		match self.stack.last_mut()
+		{	None =>
+			{	if self.started
+				{	let newline = if self.formatting.newline.is_empty() {b"\n".to_vec()} else {self.formatting.newline.clone().into_bytes()};
+					self.emit(&newline);
+				}
+				self.started = true;
+			}
+			Some(frame) =>
+			{	let is_first = frame.is_empty;
+				frame.is_empty = false;
+				let is_sorted = match frame.members
+				{	Some(ref mut members) =>
+					{	members.push((Vec::new(), Vec::new()));
+						true
+					}
+					None => false
+				};
+				if !is_first && !is_sorted
+				{	self.emit(b",");
+				}
+				self.emit_newline(depth);
+				if let Some(frame) = self.stack.last_mut()
+				{	frame.in_key = frame.is_object;
+				}
+			}
+		}
+	}
+
+	fn end_container(&mut self, c: u8) -> io::Result<()>
+	{	let frame = match self.stack.pop()
+		{	Some(frame) if frame.is_object == (c == b'}') => frame,
+			_ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid JSON input: unexpected end of array or object"))
+		};
+		if let Some(mut members) = frame.members
+		{	if self.formatting.canonical
+			{	members.sort_by(|a, b| canonical_key_units(key_body(&a.0)).cmp(canonical_key_units(key_body(&b.0))));
+			}
+			else
+			{	members.sort_by(|a, b| key_body(&a.0).cmp(key_body(&b.0)));
+			}
+			for (i, (_, member)) in members.iter().enumerate()
+			{	if i > 0
+				{	self.emit(b",");
+				}
+				self.emit(member);
+			}
+		}
+		if !frame.is_empty
+		{	self.emit_newline(self.stack.len());
+		}
+		self.emit(&[c]);
+		self.end_value();
+		Ok(())
+	}
+
+	/// Called after a complete value.
+	fn end_value(&mut self)
+	{	if self.raw_depth == Some(self.stack.len())
+		{	self.raw_values += 1;
+		}
+		self.expect = match self.stack.last()
+		{	None => Expect::Root,
+			Some(frame) if frame.in_key => Expect::Colon,
+			Some(_) => Expect::Comma,
+		};
+	}
+
+	/// Checks and writes collected number or literal. Numbers are written as they are, and in canonical mode they are reformatted.
+	fn end_scalar(&mut self) -> io::Result<()>
+	{	self.in_scalar = false;
+		let token = std::mem::take(&mut self.token);
+		match &token[..]
+		{	b"true" | b"false" | b"null" => self.emit(&token),
+			_ if !is_number(&token) => return Err(invalid_data("Invalid JSON input: invalid number format")),
+			_ if !self.formatting.canonical => self.emit(&token),
+			_ => self.emit_canonical_number(&token)?,
+		}
+		self.token = token;
+		self.token.clear();
+		self.end_value();
+		Ok(())
+	}
+
+	/// Writes number given in JSON syntax like ECMAScript does.
+	fn emit_canonical_number(&mut self, number: &[u8]) -> io::Result<()>
+	{	let value = std::str::from_utf8(number).ok().and_then(|s| s.parse::<f64>().ok()).filter(|v| v.is_finite()).ok_or_else(|| invalid_data("Number is out of range for canonical JSON"))?;
+		let mut buffer = Vec::new();
+		write_ecmascript_number(value, &mut buffer)?;
+		self.emit(&buffer);
+		Ok(())
+	}
+
+	/// Like `emit()`, but escapes the bytes according to `Formatting::escaping`.
+	fn emit_escaped(&mut self, bytes: &[u8])
+	{	let escaping = self.formatting.escaping;
+		for frame in self.stack.iter_mut().rev()
+		{	if let Some(ref mut members) = frame.members
+			{	let member = members.last_mut().unwrap();
+				if frame.in_key
+				{	push_escaped(&mut member.0, bytes, escaping);
+				}
+				push_escaped(&mut member.1, bytes, escaping);
+				return;
+			}
+		}
+		push_escaped(&mut self.buffer, bytes, escaping);
+	}
+
+	/// Writes quoted and escaped string, given to the writer as [JsonSink](trait.JsonSink.html) call.
+	fn emit_string(&mut self, value: &str)
+	{	if self.formatting.canonical
+		{	let mut buffer = Vec::with_capacity(value.len() + 2);
+			buffer.push(b'"');
+			push_canonical_string(&mut buffer, value);
+			buffer.push(b'"');
+			self.emit(&buffer);
+		}
+		else
+		{	self.emit(b"\"");
+			self.emit_escaped(value.as_bytes());
+			self.emit(b"\"");
+		}
+	}
+
+	/// Checks that a value can be written here as [JsonSink](trait.JsonSink.html) call. After a value in array, this call starts the next element, like `,` does.
+	fn check_sink_value(&mut self) -> io::Result<()>
+	{	if self.in_scalar
+		{	self.end_scalar()?;
+		}
+		let is_in_array = self.stack.last().is_some_and(|frame| !frame.is_object);
+		match self.expect
+		{	_ if self.raw_depth.is_some() || self.in_string => Err(invalid_data("Invalid JSON input: unexpected value")),
+			Expect::Root | Expect::Value | Expect::FirstValue | Expect::PropertyValue => Ok(()),
+			Expect::Comma if is_in_array =>
+			{	self.expect = Expect::Value;
+				Ok(())
+			}
+			_ => Err(invalid_data("Invalid JSON input: unexpected value"))
+		}
+	}
+
+	fn begin_sink_value(&mut self) -> io::Result<()>
+	{	self.check_sink_value()?;
+		if self.expect != Expect::PropertyValue
+		{	self.begin_item();
+		}
+		Ok(())
+	}
+
+	fn begin_sink_container(&mut self, is_object: bool) -> io::Result<()>
+	{	self.begin_sink_value()?;
+		self.emit(if is_object {b"{"} else {b"["});
+		let members = if is_object && self.formatting.sort_keys {Some(Vec::new())} else {None};
+		self.stack.push(Frame {is_object, is_empty: true, members, in_key: false});
+		self.expect = if is_object {Expect::FirstKey} else {Expect::FirstValue};
+		Ok(())
+	}
+
+	fn end_sink_container(&mut self, is_object: bool) -> io::Result<()>
+	{	if self.in_scalar
+		{	self.end_scalar()?;
+		}
+		let is_expected = match self.expect
+		{	Expect::Comma => true,
+			Expect::FirstValue => !is_object,
+			Expect::FirstKey => is_object,
+			_ => false
+		};
+		if !is_expected || self.raw_depth.is_some() || self.in_string
+		{	return Err(invalid_data(if is_object {"Invalid JSON input: unexpected end of object"} else {"Invalid JSON input: unexpected end of array"}));
+		}
+		self.end_container(if is_object {b'}'} else {b']'})
+	}
+
+	/// Writes string collected in canonical mode.
+	fn end_string(&mut self) -> io::Result<()>
+	{	let token = std::mem::take(&mut self.token);
+		let s = unescape_string(&token[1 .. token.len()-1])?;
+		let mut buffer = Vec::with_capacity(s.len() + 2);
+		buffer.push(b'"');
+		push_canonical_string(&mut buffer, &s);
+		buffer.push(b'"');
+		self.emit(&buffer);
+		Ok(())
+	}
+
+	/// Writes bytes of a non-ASCII character, collected in `token`. If they are not valid UTF-8, they're written as they are.
+	fn end_char(&mut self)
+	{	if !self.token.is_empty()
+		{	let mut token = std::mem::take(&mut self.token);
+			self.emit_escaped(&token);
+			token.clear();
+			self.token = token;
+		}
+	}
+
+	/// Checks and writes byte inside a string. Escape sequences in the input are kept as they are, and other characters are escaped according to `Formatting::escaping`.
+	fn put_string_byte(&mut self, c: u8) -> io::Result<()>
+	{	let is_char = self.unicode_digits == 0 && !self.in_escape && c != b'\\' && c != b'"';
+		if self.unicode_digits > 0
+		{	if !c.is_ascii_hexdigit()
+			{	return Err(invalid_data("Invalid JSON input: error in escape sequence"));
+			}
+			self.unicode_digits -= 1;
+		}
+		else if self.in_escape
+		{	match c
+			{	b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+				b'u' => self.unicode_digits = 4,
+				_ => return Err(invalid_data("Invalid JSON input: error in escape sequence"))
+			}
+			self.in_escape = false;
+		}
+		else if c == b'\\'
+		{	self.in_escape = true;
+		}
+		else if c == b'"'
+		{	self.in_string = false;
+		}
+		if self.formatting.canonical
+		{	self.token.push(c);
+			if !self.in_string
+			{	self.end_string()?;
+			}
+		}
+		else if is_char && self.formatting.escaping != Escaping::Minimal
+		{	// collect bytes of non-ASCII character, till it's complete
+			if c & 0xC0 != 0x80
+			{	self.end_char();
+			}
+			if c < 0x80
+			{	self.emit_escaped(&[c]);
+			}
+			else
+			{	self.token.push(c);
+				if self.token.len() >= utf8_len(self.token[0])
+				{	self.end_char();
+				}
+			}
+		}
+		else
+		{	self.end_char();
+			self.emit(&[c]);
+		}
+		if !self.in_string
+		{	self.end_value();
+		}
+		Ok(())
+	}
+
+	fn put_byte(&mut self, c: u8) -> io::Result<()>
+	{	if self.in_string
+		{	return self.put_string_byte(c);
+		}
+		if self.in_scalar
+		{	if is_scalar_byte(c)
+			{	self.token.push(c);
+				return Ok(());
+			}
+			self.end_scalar()?;
+		}
+		match c
+		{	b' ' | b'\t' | b'\r' | b'\n' => {}
+			b',' if self.expect == Expect::Comma =>
+			{	self.expect = if self.stack.last().map(|frame| frame.is_object) == Some(true) {Expect::Key} else {Expect::Value};
+			}
+			b':' if self.expect == Expect::Colon =>
+			{	if let Some(frame) = self.stack.last_mut()
+				{	frame.in_key = false;
+				}
+				let colon: &[u8] = if self.formatting.space_after_colon {b": "} else {b":"};
+				self.emit(colon);
+				self.expect = Expect::PropertyValue;
+			}
+			b']' | b'}' if matches!(self.expect, Expect::Comma | Expect::FirstValue | Expect::FirstKey) =>
+			{	self.end_container(c)?;
+			}
+			b'"' | b'[' | b'{' | b'-' | b'0' ..= b'9' | b'a' ..= b'z' if matches!(self.expect, Expect::Root | Expect::Value | Expect::FirstValue | Expect::PropertyValue) || c == b'"' && matches!(self.expect, Expect::Key | Expect::FirstKey) =>
+			{	if self.expect != Expect::PropertyValue
+				{	self.begin_item();
+				}
+				match c
+				{	b'"' =>
+					{	if self.formatting.canonical
+						{	self.token.push(c);
+						}
+						else
+						{	self.emit(&[c]);
+						}
+						self.in_string = true;
+					}
+					b'[' | b'{' =>
+					{	self.emit(&[c]);
+						let members = if c == b'{' && self.formatting.sort_keys {Some(Vec::new())} else {None};
+						self.stack.push(Frame {is_object: c == b'{', is_empty: true, members, in_key: false});
+						self.expect = if c == b'{' {Expect::FirstKey} else {Expect::FirstValue};
+					}
+					_ =>
+					{	self.token.push(c);
+						self.in_scalar = true;
+					}
+				}
+			}
+			_ =>
+			{	return Err(invalid_data(&format!("Invalid JSON input: unexpected {}", if c.is_ascii_graphic() {format!("'{}'", c as char)} else {format!("byte 0x{:02X}", c)})));
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Bytes that can continue a number or literal.
+fn is_scalar_byte(c: u8) -> bool
+{	c.is_ascii_alphanumeric() || c == b'+' || c == b'-' || c == b'.'
+}
+
+/// Checks that the token is a number in JSON syntax, like `-1.5e+10`.
+fn is_number(token: &[u8]) -> bool
+{	let skip_digits = |pos: &mut usize| -> usize
+	{	let from = *pos;
+		while token.get(*pos).is_some_and(u8::is_ascii_digit)
+		{	*pos += 1;
+		}
+		*pos - from
+	};
+	let mut pos = usize::from(token.first() == Some(&b'-'));
+	let int_len = skip_digits(&mut pos);
+	if int_len == 0 || int_len > 1 && token[pos - int_len] == b'0'
+	{	return false;
+	}
+	if token.get(pos) == Some(&b'.')
+	{	pos += 1;
+		if skip_digits(&mut pos) == 0
+		{	return false;
+		}
+	}
+	if matches!(token.get(pos), Some(b'e' | b'E'))
+	{	pos += 1;
+		if matches!(token.get(pos), Some(b'+' | b'-'))
+		{	pos += 1;
+		}
+		if skip_digits(&mut pos) == 0
+		{	return false;
+		}
+	}
+	pos == token.len()
+}
+
+/// Decodes escape sequences in JSON string contents. Lone UTF-16 surrogates and bytes that are not valid UTF-8 give error.
+fn unescape_string(body: &[u8]) -> io::Result<String>
+{	let mut bytes = Vec::with_capacity(body.len());
+	let mut pending_surrogate = None;
+	let mut iter = body.iter();
+	while let Some(&c) = iter.next()
+	{	if c != b'\\'
+		{	if pending_surrogate.is_some()
+			{	return Err(invalid_data("Invalid JSON input: lone surrogate in string"));
+			}
+			bytes.push(c);
+			continue;
+		}
+		let c = match iter.next()
+		{	Some(b'"') => '"',
+			Some(b'\\') => '\\',
+			Some(b'/') => '/',
+			Some(b'b') => '\u{8}',
+			Some(b'f') => '\u{c}',
+			Some(b'n') => '\n',
+			Some(b'r') => '\r',
+			Some(b't') => '\t',
+			Some(b'u') =>
+			{	let code = (0 .. 4).try_fold(0, |code, _| Some(code*16 + (*iter.next()? as char).to_digit(16)?)).ok_or_else(|| invalid_data("Invalid JSON input: error in escape sequence"))?;
+				match (pending_surrogate.take(), code)
+				{	(None, 0xD800 ..= 0xDBFF) =>
+					{	pending_surrogate = Some(code);
+						continue;
+					}
+					(Some(high), 0xDC00 ..= 0xDFFF) => char::from_u32(0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00)).unwrap(),
+					(None, _) => char::from_u32(code).ok_or_else(|| invalid_data("Invalid JSON input: lone surrogate in string"))?,
+					(Some(_), _) => return Err(invalid_data("Invalid JSON input: lone surrogate in string")),
+				}
+			}
+			_ => return Err(invalid_data("Invalid JSON input: error in escape sequence"))
+		};
+		if pending_surrogate.is_some()
+		{	return Err(invalid_data("Invalid JSON input: lone surrogate in string"));
+		}
+		let mut utf8 = [0; 4];
+		bytes.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+	}
+	if pending_surrogate.is_some()
+	{	return Err(invalid_data("Invalid JSON input: lone surrogate in string"));
+	}
+	String::from_utf8(bytes).map_err(|_| invalid_data("Invalid JSON input: string is not valid UTF-8"))
+}
+
+/// Object key without the quotes, as it was written.
+fn key_body(key: &[u8]) -> &[u8]
+{	key.get(1 .. key.len().saturating_sub(1)).unwrap_or_default()
+}
+
+/// UTF-16 code units of a key written by `push_canonical_string()` (without the quotes), for sorting keys like RFC 8785 requires.
+fn canonical_key_units(body: &[u8]) -> impl Iterator<Item=u16> + '_
+{	let mut chars = std::str::from_utf8(body).unwrap_or_default().chars();
+	let chars = std::iter::from_fn
+	(	move ||
+		{	let c = chars.next()?;
+			if c != '\\'
+			{	return Some(c);
+			}
+			Some
+			(	match chars.next()?
+				{	'b' => '\u{8}',
+					'f' => '\u{c}',
+					'n' => '\n',
+					'r' => '\r',
+					't' => '\t',
+					'u' => (0 .. 4).try_fold(0, |code, _| Some(code*16 + chars.next()?.to_digit(16)?)).and_then(char::from_u32)?,
+					c => c
+				}
+			)
+		}
+	);
+	chars.flat_map
+	(	|c|
+		{	let mut units = [0u16; 2];
+			let len = c.encode_utf16(&mut units).len();
+			units.into_iter().take(len)
+		}
+	)
+}
+
+/// Escapes string for canonical JSON: only `"`, `\` and control characters.
+fn push_canonical_string(buffer: &mut Vec<u8>, s: &str)
+{	for c in s.chars()
+	{	match c
+		{	'"' => buffer.extend_from_slice(b"\\\""),
+			'\\' => buffer.extend_from_slice(b"\\\\"),
+			'\u{8}' => buffer.extend_from_slice(b"\\b"),
+			'\t' => buffer.extend_from_slice(b"\\t"),
+			'\n' => buffer.extend_from_slice(b"\\n"),
+			'\u{c}' => buffer.extend_from_slice(b"\\f"),
+			'\r' => buffer.extend_from_slice(b"\\r"),
+			'\u{0}' ..= '\u{1f}' => buffer.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes()),
+			_ =>
+			{	let mut utf8 = [0; 4];
+				buffer.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+			}
+		}
+	}
+}
+
+impl<W: io::Write> io::Write for FormatWriter<W>
+{	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{	for &c in buf
+		{	self.put_byte(c)?;
+		}
+		self.out.write_all(&self.buffer)?;
+		self.buffer.clear();
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()>
+	{	self.out.write_all(&self.buffer)?;
+		self.buffer.clear();
+		self.out.flush()
+	}
+}
+
+/// Structural output is formatted directly. Raw text, between `begin_raw()` and `end_raw()`, is parsed and reformatted like bytes written through `io::Write`.
+impl<W: io::Write> JsonSink for FormatWriter<W>
+{	fn begin_array(&mut self) -> io::Result<()>
+	{	self.begin_sink_container(false)
+	}
+
+	fn end_array(&mut self) -> io::Result<()>
+	{	self.end_sink_container(false)
+	}
+
+	fn begin_object(&mut self) -> io::Result<()>
+	{	self.begin_sink_container(true)
+	}
+
+	fn key(&mut self, key: &str) -> io::Result<()>
+	{	if self.in_scalar
+		{	self.end_scalar()?;
+		}
+		let is_in_object = self.stack.last().is_some_and(|frame| frame.is_object);
+		let is_expected = match self.expect
+		{	Expect::Key | Expect::FirstKey => true,
+			Expect::Comma => is_in_object,
+			_ => false
+		};
+		if !is_expected || self.raw_depth.is_some() || self.in_string
+		{	return Err(invalid_data("Invalid JSON input: unexpected property name"));
+		}
+		self.begin_item();
+		self.emit_string(key);
+		if let Some(frame) = self.stack.last_mut()
+		{	frame.in_key = false;
+		}
+		let colon: &[u8] = if self.formatting.space_after_colon {b": "} else {b":"};
+		self.emit(colon);
+		self.expect = Expect::PropertyValue;
+		Ok(())
+	}
+
+	fn end_object(&mut self) -> io::Result<()>
+	{	self.end_sink_container(true)
+	}
+
+	fn null(&mut self) -> io::Result<()>
+	{	self.begin_sink_value()?;
+		self.emit(b"null");
+		self.end_value();
+		Ok(())
+	}
+
+	fn bool(&mut self, value: bool) -> io::Result<()>
+	{	self.begin_sink_value()?;
+		self.emit(if value {b"true"} else {b"false"});
+		self.end_value();
+		Ok(())
+	}
+
+	fn number(&mut self, digits: &[u8], exponent: i16, is_negative: bool) -> io::Result<()>
+	{	if !digits.iter().all(u8::is_ascii_digit)
+		{	return Err(invalid_data("Invalid JSON input: invalid number format"));
+		}
+		self.begin_sink_value()?;
+		let mut buffer = Vec::with_capacity(digits.len() + 8);
+		if self.formatting.canonical
+		{	let mut exponent_buffer = [0u8; 8];
+			if is_negative
+			{	buffer.push(b'-');
+			}
+			buffer.extend_from_slice(if digits.is_empty() {b"0"} else {digits});
+			buffer.push(b'e');
+			buffer.extend_from_slice(exponent.numtoa(10, &mut exponent_buffer));
+			self.emit_canonical_number(&buffer)?;
+		}
+		else
+		{	write_number(digits, exponent, is_negative, |part| {buffer.extend_from_slice(part); Ok::<_, ()>(())}).map_err(|_| invalid_data("Cannot format number"))?;
+			self.emit(&buffer);
+		}
+		self.end_value();
+		Ok(())
+	}
+
+	fn string(&mut self, value: &str) -> io::Result<()>
+	{	self.begin_sink_value()?;
+		self.emit_string(value);
+		self.end_value();
+		Ok(())
+	}
+
+	fn begin_raw(&mut self) -> io::Result<()>
+	{	self.check_sink_value()?;
+		self.raw_depth = Some(self.stack.len());
+		self.raw_values = 0;
+		Ok(())
+	}
+
+	fn end_raw(&mut self) -> io::Result<()>
+	{	if self.in_scalar
+		{	self.end_scalar()?;
+		}
+		match self.raw_depth.take()
+		{	Some(depth) if depth == self.stack.len() && self.raw_values == 1 && !self.in_string => Ok(()),
+			_ => Err(invalid_data("Invalid JSON input: unexpected end of input"))
+		}
+	}
+}
+
+fn invalid_data(message: &str) -> io::Error
+{	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes finite float like ECMAScript `Number.prototype.toString()` does.
+fn write_ecmascript_number(value: f64, out: &mut Vec<u8>) -> io::Result<()>
+{	let mut buffer = [0u8; READER_BUFFER_SIZE];
+	let (len, exponent, is_negative) = float_to_digits(&mut buffer, value).map_err(|_| invalid_data("Cannot format number"))?;
+	if len == 0
+	{	out.push(b'0');
+		return Ok(());
+	}
+	if is_negative
+	{	out.push(b'-');
+	}
+	let digits = &buffer[.. len];
+	let len = len as i32;
+	// position of decimal point relative to the first digit
+	let n = len + exponent as i32;
+	if len <= n && n <= 21
+	{	out.extend_from_slice(digits);
+		out.resize(out.len() + (n - len) as usize, b'0');
+	}
+	else if 0 < n && n <= 21
+	{	out.extend_from_slice(&digits[.. n as usize]);
+		out.push(b'.');
+		out.extend_from_slice(&digits[n as usize ..]);
+	}
+	else if -6 < n && n <= 0
+	{	out.extend_from_slice(b"0.");
+		out.resize(out.len() + (-n) as usize, b'0');
+		out.extend_from_slice(digits);
+	}
+	else
+	{	out.push(digits[0]);
+		if len > 1
+		{	out.push(b'.');
+			out.extend_from_slice(&digits[1 ..]);
+		}
+		out.extend_from_slice(if n > 0 {b"e+"} else {b"e-"});
+		let mut buffer = [0u8; 12];
+		out.extend_from_slice((n - 1).unsigned_abs().numtoa(10, &mut buffer));
+	}
+	Ok(())
+}
+
+/// Serializes `value` to canonical JSON ([Formatting::canonical()](struct.Formatting.html#method.canonical)).
+/// Works for `Value`, maps, and any other type that implements [DebugToJson](trait.DebugToJson.html).
+pub fn to_canonical_json<T>(value: &T) -> io::Result<String> where T: DebugToJson
+{	value.to_json_string_with(&Formatting::canonical())
+}
+
+impl<T> Reader<T> where T: Iterator<Item=u8>
+{	/// Reads next value from the stream, and returns it as canonical JSON ([Formatting::canonical()](struct.Formatting.html#method.canonical)).
+	/// The value is validated and formatted while it's read, without building [Value](enum.Value.html) from it.
+	///
+	/// ```
+	/// use nop_json::Reader;
+	///
+	/// let mut reader = Reader::new(r#" {"b": 2.0, "a": [1E3, "\u0041"]} {} "#.bytes());
+	/// assert_eq!(reader.read_canonical().unwrap(), r#"{"a":[1000,"A"],"b":2}"#);
+	/// assert_eq!(reader.read_canonical().unwrap(), "{}");
+	/// ```
+	pub fn read_canonical(&mut self) -> io::Result<String>
+	{	let mut writer = Formatting::canonical().build(Vec::new());
+		self.pipe_to_sink(&mut writer)?;
+		String::from_utf8(writer.finish()?).map_err(|_| invalid_data("Invalid UTF-8"))
+	}
+}
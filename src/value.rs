@@ -1,24 +1,52 @@
 use crate::debug_to_json::DebugToJson;
+use crate::decimal::{Decimal, Rounding};
+use crate::value_map::Map;
+use crate::nop_json::{number_to_string, write_number, float_to_digits, parse_number, push_path_key, push_path_index, READER_BUFFER_SIZE};
 
 use std::char;
 use std::fmt;
 use std::collections::{HashMap, BTreeMap, LinkedList, VecDeque};
-use std::convert::{TryInto, TryFrom};
+use std::collections::hash_map::DefaultHasher;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::convert::TryFrom;
 use std::str::FromStr;
-use std::ops::Index;
+use std::ops::{Index, IndexMut, Deref};
+use std::borrow::Cow;
+use std::error::Error;
 use numtoa::NumToA;
 
-const FORMAT_NUM_WIDTH: usize = 10;
-const FORMAT_NUM_WIDTH_Z: [u8; FORMAT_NUM_WIDTH] = [b'0'; FORMAT_NUM_WIDTH];
-const FORMAT_NUM_WIDTH_0Z: &[u8] = b"0.0000000000";
-
 /// Holds any JSON node: null, boolean, number, string, array or object.
 ///
 /// A number is stored as three parts — `Value::Number(mantissa, exponent, is_negative)` — and equals
 /// `(if is_negative {-1} else {1}) * mantissa * 10.pow(exponent)`. So `-12.5` is
-/// `Value::Number(125, -1, true)`. Note that this representation is not normalized, so two `Value`s
-/// that print the same may compare unequal (e.g. `Value::Number(10, 0, false)` vs
-/// `Value::Number(1, 1, false)`).
+/// `Value::Number(125, -1, true)`. This representation is not normalized, but numbers are compared by their numeric value,
+/// so `Value::Number(10, 0, false) == Value::Number(1, 1, false)`.
+///
+/// When the mantissa doesn't fit `u64`, the number is stored as `Value::BigNumber(digits, exponent, is_negative)`,
+/// where `digits` is the mantissa written in decimal (see [Digits](struct.Digits.html)). So numbers of any length survive reading and writing back exactly.
+/// ```
+/// use nop_json::{Reader, Value};
+///
+/// let mut reader = Reader::new(r#" 123456789012345678901234567890.5 "#.bytes());
+/// let v: Value = reader.read().unwrap();
+/// assert_eq!(v, Value::BigNumber("1234567890123456789012345678905".parse().unwrap(), -1, false));
+/// assert_eq!(v.to_string(), "123456789012345678901234567890.5");
+/// ```
+///
+/// `Value` implements `Eq`, `Hash` and `Ord`, so it can be stored in `HashSet` and used as map key.
+/// Values of different types are ordered like this: null < booleans < numbers < strings < arrays < objects.
+/// Arrays are compared element by element, and objects - as lists of (key, value) pairs sorted by key.
+/// ```
+/// use nop_json::Value;
+/// use std::collections::HashSet;
+///
+/// let mut set = HashSet::new();
+/// set.insert(Value::Number(10, 0, false));
+/// assert!(set.contains(&Value::Number(1, 1, false)));
+/// assert!(Value::Number(5, -1, true) < Value::Number(0, 0, false));
+/// assert!(Value::Bool(true) < Value::Number(0, 0, false));
+/// ```
 ///
 /// Many built-in types can be converted to `Value`.
 /// ```
@@ -39,14 +67,15 @@ const FORMAT_NUM_WIDTH_0Z: &[u8] = b"0.0000000000";
 /// assert_eq!(v0, 3u32);
 /// assert_eq!(v1, vec![true, false, true]);
 /// ```
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub enum Value
 {	Null,
 	Bool(bool),
 	Number(u64, i16, bool),
+	BigNumber(Digits, i16, bool),
 	String(String),
 	Array(Vec<Value>),
-	Object(HashMap<String, Value>)
+	Object(Map)
 }
 
 impl Value
@@ -59,7 +88,7 @@ impl Value
 	}
 
 	pub fn is_number(&self) -> bool
-	{	match *self {Value::Number(_, _, _) => true, _ => false}
+	{	match *self {Value::Number(_, _, _) | Value::BigNumber(_, _, _) => true, _ => false}
 	}
 
 	pub fn is_string(&self) -> bool
@@ -73,11 +102,408 @@ impl Value
 	pub fn is_object(&self) -> bool
 	{	match *self {Value::Object(_) => true, _ => false}
 	}
+
+	/// Parses a JSON number from string, keeping all its digits. The string must contain nothing but the number
+	/// (surrounding whitespace is allowed).
+	/// ```
+	/// use nop_json::Value;
+	///
+	/// let v = Value::parse_number("-1e400").unwrap();
+	/// assert_eq!(v, Value::Number(1, 400, true));
+	/// let v = Value::parse_number("18446744073709551616").unwrap();
+	/// assert_eq!(v.to_string(), "18446744073709551616");
+	/// assert_eq!(Value::parse_number("12a").unwrap_err().to_string(), "$: String \"12a\" is not a valid number");
+	/// ```
+	pub fn parse_number(s: &str) -> Result<Value, ConversionError>
+	{	parse_number(s).map_err(|_| ConversionError::new(format!("String \"{}\" is not a valid number", s)))
+	}
+
+	/// If this is a string, returns reference to it.
+	pub fn as_str(&self) -> Option<&str>
+	{	match *self {Value::String(ref v) => Some(v), _ => None}
+	}
+
+	/// If this is a boolean, returns it.
+	pub fn as_bool(&self) -> Option<bool>
+	{	match *self {Value::Bool(v) => Some(v), _ => None}
+	}
+
+	/// If this is an integer number that fits `i64`, returns it. Numbers with fractional part give `None`.
+	/// ```
+	/// use nop_json::Value;
+	///
+	/// assert_eq!(Value::Number(12, 1, true).as_i64(), Some(-120));
+	/// assert_eq!(Value::Number(120, -1, false).as_i64(), Some(12));
+	/// assert_eq!(Value::Number(125, -1, false).as_i64(), None);
+	/// assert_eq!(Value::String("12".to_string()).as_i64(), None);
+	/// ```
+	pub fn as_i64(&self) -> Option<i64>
+	{	let (magnitude, is_negative) = self.integer_parts()?;
+		let magnitude = i128::try_from(magnitude).ok()?;
+		i64::try_from(if is_negative {-magnitude} else {magnitude}).ok()
+	}
+
+	/// If this is a non-negative integer number that fits `u64`, returns it. Numbers with fractional part give `None`.
+	pub fn as_u64(&self) -> Option<u64>
+	{	match self.integer_parts()?
+		{	(magnitude, false) => u64::try_from(magnitude).ok(),
+			(0, true) => Some(0),
+			(_, true) => None
+		}
+	}
+
+	/// If this is a number, returns the nearest `f64`.
+	pub fn as_f64(&self) -> Option<f64>
+	{	match *self
+		{	Value::Number(mantissa, exponent, is_negative) =>
+			{	let mut buffer = [0u8; 24];
+				parse_float(mantissa.numtoa(10, &mut buffer), exponent, is_negative).ok()
+			},
+			Value::BigNumber(ref digits, exponent, is_negative) => parse_float(digits.as_bytes(), exponent, is_negative).ok(),
+			_ => None
+		}
+	}
+
+	/// If this is an array, returns reference to it.
+	pub fn as_array(&self) -> Option<&Vec<Value>>
+	{	match *self {Value::Array(ref v) => Some(v), _ => None}
+	}
+
+	/// If this is an array, returns mutable reference to it.
+	pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>>
+	{	match *self {Value::Array(ref mut v) => Some(v), _ => None}
+	}
+
+	/// If this is an object, returns reference to it.
+	pub fn as_object(&self) -> Option<&Map>
+	{	match *self {Value::Object(ref v) => Some(v), _ => None}
+	}
+
+	/// If this is an object, returns mutable reference to it.
+	pub fn as_object_mut(&mut self) -> Option<&mut Map>
+	{	match *self {Value::Object(ref mut v) => Some(v), _ => None}
+	}
+
+	/// Returns array element by `usize` index, or object property by string key.
+	/// Returns `None` if there's no such element or property, or if this value is of other type.
+	/// ```
+	/// use nop_json::{Reader, Value};
+	///
+	/// let v: Value = Reader::new(r#" {"items": [10, 20]} "#.bytes()).read().unwrap();
+	/// assert_eq!(v.get("items").and_then(|items| items.get(1)), Some(&Value::Number(2, 1, false)));
+	/// assert_eq!(v.get("other"), None);
+	/// assert_eq!(v.get(0), None);
+	/// ```
+	pub fn get<I>(&self, index: I) -> Option<&Value> where I: ValueIndex
+	{	index.index_into(self)
+	}
+
+	/// Like [get()](enum.Value.html#method.get), but returns mutable reference.
+	pub fn get_mut<I>(&mut self, index: I) -> Option<&mut Value> where I: ValueIndex
+	{	index.index_into_mut(self)
+	}
+
+	/// Takes this value out, leaving `Value::Null` in it's place.
+	pub fn take(&mut self) -> Value
+	{	std::mem::replace(self, Value::Null)
+	}
+
+	/// Sets object property, and returns it's old value, if there was such property.
+	/// If this value is null, it's converted to empty object first.
+	///
+	/// # Panics
+	///
+	/// If this value is not object or null.
+	/// ```
+	/// use nop_json::Value;
+	///
+	/// let mut v = Value::Null;
+	/// assert_eq!(v.insert("a", Value::Bool(true)), None);
+	/// assert_eq!(v.insert("a", Value::Bool(false)), Some(Value::Bool(true)));
+	/// assert_eq!(v.to_string(), r#"{"a":false}"#);
+	/// ```
+	pub fn insert<K>(&mut self, key: K, value: Value) -> Option<Value> where K: Into<String>
+	{	if let Value::Null = *self
+		{	*self = Value::Object(Map::new());
+		}
+		match *self
+		{	Value::Object(ref mut v) => v.insert(key.into(), value),
+			_ => panic!("Cannot insert property to {}", self.type_name())
+		}
+	}
+
+	/// Removes array element by `usize` index (shifting all elements after it), or object property by string key.
+	/// Returns the removed value, or `None` if there was no such element or property.
+	pub fn remove<I>(&mut self, index: I) -> Option<Value> where I: ValueIndex
+	{	index.remove_from(self)
+	}
+
+	/// Number of elements in array, or properties in object. For other types returns 0.
+	pub fn len(&self) -> usize
+	{	match *self
+		{	Value::Array(ref v) => v.len(),
+			Value::Object(ref v) => v.len(),
+			_ => 0
+		}
+	}
+
+	/// Returns `true` if [len()](enum.Value.html#method.len) is 0.
+	pub fn is_empty(&self) -> bool
+	{	self.len() == 0
+	}
+
+	/// Looks up a value by JSON Pointer ([RFC 6901](https://tools.ietf.org/html/rfc6901)), like `/items/0/name`.
+	/// Empty string points to the whole document.
+	/// ```
+	/// use nop_json::{Reader, Value};
+	///
+	/// let v: Value = Reader::new(r#" {"items": [{"name": "a/b"}], "x~y": 1} "#.bytes()).read().unwrap();
+	/// assert_eq!(v.pointer("/items/0/name"), Some(&Value::String("a/b".to_string())));
+	/// assert_eq!(v.pointer("/x~0y"), Some(&Value::Number(1, 0, false)));
+	/// assert_eq!(v.pointer("/items/1"), None);
+	/// assert_eq!(v.pointer(""), Some(&v));
+	/// ```
+	pub fn pointer(&self, pointer: &str) -> Option<&Value>
+	{	if pointer.is_empty()
+		{	return Some(self);
+		}
+		let mut value = self;
+		for token in pointer.strip_prefix('/')?.split('/')
+		{	value = match *value
+			{	Value::Object(ref v) => v.get(unescape_pointer_token(token).as_ref())?,
+				Value::Array(ref v) => v.get(parse_pointer_index(token)?)?,
+				_ => return None
+			};
+		}
+		Some(value)
+	}
+
+	/// Like [pointer()](enum.Value.html#method.pointer), but returns mutable reference.
+	pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value>
+	{	if pointer.is_empty()
+		{	return Some(self);
+		}
+		let mut value = self;
+		for token in pointer.strip_prefix('/')?.split('/')
+		{	value = match *value
+			{	Value::Object(ref mut v) => v.get_mut(unescape_pointer_token(token).as_ref())?,
+				Value::Array(ref mut v) => v.get_mut(parse_pointer_index(token)?)?,
+				_ => return None
+			};
+		}
+		Some(value)
+	}
+
+	/// Name of this value type for error messages.
+	pub(crate) fn type_name(&self) -> &'static str
+	{	match *self
+		{	Value::Null => "null",
+			Value::Bool(_) => "boolean",
+			Value::Number(_, _, _) | Value::BigNumber(_, _, _) => "number",
+			Value::String(_) => "string",
+			Value::Array(_) => "array",
+			Value::Object(_) => "object",
+		}
+	}
+
+	/// If this is an integer number (without fractional part) that fits `u128`, returns it's absolute value and sign.
+	fn integer_parts(&self) -> Option<(u128, bool)>
+	{	let mut buffer = [0u8; 24];
+		let parts = NumberParts::new(self, &mut buffer)?;
+		if parts.exponent < 0
+		{	return None;
+		}
+		let mut result = 0u128;
+		for &c in parts.digits
+		{	result = result.checked_mul(10)?.checked_add((c - b'0') as u128)?;
+		}
+		if parts.exponent > 0
+		{	result = result.checked_mul(10u128.checked_pow(parts.exponent as u32)?)?;
+		}
+		Some((result, parts.is_negative))
+	}
+}
+
+/// Number in normalized form: digits without leading and trailing zeros (empty for zero), exponent and sign (zero is never negative).
+struct NumberParts<'a>
+{	digits: &'a [u8],
+	exponent: i32,
+	is_negative: bool,
+}
+
+impl<'a> NumberParts<'a>
+{	fn new(value: &'a Value, buffer: &'a mut [u8; 24]) -> Option<Self>
+	{	let (digits, exponent, is_negative) = match *value
+		{	Value::Number(mantissa, exponent, is_negative) => (mantissa.numtoa(10, buffer), exponent, is_negative),
+			Value::BigNumber(ref digits, exponent, is_negative) => (digits.as_bytes(), exponent, is_negative),
+			_ => return None
+		};
+		match digits.iter().rposition(|&c| c != b'0')
+		{	None => Some(NumberParts {digits: &[], exponent: 0, is_negative: false}),
+			Some(last) =>
+			{	let first = digits.iter().position(|&c| c != b'0').unwrap_or(0);
+				Some(NumberParts {digits: &digits[first ..= last], exponent: exponent as i32 + (digits.len() - 1 - last) as i32, is_negative})
+			}
+		}
+	}
+
+	fn cmp_magnitude(&self, other: &Self) -> Ordering
+	{	if self.digits.is_empty() || other.digits.is_empty()
+		{	return (!self.digits.is_empty()).cmp(&!other.digits.is_empty());
+		}
+		// compare position of the most significant digit, and then the digits
+		let pos = self.digits.len() as i32 + self.exponent;
+		let other_pos = other.digits.len() as i32 + other.exponent;
+		pos.cmp(&other_pos).then_with(|| self.digits.cmp(other.digits))
+	}
+}
+
+impl<'a> PartialEq for NumberParts<'a>
+{	fn eq(&self, other: &Self) -> bool
+	{	self.digits == other.digits && self.exponent == other.exponent && self.is_negative == other.is_negative
+	}
+}
+
+impl<'a> Eq for NumberParts<'a> {}
+
+impl<'a> PartialOrd for NumberParts<'a>
+{	fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+	{	Some(self.cmp(other))
+	}
+}
+
+impl<'a> Ord for NumberParts<'a>
+{	fn cmp(&self, other: &Self) -> Ordering
+	{	match (self.is_negative, other.is_negative)
+		{	(false, true) => Ordering::Greater,
+			(true, false) => Ordering::Less,
+			(false, false) => self.cmp_magnitude(other),
+			(true, true) => other.cmp_magnitude(self),
+		}
+	}
+}
+
+impl Value
+{	/// Position of this value type in ordering between different types.
+	fn type_rank(&self) -> u8
+	{	match *self
+		{	Value::Null => 0,
+			Value::Bool(_) => 1,
+			Value::Number(_, _, _) | Value::BigNumber(_, _, _) => 2,
+			Value::String(_) => 3,
+			Value::Array(_) => 4,
+			Value::Object(_) => 5,
+		}
+	}
+}
+
+impl PartialEq for Value
+{	fn eq(&self, other: &Self) -> bool
+	{	match (self, other)
+		{	(Value::Null, Value::Null) => true,
+			(Value::Bool(a), Value::Bool(b)) => a == b,
+			(Value::String(a), Value::String(b)) => a == b,
+			(Value::Array(a), Value::Array(b)) => a == b,
+			(Value::Object(a), Value::Object(b)) => a == b,
+			_ =>
+			{	let mut buffer = [0u8; 24];
+				let mut other_buffer = [0u8; 24];
+				match (NumberParts::new(self, &mut buffer), NumberParts::new(other, &mut other_buffer))
+				{	(Some(a), Some(b)) => a == b,
+					_ => false
+				}
+			}
+		}
+	}
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value
+{	fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+	{	Some(self.cmp(other))
+	}
+}
+
+impl Ord for Value
+{	fn cmp(&self, other: &Self) -> Ordering
+	{	match (self, other)
+		{	(Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+			(Value::String(a), Value::String(b)) => a.cmp(b),
+			(Value::Array(a), Value::Array(b)) => a.cmp(b),
+			(Value::Object(a), Value::Object(b)) => cmp_objects(a, b),
+			_ =>
+			{	let mut buffer = [0u8; 24];
+				let mut other_buffer = [0u8; 24];
+				match (NumberParts::new(self, &mut buffer), NumberParts::new(other, &mut other_buffer))
+				{	(Some(a), Some(b)) => a.cmp(&b),
+					_ => self.type_rank().cmp(&other.type_rank())
+				}
+			}
+		}
+	}
+}
+
+/// Compares objects like sequences of properties sorted by key, but without sorting them.
+fn cmp_objects(a: &Map, b: &Map) -> Ordering
+{	// find the smallest key that is only in one object, or has different values (all smaller keys are the same in both)
+	let mut first: Option<(&str, Option<&Value>, Option<&Value>)> = None;
+	for (key, value) in a
+	{	if first.is_none_or(|(k, _, _)| key.as_str() < k)
+		{	let other = b.get(key);
+			if other != Some(value)
+			{	first = Some((key, Some(value), other));
+			}
+		}
+	}
+	for (key, value) in b
+	{	if first.is_none_or(|(k, _, _)| key.as_str() < k) && !a.contains_key(key)
+		{	first = Some((key, None, Some(value)));
+		}
+	}
+	match first
+	{	None => Ordering::Equal,
+		Some((_, Some(a_value), Some(b_value))) => a_value.cmp(b_value),
+		// only `a` has this key, so it's compared to the next key of `b`, or `b` is shorter
+		Some((key, Some(_), None)) => if b.keys().any(|k| k.as_str() > key) {Ordering::Less} else {Ordering::Greater},
+		Some((key, _, _)) => if a.keys().any(|k| k.as_str() > key) {Ordering::Greater} else {Ordering::Less},
+	}
+}
+
+impl Hash for Value
+{	fn hash<H: Hasher>(&self, state: &mut H)
+	{	self.type_rank().hash(state);
+		match *self
+		{	Value::Null => {},
+			Value::Bool(v) => v.hash(state),
+			Value::Number(_, _, _) | Value::BigNumber(_, _, _) =>
+			{	let mut buffer = [0u8; 24];
+				let parts = NumberParts::new(self, &mut buffer).unwrap();
+				parts.digits.hash(state);
+				parts.exponent.hash(state);
+				parts.is_negative.hash(state);
+			}
+			Value::String(ref v) => v.hash(state),
+			Value::Array(ref v) => v.hash(state),
+			Value::Object(ref v) =>
+			{	// must not depend on iteration order
+				let mut sum = 0u64;
+				for (key, item) in v
+				{	let mut hasher = DefaultHasher::new();
+					key.hash(&mut hasher);
+					item.hash(&mut hasher);
+					sum = sum.wrapping_add(hasher.finish());
+				}
+				v.len().hash(state);
+				sum.hash(state);
+			}
+		}
+	}
 }
 
 impl fmt::Debug for Value
 {	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
-	{	DebugToJson::fmt(self, f)
+	{	self.debug_fmt(f)
 	}
 }
 
@@ -89,143 +515,290 @@ impl fmt::Display for Value
 
 // 1. From value
 
+/// Error returned by `TryFrom<Value>` conversions.
+///
+/// Mantissa of [Value::BigNumber](enum.Value.html#variant.BigNumber), written in decimal.
+///
+/// It's a `String` that contains at least one digit, and nothing but ASCII digits `0` - `9`, so the `Value` always serializes to a valid JSON number.
+/// It dereferences to `str`.
+///
+/// ```
+/// use nop_json::{Value, Digits};
+///
+/// let digits: Digits = "123456789012345678901".parse().unwrap();
+/// assert_eq!(digits.len(), 21);
+/// assert_eq!(Value::BigNumber(digits, -1, true).to_string(), "-12345678901234567890.1");
+///
+/// assert!("12e3".parse::<Digits>().is_err());
+/// assert!(Digits::new(String::new()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Digits(String);
+
+impl Digits
+{	/// Checks that `digits` is a valid mantissa.
+	pub fn new(digits: String) -> Result<Self, ConversionError>
+	{	if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit())
+		{	return Err(ConversionError::new(format!("\"{}\" is not a valid sequence of decimal digits", digits)));
+		}
+		Ok(Digits(digits))
+	}
+
+	/// For digits that are already known to be valid.
+	pub(crate) fn new_unchecked(digits: String) -> Self
+	{	Digits(digits)
+	}
+
+	pub fn as_str(&self) -> &str
+	{	&self.0
+	}
+
+	pub fn into_string(self) -> String
+	{	self.0
+	}
+}
+
+impl Deref for Digits
+{	type Target = str;
+
+	fn deref(&self) -> &str
+	{	&self.0
+	}
+}
+
+impl FromStr for Digits
+{	type Err = ConversionError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{	Digits::new(s.to_string())
+	}
+}
+
+impl TryFrom<String> for Digits
+{	type Error = ConversionError;
+
+	fn try_from(digits: String) -> Result<Self, Self::Error>
+	{	Digits::new(digits)
+	}
+}
+
+impl fmt::Display for Digits
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	f.write_str(&self.0)
+	}
+}
+
+/// Tells what went wrong, and where: for values converted from nested arrays and objects, the path is like `$.items[1]`.
+///
+/// ```
+/// use nop_json::{json, ConversionError};
+/// use std::collections::HashMap;
+///
+/// let error = HashMap::<String, Vec<u8>>::try_from(json!({"a": [1, 2, 1000]})).unwrap_err();
+/// assert_eq!(error.path(), "$.a[2]");
+/// assert_eq!(error.to_string(), "$.a[2]: Number 1000 doesn't fit u8");
+///
+/// let error = Vec::<i32>::try_from(json!([1, [2]])).unwrap_err();
+/// assert_eq!(error.to_string(), "$[1]: Value must be i32, not array");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError
+{	path: String,
+	message: String,
+}
+
+impl ConversionError
+{	/// Creates error with the given description, at path `$`. Use it in your own `TryFrom<Value>` implementations.
+	pub fn new<S>(message: S) -> Self where S: Into<String>
+	{	ConversionError {path: "$".to_string(), message: message.into()}
+	}
+
+	/// Error that says that `value` can't be converted to type called `expected`.
+	pub fn type_mismatch(expected: &str, value: &Value) -> Self
+	{	Self::new(format!("Value must be {}, not {}", expected, value.type_name()))
+	}
+
+	/// Error that says that the number or string in `value` is not in the range of type called `expected`.
+	pub fn out_of_range(expected: &str, value: &Value) -> Self
+	{	match *value
+		{	Value::String(ref v) => Self::new(format!("String \"{}\" is not a valid {}", v, expected)),
+			_ => Self::new(format!("Number {} doesn't fit {}", value, expected))
+		}
+	}
+
+	/// Makes path of this error relative to array element at `index`. Containers call this for errors in their elements.
+	pub fn at_index(mut self, index: usize) -> Self
+	{	let mut path = String::with_capacity(self.path.len() + 8);
+		path.push('$');
+		push_path_index(&mut path, index);
+		path.push_str(&self.path[1 ..]);
+		self.path = path;
+		self
+	}
+
+	/// Makes path of this error relative to object property `key`. Containers call this for errors in their values.
+	/// Keys that are not identifiers are written in brackets, like `$["first name"]`.
+	pub fn at_key(mut self, key: &str) -> Self
+	{	let mut path = String::with_capacity(self.path.len() + key.len() + 1);
+		path.push('$');
+		push_path_key(&mut path, key);
+		path.push_str(&self.path[1 ..]);
+		self.path = path;
+		self
+	}
+
+	/// Where in the converted value the error occurred, like `$.items[1]`.
+	pub fn path(&self) -> &str
+	{	&self.path
+	}
+
+	/// Description of the problem.
+	pub fn message(&self) -> &str
+	{	&self.message
+	}
+}
+
+impl fmt::Display for ConversionError
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	write!(f, "{}: {}", self.path, self.message)
+	}
+}
+
+impl Error for ConversionError {}
+
 macro_rules! impl_from_value_float
 {	($ty:ty) =>
 	{	impl TryFrom<Value> for $ty
-		{	type Error = ();
+		{	type Error = ConversionError;
 
 			fn try_from(value: Value) -> Result<Self, Self::Error>
-			{	match value
+			{	let result = match value
 				{	Value::Null => Ok(0.0),
 					Value::Bool(v) => Ok(if v {1.0} else {0.0}),
 					Value::Number(mantissa, exponent, is_negative) =>
-					{	let mut result = (mantissa as Self)*((10.0 as Self).powi(exponent as i32));
-						if is_negative
-						{	result = -result;
-						}
-						Ok(result)
+					{	let mut buffer = [0u8; 24];
+						parse_float(mantissa.numtoa(10, &mut buffer), exponent, is_negative)
+					},
+					Value::BigNumber(ref digits, exponent, is_negative) =>
+					{	parse_float(digits.as_bytes(), exponent, is_negative)
 					},
-					Value::String(v) =>
+					Value::String(ref v) =>
 					{	v.parse().map_err(|_| ())
 					},
-					Value::Array(_v) => Err(()),
-					Value::Object(_v) => Err(()),
-				}
+					Value::Array(_) | Value::Object(_) => return Err(ConversionError::type_mismatch(stringify!($ty), &value)),
+				};
+				result.map_err(|_| ConversionError::out_of_range(stringify!($ty), &value))
 			}
 		}
 	}
 }
 
-macro_rules! impl_from_value_signed
-{	($ty:ty) =>
+macro_rules! impl_from_value_int
+{	($ty:ty, $is_unsigned:expr) =>
 	{	impl TryFrom<Value> for $ty
-		{	type Error = ();
+		{	type Error = ConversionError;
 
 			fn try_from(value: Value) -> Result<Self, Self::Error>
 			{	match value
 				{	Value::Null => Ok(0),
 					Value::Bool(v) => Ok(if v {1} else {0}),
-					Value::Number(mantissa, exponent, is_negative) =>
-					{	let mut result = if is_negative
-						{	Self::try_from(i64::try_from(mantissa).map_err(|_| ())?.wrapping_neg()).map_err(|_| ())?
-						}
-						else
-						{	Self::try_from(mantissa).map_err(|_| ())?
-						};
-						if exponent != 0
-						{	if exponent > 0
-							{	result = result.checked_mul((10 as Self).checked_pow(exponent as u32).ok_or(())?).ok_or(())?;
-							}
-							else
-							{	result = result / (10 as Self).checked_pow(exponent.checked_neg().ok_or(())? as u32).ok_or(())?;
-							}
-						}
-						Ok(result)
-					},
-					Value::String(v) =>
-					{	v.parse().map_err(|_| ())
-					},
-					Value::Array(_v) => Err(()),
-					Value::Object(_v) => Err(()),
+					Value::String(ref v) => v.parse().map_err(|_| ConversionError::out_of_range(stringify!($ty), &value)),
+					_ => number_to_int(&value, stringify!($ty), $is_unsigned),
 				}
 			}
 		}
 	}
 }
 
-macro_rules! impl_from_value_unsigned
-{	($ty:ty) =>
-	{	impl TryFrom<Value> for $ty
-		{	type Error = ();
+/// Converts number to integer type `T`, dropping the fractional part. Negative numbers don't convert to unsigned types, even if their integer part is 0.
+fn number_to_int<T>(value: &Value, type_name: &str, is_unsigned: bool) -> Result<T, ConversionError> where T: TryFrom<u128> + TryFrom<i128>
+{	let mut buffer = [0u8; 24];
+	let (digits, exponent, is_negative) = match *value
+	{	Value::Number(mantissa, exponent, is_negative) => (mantissa.numtoa(10, &mut buffer), exponent, is_negative),
+		Value::BigNumber(ref digits, exponent, is_negative) => (digits.as_bytes(), exponent, is_negative),
+		_ => return Err(ConversionError::type_mismatch(type_name, value))
+	};
+	let error = |_| ConversionError::out_of_range(type_name, value);
+	let magnitude = number_to_u128(digits, exponent).map_err(error)?;
+	if !is_negative
+	{	T::try_from(magnitude).map_err(|_| error(()))
+	}
+	else if is_unsigned || magnitude > i128::MAX as u128 + 1
+	{	Err(error(()))
+	}
+	else
+	{	T::try_from((magnitude as i128).wrapping_neg()).map_err(|_| error(()))
+	}
+}
 
-			fn try_from(value: Value) -> Result<Self, Self::Error>
-			{	match value
-				{	Value::Null => Ok(0),
-					Value::Bool(v) => Ok(if v {1} else {0}),
-					Value::Number(mantissa, exponent, is_negative) =>
-					{	if is_negative
-						{	return Err(());
-						}
-						let mut result = Self::try_from(mantissa).map_err(|_| ())?;
-						if exponent != 0
-						{	if exponent > 0
-							{	result = result.checked_mul((10 as Self).checked_pow(exponent as u32).ok_or(())?).ok_or(())?;
-							}
-							else
-							{	result = result / (10 as Self).checked_pow(exponent.checked_neg().ok_or(())? as u32).ok_or(())?;
-							}
-						}
-						Ok(result)
-					},
-					Value::String(v) =>
-					{	v.parse().map_err(|_| ())
-					},
-					Value::Array(_v) => Err(()),
-					Value::Object(_v) => Err(()),
-				}
-			}
+/// Converts number in the form of decimal `digits` to float by parsing it's text representation, so the result is correctly rounded.
+fn parse_float<T>(digits: &[u8], exponent: i16, is_negative: bool) -> Result<T, ()> where T: FromStr
+{	let digits = std::str::from_utf8(digits).map_err(|_| ())?;
+	let s = format!("{}{}e{}", if is_negative {"-"} else {""}, if digits.is_empty() {"0"} else {digits}, exponent);
+	s.parse().map_err(|_| ())
+}
+
+/// Integer part of `digits * 10.pow(exponent)`. Returns `Err` if it doesn't fit `u128`.
+fn number_to_u128(digits: &[u8], exponent: i16) -> Result<u128, ()>
+{	let int_len = if exponent >= 0 {digits.len()} else {digits.len().saturating_sub(exponent.unsigned_abs() as usize)};
+	let mut result = 0u128;
+	for &c in &digits[.. int_len]
+	{	if !c.is_ascii_digit()
+		{	return Err(());
 		}
+		result = result.checked_mul(10).and_then(|r| r.checked_add((c - b'0') as u128)).ok_or(())?;
 	}
+	if exponent > 0 && result != 0
+	{	result = result.checked_mul(10u128.checked_pow(exponent as u32).ok_or(())?).ok_or(())?;
+	}
+	Ok(result)
 }
 
 impl_from_value_float!(f64);
 impl_from_value_float!(f32);
-impl_from_value_signed!(isize);
-impl_from_value_signed!(i128);
-impl_from_value_signed!(i64);
-impl_from_value_signed!(i32);
-impl_from_value_signed!(i16);
-impl_from_value_signed!(i8);
-impl_from_value_unsigned!(usize);
-impl_from_value_unsigned!(u128);
-impl_from_value_unsigned!(u64);
-impl_from_value_unsigned!(u32);
-impl_from_value_unsigned!(u16);
-impl_from_value_unsigned!(u8);
+impl_from_value_int!(isize, false);
+impl_from_value_int!(i128, false);
+impl_from_value_int!(i64, false);
+impl_from_value_int!(i32, false);
+impl_from_value_int!(i16, false);
+impl_from_value_int!(i8, false);
+impl_from_value_int!(usize, true);
+impl_from_value_int!(u128, true);
+impl_from_value_int!(u64, true);
+impl_from_value_int!(u32, true);
+impl_from_value_int!(u16, true);
+impl_from_value_int!(u8, true);
+
+/// Exact conversion: fails if the number has more fractional digits than `SCALE`.
+/// Use [Decimal::from_value()](struct.Decimal.html#method.from_value) to round.
+impl<const SCALE: u32> TryFrom<Value> for Decimal<SCALE>
+{	type Error = ConversionError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error>
+	{	Decimal::from_value(value, Rounding::Error)
+	}
+}
 
 impl TryFrom<Value> for ()
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: Value) -> Result<Self, Self::Error>
 	{	match value
 		{	Value::Null => Ok(()),
-			Value::Bool(_v) => Err(()),
-			Value::Number(_mantissa, _exponent, _is_negative) => Err(()),
-			Value::String(_v) => Err(()),
-			Value::Array(_v) => Err(()),
-			Value::Object(_v) => Err(()),
+			_ => Err(ConversionError::type_mismatch("null", &value)),
 		}
 	}
 }
 
 impl TryFrom<Value> for bool
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: Value) -> Result<Self, Self::Error>
 	{	match value
 		{	Value::Null => Ok(false),
 			Value::Bool(v) => Ok(v),
 			Value::Number(mantissa, _exponent, _is_negative) => Ok(mantissa != 0),
+			Value::BigNumber(digits, _exponent, _is_negative) => Ok(digits.bytes().any(|c| c != b'0')),
 			Value::String(_v) => Ok(true),
 			Value::Array(_v) => Ok(true),
 			Value::Object(_v) => Ok(true),
@@ -234,7 +807,7 @@ impl TryFrom<Value> for bool
 }
 
 impl TryFrom<Value> for char
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: Value) -> Result<Self, Self::Error>
 	{	match value
@@ -253,15 +826,22 @@ impl TryFrom<Value> for char
 					Ok(s.chars().next().unwrap())
 				}
 			},
-			Value::String(v) => v.chars().next().ok_or(()),
-			Value::Array(_v) => Err(()),
-			Value::Object(_v) => Err(()),
+			Value::BigNumber(digits, _exponent, is_negative) =>
+			{	if is_negative
+				{	Ok('-')
+				}
+				else
+				{	Ok(digits.chars().next().unwrap_or('0'))
+				}
+			},
+			Value::String(ref v) => v.chars().next().ok_or_else(|| ConversionError::new("Value must be char, not empty string")),
+			Value::Array(_) | Value::Object(_) => Err(ConversionError::type_mismatch("char", &value)),
 		}
 	}
 }
 
 impl TryFrom<Value> for String
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: Value) -> Result<Self, Self::Error>
 	{	match value
@@ -269,144 +849,232 @@ impl TryFrom<Value> for String
 			Value::Bool(v) => Ok(if v {"true".to_string()} else {"false".to_string()}),
 			Value::Number(mantissa, exponent, is_negative) =>
 			{	let mut buffer = [0u8; 24];
-				let mantissa = mantissa.numtoa(10, &mut buffer);
-				let len = mantissa.len();
-				if exponent >= 0
-				{	let e = exponent as usize;
-					if len+e <= FORMAT_NUM_WIDTH
-					{	// append zeroes according to exponent
-						let mut vec = if !is_negative
-						{	Vec::with_capacity(mantissa.len() + e)
+				let digits = mantissa.numtoa(10, &mut buffer);
+				let mut buffer = [0u8; READER_BUFFER_SIZE];
+				buffer[0 .. digits.len()].copy_from_slice(digits);
+				let len = number_to_string(&mut buffer, digits.len(), exponent, is_negative).map_err(|_| ConversionError::out_of_range("String", &value))?;
+				String::from_utf8(buffer[0 .. len].to_vec()).map_err(|_| ConversionError::out_of_range("String", &value))
+			},
+			Value::BigNumber(ref digits, exponent, is_negative) =>
+			{	let mut result = Vec::with_capacity(digits.len() + 8);
+				write_number(digits.as_bytes(), exponent, is_negative, |part| {result.extend_from_slice(part); Ok::<_, ()>(())}).map_err(|_| ConversionError::out_of_range("String", &value))?;
+				String::from_utf8(result).map_err(|_| ConversionError::out_of_range("String", &value))
+			},
+			Value::String(v) => Ok(v),
+			Value::Array(_) | Value::Object(_) => Err(ConversionError::type_mismatch("String", &value)),
+		}
+	}
+}
+
+/// `null` converts to `None`.
+impl<T> TryFrom<Value> for Option<T> where T: TryFrom<Value, Error=ConversionError>
+{	type Error = ConversionError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error>
+	{	match value
+		{	Value::Null => Ok(None),
+			_ => Ok(Some(T::try_from(value)?)),
+		}
+	}
+}
+
+macro_rules! impl_from_value_sequence
+{	($ty:ident, $push:ident) =>
+	{	/// Converts from array. `null` gives empty collection.
+		impl<T> TryFrom<Value> for $ty<T> where T: TryFrom<Value, Error=ConversionError>
+		{	type Error = ConversionError;
+
+			fn try_from(value: Value) -> Result<Self, Self::Error>
+			{	match value
+				{	Value::Null => Ok($ty::new()),
+					Value::Array(v) =>
+					{	let mut arr = $ty::new();
+						for (i, item) in v.into_iter().enumerate()
+						{	arr.$push(T::try_from(item).map_err(|e| e.at_index(i))?);
 						}
-						else
-						{	let mut vec = Vec::with_capacity(mantissa.len() + e + 1);
-							vec.push(b'-');
-							vec
-						};
-						vec.extend_from_slice(mantissa);
-						vec.extend_from_slice(&FORMAT_NUM_WIDTH_Z[0 .. e]);
-						return String::from_utf8(vec).map_err(|_| ());
+						Ok(arr)
 					}
+					_ => Err(ConversionError::type_mismatch("array", &value)),
 				}
-				else
-				{	let e = exponent.wrapping_neg() as usize;
-					if e < len
-					{	// insert dot in the middle of number
-						let mut vec = if !is_negative
-						{	Vec::with_capacity(mantissa.len() + 1)
-						}
-						else
-						{	let mut vec = Vec::with_capacity(mantissa.len() + 2);
-							vec.push(b'-');
-							vec
-						};
-						vec.extend_from_slice(&mantissa[0 .. len-e]);
-						vec.push(b'.');
-						vec.extend_from_slice(&mantissa[len-e ..]);
-						return String::from_utf8(vec).map_err(|_| ());
-					}
-					if e <= FORMAT_NUM_WIDTH
-					{	// prepend with 0.000...
-						let mut vec = if !is_negative
-						{	Vec::with_capacity(mantissa.len() + e-len+2)
+			}
+		}
+	}
+}
+
+impl_from_value_sequence!(Vec, push);
+impl_from_value_sequence!(LinkedList, push_back);
+impl_from_value_sequence!(VecDeque, push_back);
+
+macro_rules! impl_from_value_map
+{	($ty:ident) =>
+	{	/// Converts from object. `null` gives empty map.
+		impl<T> TryFrom<Value> for $ty<String, T> where T: TryFrom<Value, Error=ConversionError>
+		{	type Error = ConversionError;
+
+			fn try_from(value: Value) -> Result<Self, Self::Error>
+			{	match value
+				{	Value::Null => Ok($ty::new()),
+					Value::Object(v) =>
+					{	let mut obj = $ty::new();
+						for (key, item) in v
+						{	let item = T::try_from(item).map_err(|e| e.at_key(&key))?;
+							obj.insert(key, item);
 						}
-						else
-						{	let mut vec = Vec::with_capacity(mantissa.len() + e-len+3);
-							vec.push(b'-');
-							vec
-						};
-						vec.extend_from_slice(&FORMAT_NUM_WIDTH_0Z[0 .. e-len+2]);
-						vec.extend_from_slice(mantissa);
-						return String::from_utf8(vec).map_err(|_| ());
+						Ok(obj)
 					}
+					_ => Err(ConversionError::type_mismatch("object", &value)),
 				}
-				let mut buffer = [0u8; 24];
-				let exponent = exponent.numtoa(10, &mut buffer);
-				let mut vec = if !is_negative
-				{	Vec::with_capacity(mantissa.len() + exponent.len() + 1)
-				}
-				else
-				{	let mut vec = Vec::with_capacity(mantissa.len() + exponent.len() + 2);
-					vec.push(b'-');
-					vec
-				};
-				vec.extend_from_slice(mantissa);
-				vec.push(b'e');
-				vec.extend_from_slice(exponent);
-				String::from_utf8(vec).map_err(|_| ())
-			},
-			Value::String(v) => Ok(v),
-			Value::Array(_v) => Err(()),
-			Value::Object(_v) => Err(()),
+			}
 		}
 	}
 }
 
-impl<T> TryFrom<Value> for Vec<T> where T: TryFrom<Value>
-{	type Error = ();
+impl_from_value_map!(HashMap);
+impl_from_value_map!(BTreeMap);
+
+/// Converts from array of exactly 2 elements.
+impl<A, B> TryFrom<Value> for (A, B) where A: TryFrom<Value, Error=ConversionError>, B: TryFrom<Value, Error=ConversionError>
+{	type Error = ConversionError;
 
 	fn try_from(value: Value) -> Result<Self, Self::Error>
 	{	match value
-		{	Value::Null => Ok(Vec::new()),
-			Value::Bool(_v) => Err(()),
-			Value::Number(_mantissa, _exponent, _is_negative) => Err(()),
-			Value::String(_v) => Err(()),
-			Value::Array(v) =>
-			{	let mut arr = Vec::with_capacity(v.len());
-				for item in v
-				{	arr.push(item.try_into().map_err(|_| ())?)
-				}
-				Ok(arr)
+		{	Value::Array(v) if v.len() == 2 =>
+			{	let mut v = v.into_iter();
+				let a = A::try_from(v.next().unwrap()).map_err(|e| e.at_index(0))?;
+				let b = B::try_from(v.next().unwrap()).map_err(|e| e.at_index(1))?;
+				Ok((a, b))
+			}
+			Value::Array(v) => Err(ConversionError::new(format!("Value must be array[2], not array[{}]", v.len()))),
+			_ => Err(ConversionError::type_mismatch("array[2]", &value)),
+		}
+	}
+}
+
+/// Converts from array of exactly 3 elements.
+impl<A, B, C> TryFrom<Value> for (A, B, C) where A: TryFrom<Value, Error=ConversionError>, B: TryFrom<Value, Error=ConversionError>, C: TryFrom<Value, Error=ConversionError>
+{	type Error = ConversionError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error>
+	{	match value
+		{	Value::Array(v) if v.len() == 3 =>
+			{	let mut v = v.into_iter();
+				let a = A::try_from(v.next().unwrap()).map_err(|e| e.at_index(0))?;
+				let b = B::try_from(v.next().unwrap()).map_err(|e| e.at_index(1))?;
+				let c = C::try_from(v.next().unwrap()).map_err(|e| e.at_index(2))?;
+				Ok((a, b, c))
 			}
-			Value::Object(_v) => Err(()),
+			Value::Array(v) => Err(ConversionError::new(format!("Value must be array[3], not array[{}]", v.len()))),
+			_ => Err(ConversionError::type_mismatch("array[3]", &value)),
 		}
 	}
 }
 
 // 2. To value
 
-macro_rules! impl_from_value_signed
-{	($ty:ty) =>
+macro_rules! impl_from_int
+{	($ty:ty, $wide:ty) =>
 	{	impl TryFrom<$ty> for Value
-		{	type Error = ();
+		{	type Error = ConversionError;
 
 			fn try_from(value: $ty) -> Result<Self, Self::Error>
-			{	if value >= 0
-				{	Ok(Value::Number(value.try_into().map_err(|_| ())?, 0, false))
-				}
-				else
-				{	Ok(Value::Number(i64::try_from(value).map_err(|_| ())?.wrapping_neg() as u64, 0, true))
-				}
+			{	Value::try_from(value as $wide)
 			}
 		}
 	}
 }
 
-macro_rules! impl_from_value_unsigned
+impl_from_int!(isize, i128);
+impl_from_int!(i64, i128);
+impl_from_int!(i32, i128);
+impl_from_int!(i16, i128);
+impl_from_int!(i8, i128);
+impl_from_int!(usize, u128);
+impl_from_int!(u64, u128);
+impl_from_int!(u32, u128);
+impl_from_int!(u16, u128);
+impl_from_int!(u8, u128);
+
+impl TryFrom<i128> for Value
+{	type Error = ConversionError;
+
+	fn try_from(value: i128) -> Result<Self, Self::Error>
+	{	let mut result = Value::try_from(value.unsigned_abs())?;
+		if value < 0
+		{	match result
+			{	Value::Number(_, _, ref mut is_negative) => *is_negative = true,
+				Value::BigNumber(_, _, ref mut is_negative) => *is_negative = true,
+				_ => unreachable!()
+			}
+		}
+		Ok(result)
+	}
+}
+
+impl TryFrom<u128> for Value
+{	type Error = ConversionError;
+
+	fn try_from(value: u128) -> Result<Self, Self::Error>
+	{	match u64::try_from(value)
+		{	Ok(value) => Ok(Value::Number(value, 0, false)),
+			Err(_) => Ok(Value::BigNumber(Digits(value.to_string()), 0, false))
+		}
+	}
+}
+
+macro_rules! impl_from_float
 {	($ty:ty) =>
 	{	impl TryFrom<$ty> for Value
-		{	type Error = ();
+		{	type Error = ConversionError;
 
+			/// Infinity and NaN have no JSON representation, so they become strings "Infinity", "-Infinity" and "NaN",
+			/// that convert back to the same float.
 			fn try_from(value: $ty) -> Result<Self, Self::Error>
-			{	Ok(Value::Number(value.try_into().map_err(|_| ())?, 0, false))
+			{	if !value.is_finite()
+				{	let s = if value.is_nan() {"NaN"} else if value > 0.0 {"Infinity"} else {"-Infinity"};
+					return Ok(Value::String(s.to_string()));
+				}
+				let mut buffer = [0u8; READER_BUFFER_SIZE];
+				let (len, exponent, is_negative) = float_to_digits(&mut buffer, value).map_err(|_| ConversionError::new(format!("Cannot convert {} to number", value)))?;
+				let mut mantissa = 0u64;
+				for c in &buffer[0 .. len]
+				{	mantissa = mantissa*10 + (c - b'0') as u64;
+				}
+				Ok(Value::Number(mantissa, exponent, is_negative))
 			}
 		}
 	}
 }
 
-impl_from_value_signed!(isize);
-impl_from_value_signed!(i64);
-impl_from_value_signed!(i32);
-impl_from_value_signed!(i16);
-impl_from_value_signed!(i8);
-impl_from_value_unsigned!(usize);
-impl_from_value_unsigned!(u64);
-impl_from_value_unsigned!(u32);
-impl_from_value_unsigned!(u16);
-impl_from_value_unsigned!(u8);
+impl_from_float!(f64);
+impl_from_float!(f32);
+
+impl<const SCALE: u32> TryFrom<Decimal<SCALE>> for Value
+{	type Error = ConversionError;
+
+	fn try_from(value: Decimal<SCALE>) -> Result<Self, Self::Error>
+	{	let mut mantissa = value.mantissa().unsigned_abs();
+		let mut exponent = -i16::try_from(SCALE).map_err(|_| ConversionError::new(format!("Decimal scale {} is too big", SCALE)))?;
+		if mantissa == 0
+		{	exponent = 0;
+		}
+		while mantissa != 0 && mantissa.is_multiple_of(10)
+		{	mantissa /= 10;
+			exponent += 1;
+		}
+		let mut result = Value::try_from(mantissa)?;
+		match result
+		{	Value::Number(_, ref mut e, ref mut is_negative) | Value::BigNumber(_, ref mut e, ref mut is_negative) =>
+			{	*e = exponent;
+				*is_negative = value.mantissa() < 0;
+			}
+			_ => unreachable!()
+		}
+		Ok(result)
+	}
+}
 
 impl TryFrom<()> for Value
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(_value: ()) -> Result<Self, Self::Error>
 	{	Ok(Value::Null)
@@ -414,7 +1082,7 @@ impl TryFrom<()> for Value
 }
 
 impl TryFrom<bool> for Value
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: bool) -> Result<Self, Self::Error>
 	{	Ok(Value::Bool(value))
@@ -422,7 +1090,7 @@ impl TryFrom<bool> for Value
 }
 
 impl TryFrom<char> for Value
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: char) -> Result<Self, Self::Error>
 	{	Ok(Value::String(value.to_string()))
@@ -430,13 +1098,27 @@ impl TryFrom<char> for Value
 }
 
 impl TryFrom<String> for Value
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: String) -> Result<Self, Self::Error>
 	{	Ok(Value::String(value))
 	}
 }
 
+impl<'a> TryFrom<&'a str> for Value
+{	type Error = ConversionError;
+
+	fn try_from(value: &'a str) -> Result<Self, Self::Error>
+	{	Ok(Value::String(value.to_string()))
+	}
+}
+
+impl From<Map> for Value
+{	fn from(value: Map) -> Self
+	{	Value::Object(value)
+	}
+}
+
 impl FromStr for Value
 {	type Err = ();
 
@@ -445,23 +1127,35 @@ impl FromStr for Value
 	}
 }
 
+/// `None` converts to `null`.
+impl<T> TryFrom<Option<T>> for Value where Value: TryFrom<T, Error=ConversionError>
+{	type Error = ConversionError;
+
+	fn try_from(value: Option<T>) -> Result<Self, Self::Error>
+	{	match value
+		{	None => Ok(Value::Null),
+			Some(v) => Value::try_from(v),
+		}
+	}
+}
+
 impl TryFrom<Vec<Value>> for Value
-{	type Error = ();
+{	type Error = ConversionError;
 
 	fn try_from(value: Vec<Value>) -> Result<Self, Self::Error>
 	{	Ok(Value::Array(value))
 	}
 }
 
-macro_rules! impl_from_vec
-{	($ty:ty) =>
-	{	impl TryFrom<Vec<$ty>> for Value
-		{	type Error = ();
+macro_rules! impl_from_sequence
+{	($ty:ident) =>
+	{	impl<T> TryFrom<$ty<T>> for Value where Value: TryFrom<T, Error=ConversionError>
+		{	type Error = ConversionError;
 
-			fn try_from(value: Vec<$ty>) -> Result<Self, Self::Error>
+			fn try_from(value: $ty<T>) -> Result<Self, Self::Error>
 			{	let mut vec = Vec::with_capacity(value.len());
-				for v in value
-				{	vec.push(Value::try_from(v)?);
+				for (i, v) in value.into_iter().enumerate()
+				{	vec.push(Value::try_from(v).map_err(|e| e.at_index(i))?);
 				}
 				Ok(Value::Array(vec))
 			}
@@ -469,154 +1163,143 @@ macro_rules! impl_from_vec
 	}
 }
 
-macro_rules! impl_from_linked_list
-{	($ty:ty) =>
-	{	impl TryFrom<LinkedList<$ty>> for Value
-		{	type Error = ();
+impl_from_sequence!(Vec);
+impl_from_sequence!(LinkedList);
+impl_from_sequence!(VecDeque);
 
-			fn try_from(value: LinkedList<$ty>) -> Result<Self, Self::Error>
-			{	let mut vec = Vec::with_capacity(value.len());
-				for v in value
-				{	vec.push(Value::try_from(v)?);
-				}
-				Ok(Value::Array(vec))
-			}
-		}
+impl TryFrom<HashMap<String, Value>> for Value
+{	type Error = ConversionError;
+
+	fn try_from(value: HashMap<String, Value>) -> Result<Self, Self::Error>
+	{	Ok(Value::Object(value.into()))
 	}
 }
 
-macro_rules! impl_from_vec_deque
-{	($ty:ty) =>
-	{	impl TryFrom<VecDeque<$ty>> for Value
-		{	type Error = ();
+macro_rules! impl_from_map
+{	($ty:ident) =>
+	{	impl<T> TryFrom<$ty<String, T>> for Value where Value: TryFrom<T, Error=ConversionError>
+		{	type Error = ConversionError;
 
-			fn try_from(value: VecDeque<$ty>) -> Result<Self, Self::Error>
-			{	let mut vec = Vec::with_capacity(value.len());
-				for v in value
-				{	vec.push(Value::try_from(v)?);
+			fn try_from(value: $ty<String, T>) -> Result<Self, Self::Error>
+			{	let mut obj = Map::with_capacity(value.len());
+				for (key, v) in value
+				{	let v = Value::try_from(v).map_err(|e| e.at_key(&key))?;
+					obj.insert(key, v);
 				}
-				Ok(Value::Array(vec))
+				Ok(Value::Object(obj))
 			}
 		}
 	}
 }
 
-impl_from_vec!(isize);
-impl_from_vec!(i64);
-impl_from_vec!(i32);
-impl_from_vec!(i16);
-impl_from_vec!(i8);
-impl_from_vec!(usize);
-impl_from_vec!(u64);
-impl_from_vec!(u32);
-impl_from_vec!(u16);
-impl_from_vec!(u8);
-impl_from_vec!(bool);
-impl_from_vec!(char);
-impl_from_vec!(());
-impl_from_vec!(String);
-
-impl_from_linked_list!(isize);
-impl_from_linked_list!(i64);
-impl_from_linked_list!(i32);
-impl_from_linked_list!(i16);
-impl_from_linked_list!(i8);
-impl_from_linked_list!(usize);
-impl_from_linked_list!(u64);
-impl_from_linked_list!(u32);
-impl_from_linked_list!(u16);
-impl_from_linked_list!(u8);
-impl_from_linked_list!(bool);
-impl_from_linked_list!(char);
-impl_from_linked_list!(());
-impl_from_linked_list!(String);
-
-impl_from_vec_deque!(isize);
-impl_from_vec_deque!(i64);
-impl_from_vec_deque!(i32);
-impl_from_vec_deque!(i16);
-impl_from_vec_deque!(i8);
-impl_from_vec_deque!(usize);
-impl_from_vec_deque!(u64);
-impl_from_vec_deque!(u32);
-impl_from_vec_deque!(u16);
-impl_from_vec_deque!(u8);
-impl_from_vec_deque!(bool);
-impl_from_vec_deque!(char);
-impl_from_vec_deque!(());
-impl_from_vec_deque!(String);
+impl_from_map!(HashMap);
+impl_from_map!(BTreeMap);
 
+impl<A, B> TryFrom<(A, B)> for Value where Value: TryFrom<A, Error=ConversionError> + TryFrom<B, Error=ConversionError>
+{	type Error = ConversionError;
 
-impl TryFrom<HashMap<String, Value>> for Value
-{	type Error = ();
+	fn try_from(value: (A, B)) -> Result<Self, Self::Error>
+	{	let a = Value::try_from(value.0).map_err(|e| e.at_index(0))?;
+		let b = Value::try_from(value.1).map_err(|e| e.at_index(1))?;
+		Ok(Value::Array(vec![a, b]))
+	}
+}
 
-	fn try_from(value: HashMap<String, Value>) -> Result<Self, Self::Error>
-	{	Ok(Value::Object(value))
+impl<A, B, C> TryFrom<(A, B, C)> for Value where Value: TryFrom<A, Error=ConversionError> + TryFrom<B, Error=ConversionError> + TryFrom<C, Error=ConversionError>
+{	type Error = ConversionError;
+
+	fn try_from(value: (A, B, C)) -> Result<Self, Self::Error>
+	{	let a = Value::try_from(value.0).map_err(|e| e.at_index(0))?;
+		let b = Value::try_from(value.1).map_err(|e| e.at_index(1))?;
+		let c = Value::try_from(value.2).map_err(|e| e.at_index(2))?;
+		Ok(Value::Array(vec![a, b, c]))
 	}
 }
 
-macro_rules! impl_from_hash_map
-{	($ty:ty) =>
-	{	impl TryFrom<HashMap<String, $ty>> for Value
-		{	type Error = ();
+/// Decodes `~1` to `/` and `~0` to `~` in JSON Pointer reference token.
+pub fn unescape_pointer_token(token: &str) -> Cow<'_, str>
+{	if token.contains('~')
+	{	Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+	}
+	else
+	{	Cow::Borrowed(token)
+	}
+}
 
-			fn try_from(value: HashMap<String, $ty>) -> Result<Self, Self::Error>
-			{	let mut obj = HashMap::with_capacity(value.len());
-				for (key, v) in value
-				{	obj.insert(key, Value::try_from(v)?);
-				}
-				Ok(Value::Object(obj))
-			}
-		}
+/// Array index in JSON Pointer: decimal digits without leading zeros.
+pub fn parse_pointer_index(token: &str) -> Option<usize>
+{	if token.is_empty() || token.len() > 1 && token.starts_with('0') || !token.bytes().all(|c| c.is_ascii_digit())
+	{	return None;
 	}
+	token.parse().ok()
 }
 
-macro_rules! impl_from_btree_map
-{	($ty:ty) =>
-	{	impl TryFrom<BTreeMap<String, $ty>> for Value
-		{	type Error = ();
+/// Type that can be used as index in [Value::get()](enum.Value.html#method.get), [Value::get_mut()](enum.Value.html#method.get_mut)
+/// and [Value::remove()](enum.Value.html#method.remove): `usize` for arrays, and `str` or `String` for objects.
+pub trait ValueIndex
+{	fn index_into<'a>(&self, value: &'a Value) -> Option<&'a Value>;
+	fn index_into_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value>;
+	fn remove_from(&self, value: &mut Value) -> Option<Value>;
+}
 
-			fn try_from(value: BTreeMap<String, $ty>) -> Result<Self, Self::Error>
-			{	let mut obj = HashMap::with_capacity(value.len());
-				for (key, v) in value
-				{	obj.insert(key, Value::try_from(v)?);
-				}
-				Ok(Value::Object(obj))
-			}
+impl ValueIndex for usize
+{	fn index_into<'a>(&self, value: &'a Value) -> Option<&'a Value>
+	{	match *value {Value::Array(ref v) => v.get(*self), _ => None}
+	}
+
+	fn index_into_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value>
+	{	match *value {Value::Array(ref mut v) => v.get_mut(*self), _ => None}
+	}
+
+	fn remove_from(&self, value: &mut Value) -> Option<Value>
+	{	match *value
+		{	Value::Array(ref mut v) if *self < v.len() => Some(v.remove(*self)),
+			_ => None
 		}
 	}
 }
 
-impl_from_hash_map!(isize);
-impl_from_hash_map!(i64);
-impl_from_hash_map!(i32);
-impl_from_hash_map!(i16);
-impl_from_hash_map!(i8);
-impl_from_hash_map!(usize);
-impl_from_hash_map!(u64);
-impl_from_hash_map!(u32);
-impl_from_hash_map!(u16);
-impl_from_hash_map!(u8);
-impl_from_hash_map!(bool);
-impl_from_hash_map!(char);
-impl_from_hash_map!(());
-impl_from_hash_map!(String);
-
-impl_from_btree_map!(isize);
-impl_from_btree_map!(i64);
-impl_from_btree_map!(i32);
-impl_from_btree_map!(i16);
-impl_from_btree_map!(i8);
-impl_from_btree_map!(usize);
-impl_from_btree_map!(u64);
-impl_from_btree_map!(u32);
-impl_from_btree_map!(u16);
-impl_from_btree_map!(u8);
-impl_from_btree_map!(bool);
-impl_from_btree_map!(char);
-impl_from_btree_map!(());
-impl_from_btree_map!(String);
+impl ValueIndex for str
+{	fn index_into<'a>(&self, value: &'a Value) -> Option<&'a Value>
+	{	match *value {Value::Object(ref v) => v.get(self), _ => None}
+	}
+
+	fn index_into_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value>
+	{	match *value {Value::Object(ref mut v) => v.get_mut(self), _ => None}
+	}
 
+	fn remove_from(&self, value: &mut Value) -> Option<Value>
+	{	match *value {Value::Object(ref mut v) => v.remove(self), _ => None}
+	}
+}
+
+impl ValueIndex for String
+{	fn index_into<'a>(&self, value: &'a Value) -> Option<&'a Value>
+	{	self.as_str().index_into(value)
+	}
+
+	fn index_into_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value>
+	{	self.as_str().index_into_mut(value)
+	}
+
+	fn remove_from(&self, value: &mut Value) -> Option<Value>
+	{	self.as_str().remove_from(value)
+	}
+}
+
+impl<T> ValueIndex for &T where T: ValueIndex + ?Sized
+{	fn index_into<'a>(&self, value: &'a Value) -> Option<&'a Value>
+	{	(**self).index_into(value)
+	}
+
+	fn index_into_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value>
+	{	(**self).index_into_mut(value)
+	}
+
+	fn remove_from(&self, value: &mut Value) -> Option<Value>
+	{	(**self).remove_from(value)
+	}
+}
 
 impl<'a> Index<&'a str> for Value
 {	type Output = Value;
@@ -626,6 +1309,7 @@ impl<'a> Index<&'a str> for Value
 		{	Value::Null => &Value::Null,
 			Value::Bool(ref _v) => &Value::Null,
 			Value::Number(ref _mantissa, ref _exponent, ref _is_negative) => &Value::Null,
+			Value::BigNumber(ref _digits, ref _exponent, ref _is_negative) => &Value::Null,
 			Value::String(ref _v) => &Value::Null,
 			Value::Array(ref _v) => &Value::Null,
 			Value::Object(ref v) =>
@@ -634,3 +1318,60 @@ impl<'a> Index<&'a str> for Value
 		}
 	}
 }
+
+/// Returns object property, or `Value::Null` if there's no such property, or this value is not object.
+/// Unlike `Index<&str>`, this can add new property:
+/// ```
+/// use nop_json::Value;
+///
+/// let mut v = Value::Null;
+/// v["a"]["b"] = Value::Bool(true); // null values become objects
+/// assert_eq!(v.to_string(), r#"{"a":{"b":true}}"#);
+/// ```
+///
+/// # Panics
+///
+/// If this value is not object or null.
+impl<'a> IndexMut<&'a str> for Value
+{	fn index_mut(&mut self, index: &'a str) -> &mut Self::Output
+	{	if let Value::Null = *self
+		{	*self = Value::Object(Map::new());
+		}
+		match *self
+		{	Value::Object(ref mut v) =>
+			{	if !v.contains_key(index)
+				{	v.insert(index.to_string(), Value::Null);
+				}
+				v.get_mut(index).unwrap()
+			}
+			_ => panic!("Cannot index {} with key \"{}\"", self.type_name(), index)
+		}
+	}
+}
+
+/// Returns array element, or `Value::Null` if the index is out of bounds, or this value is not array.
+impl Index<usize> for Value
+{	type Output = Value;
+
+	fn index(&self, index: usize) -> &Self::Output
+	{	match *self
+		{	Value::Array(ref v) => v.get(index).unwrap_or(&Value::Null),
+			_ => &Value::Null
+		}
+	}
+}
+
+/// # Panics
+///
+/// If this value is not array, or the index is out of bounds.
+impl IndexMut<usize> for Value
+{	fn index_mut(&mut self, index: usize) -> &mut Self::Output
+	{	match *self
+		{	Value::Array(ref mut v) =>
+			{	let len = v.len();
+				v.get_mut(index).unwrap_or_else(|| panic!("Index {} out of bounds of array with length {}", index, len))
+			}
+			_ => panic!("Cannot index {} with number {}", self.type_name(), index)
+		}
+	}
+}
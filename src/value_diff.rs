@@ -0,0 +1,127 @@
+use crate::value::Value;
+use crate::nop_json::{push_path_key, push_path_index};
+
+use std::fmt;
+
+/// One difference between two documents, found by [Value::diff()](enum.Value.html#method.diff).
+///
+/// The path is in the same notation, that [Reader](struct.Reader.html) uses in error messages, like `$.a[3].b`.
+/// Keys that are not identifiers are written in brackets and quoted, like `$["first name"]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change
+{	/// Property or array element exists only in the new document.
+	Added {path: String, value: Value},
+	/// Property or array element exists only in the old document.
+	Removed {path: String, value: Value},
+	/// Value of the same type has different value.
+	Changed {path: String, old: Value, new: Value},
+	/// Value has different type (like number vs string, or array vs object).
+	TypeChanged {path: String, old: Value, new: Value},
+}
+
+impl Change
+{	/// Where in the document this change occurred.
+	pub fn path(&self) -> &str
+	{	match *self
+		{	Change::Added {ref path, ..} => path,
+			Change::Removed {ref path, ..} => path,
+			Change::Changed {ref path, ..} => path,
+			Change::TypeChanged {ref path, ..} => path,
+		}
+	}
+}
+
+impl fmt::Display for Change
+{	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{	match *self
+		{	Change::Added {ref path, ref value} => write!(f, "{}: added {}", path, value),
+			Change::Removed {ref path, ref value} => write!(f, "{}: removed {}", path, value),
+			Change::Changed {ref path, ref old, ref new} => write!(f, "{}: changed {} -> {}", path, old, new),
+			Change::TypeChanged {ref path, ref old, ref new} => write!(f, "{}: type changed {} {} -> {} {}", path, old.type_name(), old, new.type_name(), new),
+		}
+	}
+}
+
+impl Value
+{	/// Compares two documents, and returns list of differences. Objects are compared recursively, and arrays - element by element.
+	/// Numbers are compared by numeric value, so `1` and `1.0` are equal.
+	///
+	/// ```
+	/// use nop_json::{json, Value, Change};
+	///
+	/// let old = json!({"name": "app", "servers": [{"port": 80}, {"port": 443}], "debug": false});
+	/// let new = json!({"name": "app", "servers": [{"port": 8080}, {"port": 443.0}], "debug": "no", "env": "prod"});
+	/// let changes = Value::diff(&old, &new);
+	/// assert_eq!(changes[0], Change::TypeChanged {path: "$.debug".to_string(), old: json!(false), new: json!("no")});
+	/// assert_eq!(changes[1], Change::Added {path: "$.env".to_string(), value: json!("prod")});
+	/// assert_eq!(changes[2], Change::Changed {path: "$.servers[0].port".to_string(), old: json!(80), new: json!(8080)});
+	/// assert_eq!(changes.len(), 3);
+	/// ```
+	pub fn diff(old: &Value, new: &Value) -> Vec<Change>
+	{	let mut changes = Vec::new();
+		let mut path = "$".to_string();
+		diff_into(old, new, &mut path, &mut changes);
+		changes
+	}
+
+	/// Compares two documents like [diff()](enum.Value.html#method.diff), and returns human-readable report, with one line per change.
+	/// If the documents are equal, returns empty string.
+	///
+	/// ```
+	/// use nop_json::{json, Value};
+	///
+	/// let report = Value::diff_report(&json!({"a": [1, 2], "b": true}), &json!({"a": [1], "b": 1}));
+	/// assert_eq!(report, "$.a[1]: removed 2\n$.b: type changed boolean true -> number 1\n");
+	/// ```
+	pub fn diff_report(old: &Value, new: &Value) -> String
+	{	let mut report = String::new();
+		for change in Value::diff(old, new)
+		{	report.push_str(&change.to_string());
+			report.push('\n');
+		}
+		report
+	}
+}
+
+fn diff_into(old: &Value, new: &Value, path: &mut String, changes: &mut Vec<Change>)
+{	if old == new
+	{	return;
+	}
+	let path_len = path.len();
+	match (old, new)
+	{	(Value::Object(old), Value::Object(new)) =>
+		{	let mut keys: Vec<&String> = old.keys().chain(new.keys().filter(|key| !old.contains_key(key))).collect();
+			keys.sort_unstable();
+			for key in keys
+			{	push_path_key(path, key);
+				match (old.get(key), new.get(key))
+				{	(Some(old_value), Some(new_value)) => diff_into(old_value, new_value, path, changes),
+					(Some(old_value), None) => changes.push(Change::Removed {path: path.clone(), value: old_value.clone()}),
+					(None, Some(new_value)) => changes.push(Change::Added {path: path.clone(), value: new_value.clone()}),
+					(None, None) => {}
+				}
+				path.truncate(path_len);
+			}
+		}
+		(Value::Array(old), Value::Array(new)) =>
+		{	for i in 0 .. old.len().max(new.len())
+			{	push_path_index(path, i);
+				match (old.get(i), new.get(i))
+				{	(Some(old_value), Some(new_value)) => diff_into(old_value, new_value, path, changes),
+					(Some(old_value), None) => changes.push(Change::Removed {path: path.clone(), value: old_value.clone()}),
+					(None, Some(new_value)) => changes.push(Change::Added {path: path.clone(), value: new_value.clone()}),
+					(None, None) => {}
+				}
+				path.truncate(path_len);
+			}
+		}
+		_ =>
+		{	if old.type_name() == new.type_name()
+			{	changes.push(Change::Changed {path: path.clone(), old: old.clone(), new: new.clone()});
+			}
+			else
+			{	changes.push(Change::TypeChanged {path: path.clone(), old: old.clone(), new: new.clone()});
+			}
+		}
+	}
+}
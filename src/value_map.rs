@@ -0,0 +1,229 @@
+use crate::value::Value;
+
+use std::fmt;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Properties of JSON object, that [Value::Object](enum.Value.html) holds. Works like `HashMap<String, Value>`, but keeps properties
+/// in the order they were inserted, so objects are written back in the same order as they were read or built.
+///
+/// Setting a property that already exists keeps it at it's place. Removing a property shifts all properties after it, so it takes O(n) time.
+/// Comparison doesn't depend on the order.
+///
+/// ```
+/// use nop_json::{Map, Value};
+///
+/// let mut map = Map::new();
+/// map.insert("b".to_string(), Value::Number(1, 0, false));
+/// map.insert("a".to_string(), Value::Number(2, 0, false));
+/// map.insert("b".to_string(), Value::Number(3, 0, false));
+/// assert_eq!(map.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+/// assert_eq!(Value::Object(map).to_string(), r#"{"b":3,"a":2}"#);
+/// ```
+#[derive(Clone, Default)]
+pub struct Map
+{	entries: Vec<(String, Value)>,
+	index: HashMap<String, usize>,
+}
+
+impl Map
+{	pub fn new() -> Self
+	{	Map::default()
+	}
+
+	pub fn with_capacity(capacity: usize) -> Self
+	{	Map {entries: Vec::with_capacity(capacity), index: HashMap::with_capacity(capacity)}
+	}
+
+	pub fn len(&self) -> usize
+	{	self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{	self.entries.is_empty()
+	}
+
+	pub fn clear(&mut self)
+	{	self.entries.clear();
+		self.index.clear();
+	}
+
+	pub fn contains_key(&self, key: &str) -> bool
+	{	self.index.contains_key(key)
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Value>
+	{	self.index.get(key).map(|&i| &self.entries[i].1)
+	}
+
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut Value>
+	{	match self.index.get(key)
+		{	Some(&i) => Some(&mut self.entries[i].1),
+			None => None
+		}
+	}
+
+	/// Sets property, and returns it's old value, if there was such property. New properties are added to the end.
+	pub fn insert(&mut self, key: String, value: Value) -> Option<Value>
+	{	match self.index.get(&key)
+		{	Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+			None =>
+			{	self.index.insert(key.clone(), self.entries.len());
+				self.entries.push((key, value));
+						None
+			}
+		}
+	}
+
+	/// Removes property, and returns it's value, if there was such property. The properties after it are shifted.
+	pub fn remove(&mut self, key: &str) -> Option<Value>
+	{	let pos = self.index.remove(key)?;
+		let (_, value) = self.entries.remove(pos);
+		for (key, _) in &self.entries[pos ..]
+		{	if let Some(i) = self.index.get_mut(key)
+			{	*i -= 1;
+			}
+		}
+		Some(value)
+	}
+
+	pub fn iter(&self) -> MapIter<'_>
+	{	MapIter {iter: self.entries.iter()}
+	}
+
+	pub fn iter_mut(&mut self) -> MapIterMut<'_>
+	{	MapIterMut {iter: self.entries.iter_mut()}
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item=&String>
+	{	self.entries.iter().map(|(key, _)| key)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item=&Value>
+	{	self.entries.iter().map(|(_, value)| value)
+	}
+
+	pub fn values_mut(&mut self) -> impl Iterator<Item=&mut Value>
+	{	self.entries.iter_mut().map(|(_, value)| value)
+	}
+}
+
+impl PartialEq for Map
+{	fn eq(&self, other: &Self) -> bool
+	{	self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+	}
+}
+
+impl Eq for Map {}
+
+impl fmt::Debug for Map
+{	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{	f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl FromIterator<(String, Value)> for Map
+{	fn from_iter<I>(iter: I) -> Self where I: IntoIterator<Item=(String, Value)>
+	{	let mut map = Map::new();
+		map.extend(iter);
+		map
+	}
+}
+
+impl Extend<(String, Value)> for Map
+{	fn extend<I>(&mut self, iter: I) where I: IntoIterator<Item=(String, Value)>
+	{	for (key, value) in iter
+		{	self.insert(key, value);
+		}
+	}
+}
+
+impl From<HashMap<String, Value>> for Map
+{	fn from(map: HashMap<String, Value>) -> Self
+	{	map.into_iter().collect()
+	}
+}
+
+impl IntoIterator for Map
+{	type Item = (String, Value);
+	type IntoIter = MapIntoIter;
+
+	fn into_iter(self) -> MapIntoIter
+	{	MapIntoIter {iter: self.entries.into_iter()}
+	}
+}
+
+impl<'a> IntoIterator for &'a Map
+{	type Item = (&'a String, &'a Value);
+	type IntoIter = MapIter<'a>;
+
+	fn into_iter(self) -> MapIter<'a>
+	{	self.iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a mut Map
+{	type Item = (&'a String, &'a mut Value);
+	type IntoIter = MapIterMut<'a>;
+
+	fn into_iter(self) -> MapIterMut<'a>
+	{	self.iter_mut()
+	}
+}
+
+/// Iterator over properties of [Map](struct.Map.html), in their order.
+pub struct MapIter<'a>
+{	iter: std::slice::Iter<'a, (String, Value)>,
+}
+
+impl<'a> Iterator for MapIter<'a>
+{	type Item = (&'a String, &'a Value);
+
+	fn next(&mut self) -> Option<Self::Item>
+	{	self.iter.next().map(|(key, value)| (key, value))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{	self.iter.size_hint()
+	}
+}
+
+impl<'a> ExactSizeIterator for MapIter<'a> {}
+
+/// Iterator over properties of [Map](struct.Map.html) with mutable values, in their order.
+pub struct MapIterMut<'a>
+{	iter: std::slice::IterMut<'a, (String, Value)>,
+}
+
+impl<'a> Iterator for MapIterMut<'a>
+{	type Item = (&'a String, &'a mut Value);
+
+	fn next(&mut self) -> Option<Self::Item>
+	{	self.iter.next().map(|(key, value)| (&*key, value))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{	self.iter.size_hint()
+	}
+}
+
+impl<'a> ExactSizeIterator for MapIterMut<'a> {}
+
+/// Iterator that moves properties out of [Map](struct.Map.html), in their order.
+pub struct MapIntoIter
+{	iter: std::vec::IntoIter<(String, Value)>,
+}
+
+impl Iterator for MapIntoIter
+{	type Item = (String, Value);
+
+	fn next(&mut self) -> Option<Self::Item>
+	{	self.iter.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{	self.iter.size_hint()
+	}
+}
+
+impl ExactSizeIterator for MapIntoIter {}
@@ -0,0 +1,154 @@
+use crate::nop_json::{Reader, ReaderBuilder, TryFromJson, write_number};
+use crate::value::Value;
+use crate::value_map::MapIter;
+
+use std::io;
+use numtoa::NumToA;
+
+enum Frame<'a>
+{	Value(&'a Value),
+	Array(std::slice::Iter<'a, Value>, bool),
+	Object(MapIter<'a>, bool),
+	Colon,
+	String(std::slice::Iter<'a, u8>), // contents of string or key, and then closing quote
+}
+
+/// JSON text of a [Value](enum.Value.html) tree, that [Reader](struct.Reader.html) reads in [Value::deserialize()](enum.Value.html#method.deserialize).
+/// The text is produced on demand, while walking the tree, so it's never collected as a whole.
+///
+/// Numbers are written like in `Value::to_string()`, and in strings only `"` and `\` are escaped.
+pub struct ValueTokens<'a>
+{	stack: Vec<Frame<'a>>,
+	pending: Vec<u8>,
+	pending_pos: usize,
+}
+
+impl<'a> ValueTokens<'a>
+{	pub fn new(value: &'a Value) -> Self
+	{	ValueTokens {stack: vec![Frame::Value(value)], pending: Vec::new(), pending_pos: 0}
+	}
+
+	fn push_number(&mut self, digits: &[u8], exponent: i16, is_negative: bool)
+	{	let pending = &mut self.pending;
+		write_number(digits, exponent, is_negative, |part| {pending.extend_from_slice(part); Ok::<(), ()>(())}).ok();
+	}
+}
+
+impl<'a> Iterator for ValueTokens<'a>
+{	type Item = u8;
+
+	fn next(&mut self) -> Option<u8>
+	{	loop
+		{	if self.pending_pos < self.pending.len()
+			{	self.pending_pos += 1;
+				return Some(self.pending[self.pending_pos - 1]);
+			}
+			self.pending.clear();
+			self.pending_pos = 0;
+			match self.stack.pop()?
+			{	Frame::Value(value) =>
+				{	match *value
+					{	Value::Null => self.pending.extend_from_slice(b"null"),
+						Value::Bool(false) => self.pending.extend_from_slice(b"false"),
+						Value::Bool(true) => self.pending.extend_from_slice(b"true"),
+						Value::Number(mantissa, exponent, is_negative) =>
+						{	let mut buffer = [0u8; 24];
+							self.push_number(mantissa.numtoa(10, &mut buffer), exponent, is_negative);
+						}
+						Value::BigNumber(ref digits, exponent, is_negative) => self.push_number(digits.as_bytes(), exponent, is_negative),
+						Value::String(ref s) =>
+						{	self.stack.push(Frame::String(s.as_bytes().iter()));
+							return Some(b'"');
+						}
+						Value::Array(ref v) =>
+						{	self.stack.push(Frame::Array(v.iter(), true));
+							return Some(b'[');
+						}
+						Value::Object(ref v) =>
+						{	self.stack.push(Frame::Object(v.iter(), true));
+							return Some(b'{');
+						}
+					}
+				}
+				Frame::Array(mut iter, is_first) =>
+				{	match iter.next()
+					{	None => return Some(b']'),
+						Some(item) =>
+						{	self.stack.push(Frame::Array(iter, false));
+							self.stack.push(Frame::Value(item));
+							if !is_first
+							{	return Some(b',');
+							}
+						}
+					}
+				}
+				Frame::Object(mut iter, is_first) =>
+				{	match iter.next()
+					{	None => return Some(b'}'),
+						Some((key, item)) =>
+						{	self.stack.push(Frame::Object(iter, false));
+							self.stack.push(Frame::Value(item));
+							self.stack.push(Frame::Colon);
+							self.stack.push(Frame::String(key.as_bytes().iter()));
+							if !is_first
+							{	self.pending.push(b'"');
+								return Some(b',');
+							}
+							return Some(b'"');
+						}
+					}
+				}
+				Frame::Colon => return Some(b':'),
+				Frame::String(mut iter) =>
+				{	return match iter.next()
+					{	None => Some(b'"'),
+						Some(&c) =>
+						{	self.stack.push(Frame::String(iter));
+							if c==b'"' || c==b'\\'
+							{	self.pending.push(c);
+								return Some(b'\\');
+							}
+							Some(c)
+						}
+					};
+				}
+			}
+		}
+	}
+}
+
+impl Value
+{	/// Converts this value to any type that implements [TryFromJson](trait.TryFromJson.html), including types with `#[derive(TryFromJson)]`.
+	///
+	/// The same `TryFromJson` implementation is used, as when reading from a JSON stream, so conversion rules, validation and error messages
+	/// (with path to the failed property, like `$[1].price`) are the same. The reader gets JSON text that is produced while walking the tree,
+	/// so the text is never collected as a whole, and [value_size_limit](struct.ReaderBuilder.html#method.value_size_limit) doesn't apply.
+	///
+	/// ```
+	/// use nop_json::{json, Value};
+	/// use std::collections::HashMap;
+	///
+	/// let v = json!({"a": [1, 2], "b": [3]});
+	/// let map: HashMap<String, Vec<u8>> = v.deserialize().unwrap();
+	/// assert_eq!(map["a"], vec![1, 2]);
+	///
+	/// let error = json!([3, 1000]).deserialize::<Vec<u8>>().unwrap_err();
+	/// assert_eq!(error.to_string(), "$[1]: Invalid JSON input: Number is too big");
+	/// ```
+	pub fn deserialize<U>(&self) -> io::Result<U> where U: TryFromJson
+	{	self.deserialize_with(ReaderBuilder::new())
+	}
+
+	/// Like [deserialize()](enum.Value.html#method.deserialize), but uses [Reader](struct.Reader.html) options from the given builder.
+	///
+	/// ```
+	/// use nop_json::{json, Decimal, ReaderBuilder, Rounding};
+	///
+	/// let price: Decimal<2> = json!(1.005).deserialize_with(ReaderBuilder::new().decimal_rounding(Rounding::HalfEven)).unwrap();
+	/// assert_eq!(price, Decimal::new(100));
+	/// ```
+	pub fn deserialize_with<U>(&self, builder: ReaderBuilder) -> io::Result<U> where U: TryFromJson
+	{	let mut reader: Reader<ValueTokens> = builder.value_tree().build(ValueTokens::new(self));
+		reader.read()
+	}
+}
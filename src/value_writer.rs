@@ -0,0 +1,452 @@
+use crate::value::{Value, Digits};
+use crate::value_map::Map;
+use crate::write_to_json::WriteToJson;
+use crate::json_sink::JsonSink;
+
+use std::io;
+use std::char;
+
+enum Container
+{	Array(Vec<Value>),
+	Object(Map, String),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State
+{	Value,
+	AfterValue,
+	Key,
+	Colon,
+	String,
+	Escape,
+	Unicode,
+	Number,
+	Literal,
+	Done,
+}
+
+/// [JsonSink](trait.JsonSink.html) implementation, that builds [Value](enum.Value.html).
+///
+/// Built-in types, and types with `#[derive(WriteToJson)]` or `#[derive(DebugToJson)]` pass their structure and values to it directly.
+/// Other types write JSON text through `io::Write`, and this writer builds the tree from those bytes as they arrive,
+/// token by token. The text is never collected as a whole. Numbers keep all their digits (as `Value::BigNumber` if needed),
+/// and object properties keep the order in which they were written.
+/// Usually you don't need this object directly, but call [to_value()](fn.to_value.html) or [DebugToJson::to_value()](trait.DebugToJson.html#method.to_value).
+///
+/// ```
+/// use nop_json::{ValueWriter, Value, WriteToJson};
+///
+/// let mut writer = ValueWriter::new();
+/// vec![1.5, 2.0].write_to_sink(&mut writer).unwrap();
+/// assert_eq!(writer.finish().unwrap().to_string(), "[1.5,2]");
+///
+/// let mut writer = ValueWriter::new();
+/// vec![1.5, 2.0].write_to_json(&mut writer).unwrap(); // as text
+/// assert_eq!(writer.finish().unwrap().to_string(), "[1.5,2]");
+/// ```
+pub struct ValueWriter
+{	stack: Vec<Container>,
+	state: State,
+	is_key: bool,
+	can_close: bool,
+	text: Vec<u8>,
+	code: u32,
+	code_len: u8,
+	high_surrogate: u32,
+	raw_depth: Option<usize>, // stack depth where value written as text between begin_raw() and end_raw() is put
+	result: Option<Value>,
+}
+
+impl ValueWriter
+{	pub fn new() -> Self
+	{	ValueWriter
+		{	stack: Vec::new(),
+			state: State::Value,
+			is_key: false,
+			can_close: false,
+			text: Vec::new(),
+			code: 0,
+			code_len: 0,
+			high_surrogate: 0,
+			raw_depth: None,
+			result: None,
+		}
+	}
+
+	/// Call this after the whole JSON value was written, to get the result.
+	pub fn finish(mut self) -> io::Result<Value>
+	{	self.end_token().map_err(invalid_data)?;
+		match self.result
+		{	Some(value) if self.state == State::Done && self.raw_depth.is_none() => Ok(value),
+			_ => Err(invalid_data("Invalid JSON input: unexpected end of input"))
+		}
+	}
+
+	fn put_value(&mut self, value: Value)
+	{	match self.stack.last_mut()
+		{	None =>
+			{	self.result = Some(value);
+				self.state = State::Done;
+				return;
+			}
+			Some(Container::Array(v)) => v.push(value),
+			Some(Container::Object(v, key)) => {v.insert(std::mem::take(key), value);}
+		}
+		self.state = State::AfterValue;
+	}
+
+	/// Completes number or literal that ends at the current byte (or at the end of input).
+	fn end_token(&mut self) -> Result<(), &'static str>
+	{	match self.state
+		{	State::Number =>
+			{	let value = std::str::from_utf8(&self.text).ok().and_then(|s| Value::parse_number(s).ok()).ok_or("Invalid JSON input: invalid number format")?;
+				self.text.clear();
+				self.put_value(value);
+			}
+			State::Literal =>
+			{	let value = match &self.text[..]
+				{	b"null" => Value::Null,
+					b"true" => Value::Bool(true),
+					b"false" => Value::Bool(false),
+					_ => return Err("Invalid JSON input: unexpected identifier")
+				};
+				self.text.clear();
+				self.put_value(value);
+			}
+			State::String | State::Escape | State::Unicode =>
+			{	return Err("Invalid JSON input: unexpected end of input");
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	fn push_char(&mut self, c: char)
+	{	let mut buffer = [0; 4];
+		self.text.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+	}
+
+	fn put_byte(&mut self, c: u8) -> Result<(), &'static str>
+	{	match self.state
+		{	State::String =>
+			{	match c
+				{	b'"' =>
+					{	let text = String::from_utf8(std::mem::take(&mut self.text)).map_err(|_| "Invalid UTF-8 in string")?;
+						if self.is_key
+						{	if let Some(Container::Object(_, key)) = self.stack.last_mut()
+							{	*key = text;
+							}
+							self.state = State::Colon;
+						}
+						else
+						{	self.put_value(Value::String(text));
+						}
+					}
+					b'\\' => self.state = State::Escape,
+					_ => self.text.push(c)
+				}
+				if self.high_surrogate != 0 && self.state != State::Escape
+				{	return Err("Invalid UTF-16 surrogate pair");
+				}
+				return Ok(());
+			}
+			State::Escape =>
+			{	let c = match c
+				{	b'"' | b'\\' | b'/' => c,
+					b'b' => 8,
+					b'f' => 12,
+					b'n' => b'\n',
+					b'r' => b'\r',
+					b't' => b'\t',
+					b'u' =>
+					{	self.code = 0;
+						self.code_len = 0;
+						self.state = State::Unicode;
+						return Ok(());
+					}
+					_ => return Err("Invalid JSON input: invalid escape sequence")
+				};
+				if self.high_surrogate != 0
+				{	return Err("Invalid UTF-16 surrogate pair");
+				}
+				self.text.push(c);
+				self.state = State::String;
+				return Ok(());
+			}
+			State::Unicode =>
+			{	let digit = (c as char).to_digit(16).ok_or("Invalid JSON input: invalid escape sequence")?;
+				self.code = self.code << 4 | digit;
+				self.code_len += 1;
+				if self.code_len == 4
+				{	let code = self.code;
+					if self.high_surrogate != 0
+					{	if !(0xDC00 .. 0xE000).contains(&code)
+						{	return Err("Invalid UTF-16 surrogate pair");
+						}
+						let c = 0x10000 + ((self.high_surrogate - 0xD800) << 10 | (code - 0xDC00));
+						self.high_surrogate = 0;
+						self.push_char(char::from_u32(c).ok_or("Invalid UTF-16 surrogate pair")?);
+					}
+					else if (0xD800 .. 0xDC00).contains(&code)
+					{	self.high_surrogate = code;
+					}
+					else
+					{	self.push_char(char::from_u32(code).ok_or("Invalid UTF-16 surrogate pair")?);
+					}
+					self.state = State::String;
+				}
+				return Ok(());
+			}
+			State::Number =>
+			{	if c.is_ascii_digit() || c==b'.' || c==b'e' || c==b'E' || c==b'+' || c==b'-'
+				{	self.text.push(c);
+					return Ok(());
+				}
+				self.end_token()?;
+			}
+			State::Literal =>
+			{	if c.is_ascii_alphanumeric()
+				{	self.text.push(c);
+					return Ok(());
+				}
+				self.end_token()?;
+			}
+			_ => {}
+		}
+		if c.is_ascii_whitespace()
+		{	return Ok(());
+		}
+		match self.state
+		{	State::Value =>
+			{	match c
+				{	b'"' =>
+					{	self.is_key = false;
+						self.state = State::String;
+					}
+					b'[' =>
+					{	self.stack.push(Container::Array(Vec::new()));
+						self.can_close = true;
+					}
+					b'{' =>
+					{	self.stack.push(Container::Object(Map::new(), String::new()));
+						self.state = State::Key;
+						self.can_close = true;
+					}
+					b']' if self.can_close =>
+					{	self.close()?;
+					}
+					b'-' | b'0' ..= b'9' =>
+					{	self.text.push(c);
+						self.state = State::Number;
+					}
+					b'a' ..= b'z' =>
+					{	self.text.push(c);
+						self.state = State::Literal;
+					}
+					_ => return Err("Invalid JSON input: expected value")
+				}
+				if c != b'[' && c != b'{'
+				{	self.can_close = false;
+				}
+			}
+			State::AfterValue =>
+			{	if self.raw_depth == Some(self.stack.len())
+				{	return Err("Invalid JSON input: unexpected data after end of value");
+				}
+				match (c, self.stack.last())
+				{	(b',', Some(Container::Array(_))) => self.state = State::Value,
+					(b',', _) => self.state = State::Key,
+					(b']', Some(Container::Array(_))) | (b'}', Some(Container::Object(_, _))) => self.close()?,
+					_ => return Err("Invalid JSON input: expected ',' or end of array or object")
+				}
+			}
+			State::Key =>
+			{	match c
+				{	b'"' =>
+					{	self.is_key = true;
+						self.state = State::String;
+					}
+					b'}' if self.can_close => self.close()?,
+					_ => return Err("Invalid JSON input: expected property name")
+				}
+				self.can_close = false;
+			}
+			State::Colon =>
+			{	if c != b':'
+				{	return Err("Invalid JSON input: expected ':'");
+				}
+				self.state = State::Value;
+			}
+			_ =>
+			{	return Err("Invalid JSON input: unexpected data after end of value");
+			}
+		}
+		Ok(())
+	}
+
+	/// Called before a value is passed through `JsonSink` methods. Separates array elements.
+	fn begin_value(&mut self) -> io::Result<()>
+	{	match (self.state, self.stack.last())
+		{	(State::Value, _) | (State::AfterValue, Some(Container::Array(_))) if self.raw_depth.is_none() =>
+			{	self.state = State::Value;
+				self.can_close = false;
+				Ok(())
+			}
+			_ => Err(invalid_data("Invalid JSON input: unexpected value"))
+		}
+	}
+
+	fn close(&mut self) -> Result<(), &'static str>
+	{	let value = match self.stack.pop()
+		{	Some(Container::Array(v)) => Value::Array(v),
+			Some(Container::Object(v, _)) => Value::Object(v),
+			None => return Err("Invalid JSON input: unexpected end of array or object")
+		};
+		self.can_close = false;
+		self.put_value(value);
+		Ok(())
+	}
+}
+
+impl Default for ValueWriter
+{	fn default() -> Self
+	{	Self::new()
+	}
+}
+
+impl io::Write for ValueWriter
+{	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{	for &c in buf
+		{	self.put_byte(c).map_err(invalid_data)?;
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()>
+	{	Ok(())
+	}
+}
+
+impl JsonSink for ValueWriter
+{	fn begin_array(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.stack.push(Container::Array(Vec::new()));
+		self.can_close = true;
+		Ok(())
+	}
+
+	fn end_array(&mut self) -> io::Result<()>
+	{	match (self.state, self.stack.last())
+		{	(State::AfterValue, Some(Container::Array(_))) if self.raw_depth.is_none() => self.close().map_err(invalid_data),
+			(State::Value, Some(Container::Array(_))) if self.can_close && self.raw_depth.is_none() => self.close().map_err(invalid_data),
+			_ => Err(invalid_data("Invalid JSON input: unexpected end of array"))
+		}
+	}
+
+	fn begin_object(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.stack.push(Container::Object(Map::new(), String::new()));
+		self.state = State::Key;
+		self.can_close = true;
+		Ok(())
+	}
+
+	fn key(&mut self, key: &str) -> io::Result<()>
+	{	match (self.state, self.stack.last_mut())
+		{	(State::Key | State::AfterValue, Some(Container::Object(_, cur_key))) if self.raw_depth.is_none() =>
+			{	cur_key.clear();
+				cur_key.push_str(key);
+				self.state = State::Value;
+				self.can_close = false;
+				Ok(())
+			}
+			_ => Err(invalid_data("Invalid JSON input: unexpected property name"))
+		}
+	}
+
+	fn end_object(&mut self) -> io::Result<()>
+	{	match (self.state, self.stack.last())
+		{	(State::AfterValue, Some(Container::Object(_, _))) if self.raw_depth.is_none() => self.close().map_err(invalid_data),
+			(State::Key, Some(Container::Object(_, _))) if self.can_close && self.raw_depth.is_none() => self.close().map_err(invalid_data),
+			_ => Err(invalid_data("Invalid JSON input: unexpected end of object"))
+		}
+	}
+
+	fn null(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.put_value(Value::Null);
+		Ok(())
+	}
+
+	fn bool(&mut self, value: bool) -> io::Result<()>
+	{	self.begin_value()?;
+		self.put_value(Value::Bool(value));
+		Ok(())
+	}
+
+	fn number(&mut self, digits: &[u8], exponent: i16, is_negative: bool) -> io::Result<()>
+	{	if !digits.iter().all(u8::is_ascii_digit)
+		{	return Err(invalid_data("Invalid JSON input: invalid number format"));
+		}
+		self.begin_value()?;
+		let mut mantissa = 0u64;
+		for &c in digits
+		{	match mantissa.checked_mul(10).and_then(|m| m.checked_add((c - b'0') as u64))
+			{	Some(m) => mantissa = m,
+				None =>
+				{	let digits = Digits::new_unchecked(String::from_utf8_lossy(digits).into_owned());
+					self.put_value(Value::BigNumber(digits, exponent, is_negative));
+					return Ok(());
+				}
+			}
+		}
+		self.put_value(Value::Number(mantissa, exponent, is_negative));
+		Ok(())
+	}
+
+	fn string(&mut self, value: &str) -> io::Result<()>
+	{	self.begin_value()?;
+		self.put_value(Value::String(value.to_string()));
+		Ok(())
+	}
+
+	fn begin_raw(&mut self) -> io::Result<()>
+	{	self.begin_value()?;
+		self.raw_depth = Some(self.stack.len());
+		Ok(())
+	}
+
+	fn end_raw(&mut self) -> io::Result<()>
+	{	self.end_token().map_err(invalid_data)?;
+		match self.raw_depth.take()
+		{	Some(depth) if depth == self.stack.len() && (self.state == State::AfterValue || self.state == State::Done) => Ok(()),
+			_ => Err(invalid_data("Invalid JSON input: unexpected end of input"))
+		}
+	}
+}
+
+fn invalid_data(message: &str) -> io::Error
+{	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Serializes any type that implements [WriteToJson](trait.WriteToJson.html) (including types with `#[derive(WriteToJson)]`) directly to [Value](enum.Value.html),
+/// without producing JSON text. The result can be modified and then written.
+///
+/// Numbers keep all their digits, and object properties keep the order in which they were written (`Value::Object` holds a [Map](struct.Map.html)).
+///
+/// ```
+/// use nop_json::{to_value, json};
+/// use std::collections::BTreeMap;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert("id".to_string(), vec![u128::MAX]);
+/// let mut v = to_value(&map).unwrap();
+/// v.insert("secret", json!("***"));
+/// assert_eq!(v, json!({"id": [340282366920938463463374607431768211455u128], "secret": "***"}));
+/// assert_eq!(v.to_string(), r#"{"id":[340282366920938463463374607431768211455],"secret":"***"}"#);
+/// ```
+pub fn to_value<T>(value: &T) -> io::Result<Value> where T: WriteToJson<ValueWriter>
+{	let mut writer = ValueWriter::new();
+	value.write_to_sink(&mut writer)?;
+	writer.finish()
+}
@@ -1,12 +1,15 @@
 pub use nop_json_derive::WriteToJson;
-use crate::debug_to_json::DebugToJson;
 use crate::value::Value;
-use crate::escape::escape;
+use crate::decimal::Decimal;
+use crate::nop_json::{number_to_string, float_to_string, write_number, READER_BUFFER_SIZE};
+use crate::escape::write_json_string;
+use crate::json_sink::{JsonSink, integer_to_sink, float_to_sink, value_to_sink};
 
-use std::{char, fmt, f32, f64, io};
+use std::{fmt, io};
 use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, LinkedList, VecDeque};
 use std::sync::{RwLock, Mutex, Arc};
 use std::rc::Rc;
+use numtoa::NumToA;
 
 
 /// Trait that can be automatically derived for structs and enums.
@@ -24,46 +27,280 @@ use std::rc::Rc;
 /// ```
 pub trait WriteToJson<W: io::Write>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>;
+
+	/// Passes this value to [JsonSink](trait.JsonSink.html) as structure, like [ValueWriter](struct.ValueWriter.html) that builds [Value](enum.Value.html) from it.
+	/// Built-in types and derived implementations override this method. The default implementation writes JSON text with `write_to_json()`
+	/// between `begin_raw()` and `end_raw()`.
+	/// ```
+	/// use nop_json::{WriteToJson, ValueWriter, json};
+	///
+	/// let mut writer = ValueWriter::new();
+	/// (1, "a".to_string()).write_to_sink(&mut writer).unwrap();
+	/// assert_eq!(writer.finish().unwrap(), json!([1, "a"]));
+	/// ```
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.begin_raw()?;
+		self.write_to_json(out)?;
+		out.end_raw()
+	}
 }
 
-fn write_debug_to_json<W, T>(out: &mut W, value: &T) -> io::Result<()> where T: DebugToJson, W: io::Write
-{	struct Wrapper<'a, T: DebugToJson>
-	{	value: &'a T
+const ZEROS: [u8; 40] = [b'0'; 40];
+
+/// Writes decimal digits of `n` to the end of `buffer`, and returns them.
+fn u128_to_digits(mut n: u128, buffer: &mut [u8; 40]) -> &[u8]
+{	let mut pos = buffer.len();
+	loop
+	{	pos -= 1;
+		buffer[pos] = b'0' + (n % 10) as u8;
+		n /= 10;
+		if n == 0
+		{	break;
+		}
 	}
-	impl<'a, T: DebugToJson> fmt::Display for Wrapper<'a, T>
-	{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
-		{	DebugToJson::fmt(self.value, out)
+	&buffer[pos ..]
+}
+
+fn write_float<W, F>(out: &mut W, value: F, is_infinite: bool, is_negative: bool, is_nan: bool) -> io::Result<()> where W: io::Write, F: fmt::LowerExp
+{	if is_nan
+	{	out.write_all(b"\"NaN\"")
+	}
+	else if is_infinite
+	{	out.write_all(if is_negative {b"\"-Infinity\""} else {b"\"Infinity\""})
+	}
+	else
+	{	let mut buffer = [0u8; READER_BUFFER_SIZE];
+		let len = float_to_string(&mut buffer, value).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Cannot format number"))?;
+		out.write_all(&buffer[0 .. len])
+	}
+}
+
+fn write_sequence<'a, W, T, I>(out: &mut W, items: I) -> io::Result<()> where W: io::Write, T: WriteToJson<W> + 'a, I: Iterator<Item=&'a T>
+{	let mut c = b'[';
+	for item in items
+	{	out.write_all(&[c])?;
+		item.write_to_json(out)?;
+		c = b',';
+	}
+	if c == b'['
+	{	out.write_all(b"[]")
+	}
+	else
+	{	out.write_all(b"]")
+	}
+}
+
+fn write_map<'a, W, T, I>(out: &mut W, items: I) -> io::Result<()> where W: io::Write, T: WriteToJson<W> + 'a, I: Iterator<Item=(&'a String, &'a T)>
+{	let mut c = b'{';
+	for (key, item) in items
+	{	out.write_all(&[c])?;
+		write_json_string(out, key.as_bytes())?;
+		out.write_all(b":")?;
+		item.write_to_json(out)?;
+		c = b',';
+	}
+	if c == b'{'
+	{	out.write_all(b"{}")
+	}
+	else
+	{	out.write_all(b"}")
+	}
+}
+
+fn sink_sequence<'a, W, T, I>(out: &mut W, items: I) -> io::Result<()> where W: JsonSink, T: WriteToJson<W> + 'a, I: Iterator<Item=&'a T>
+{	out.begin_array()?;
+	for item in items
+	{	item.write_to_sink(out)?;
+	}
+	out.end_array()
+}
+
+fn sink_map<'a, W, T, I>(out: &mut W, items: I) -> io::Result<()> where W: JsonSink, T: WriteToJson<W> + 'a, I: Iterator<Item=(&'a String, &'a T)>
+{	out.begin_object()?;
+	for (key, item) in items
+	{	out.key(key)?;
+		item.write_to_sink(out)?;
+	}
+	out.end_object()
+}
+
+macro_rules! impl_write_integer
+{	($($t:ty),*) =>
+	{	$(
+			impl<W: io::Write> WriteToJson<W> for $t
+			{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+				{	let mut buffer = [0u8; 24];
+					out.write_all(self.numtoa(10, &mut buffer))
+				}
+
+				fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+				{	let value = *self as i128;
+					integer_to_sink(out, value.unsigned_abs(), 0, value < 0)
+				}
+			}
+		)*
+	}
+}
+
+impl_write_integer!(isize, i64, i32, i16, i8, usize, u64, u32, u16, u8);
+
+impl<W: io::Write> WriteToJson<W> for i128
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	let mut buffer = [0u8; 40];
+		if *self < 0
+		{	out.write_all(b"-")?;
 		}
+		out.write_all(u128_to_digits(self.unsigned_abs(), &mut buffer))
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	integer_to_sink(out, self.unsigned_abs(), 0, *self < 0)
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for u128
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	let mut buffer = [0u8; 40];
+		out.write_all(u128_to_digits(*self, &mut buffer))
 	}
-	let w = Wrapper {value};
-	write!(out, "{}", w)
-}
-
-impl<W: io::Write> WriteToJson<W> for ()     {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for isize  {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for i128   {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for i64    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for i32    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for i16    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for i8     {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for usize  {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for u128   {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for u64    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for u32    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for u16    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for u8     {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for f64    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for f32    {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for bool   {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for char   {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for String {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
-impl<W: io::Write> WriteToJson<W> for Value  {fn write_to_json(&self, out: &mut W) -> io::Result<()> {write_debug_to_json(out, self)}}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	integer_to_sink(out, *self, 0, false)
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for f64
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	write_float(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	float_to_sink(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for f32
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	write_float(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	float_to_sink(out, *self, self.is_infinite(), self.is_sign_negative(), self.is_nan())
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for ()
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	out.write_all(b"null")
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.null()
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for bool
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	out.write_all(if *self {b"true"} else {b"false"})
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.bool(*self)
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for char
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	let mut buffer = [0u8; 4];
+		write_json_string(out, self.encode_utf8(&mut buffer).as_bytes())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	let mut buffer = [0u8; 4];
+		out.string(self.encode_utf8(&mut buffer))
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for String
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	write_json_string(out, self.as_bytes())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.string(self)
+	}
+}
+
+impl<W: io::Write> WriteToJson<W> for Value
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	match *self
+		{	Value::Null => out.write_all(b"null"),
+			Value::Bool(v) => out.write_all(if v {b"true"} else {b"false"}),
+			Value::Number(mantissa, exponent, is_negative) =>
+			{	let mut buffer = [0u8; 24];
+				let mantissa = mantissa.numtoa(10, &mut buffer);
+				let mut buffer = [0u8; READER_BUFFER_SIZE];
+				buffer[0 .. mantissa.len()].copy_from_slice(mantissa);
+				let len = number_to_string(&mut buffer, mantissa.len(), exponent, is_negative).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Cannot format number"))?;
+				out.write_all(&buffer[0 .. len])
+			},
+			Value::BigNumber(ref digits, exponent, is_negative) =>
+			{	write_number(digits.as_bytes(), exponent, is_negative, |part| out.write_all(part))
+			},
+			Value::String(ref v) => write_json_string(out, v.as_bytes()),
+			Value::Array(ref v) => write_sequence(out, v.iter()),
+			Value::Object(ref v) => write_map(out, v.iter()),
+		}
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	value_to_sink(out, self)
+	}
+}
+
+impl<W: io::Write, const SCALE: u32> WriteToJson<W> for Decimal<SCALE>
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	let mut buffer = [0u8; 40];
+		let digits = u128_to_digits(self.mantissa().unsigned_abs(), &mut buffer);
+		let scale = SCALE as usize;
+		if self.mantissa() < 0
+		{	out.write_all(b"-")?;
+		}
+		if scale == 0
+		{	out.write_all(digits)
+		}
+		else if digits.len() > scale
+		{	let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+			out.write_all(int_part)?;
+			out.write_all(b".")?;
+			out.write_all(frac_part)
+		}
+		else
+		{	out.write_all(b"0.")?;
+			let mut zeros = scale - digits.len();
+			while zeros > 0
+			{	let n = zeros.min(ZEROS.len());
+				out.write_all(&ZEROS[.. n])?;
+				zeros -= n;
+			}
+			out.write_all(digits)
+		}
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	integer_to_sink(out, self.mantissa().unsigned_abs(), -(SCALE as i16), self.mantissa() < 0)
+	}
+}
 
 impl<W: io::Write, T> WriteToJson<W> for Box<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
 	{	let v: &T = &*self;
 		v.write_to_json(out)
 	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	T::write_to_sink(self, out)
+	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for RwLock<T> where T: WriteToJson<W>
@@ -73,6 +310,13 @@ impl<W: io::Write, T> WriteToJson<W> for RwLock<T> where T: WriteToJson<W>
 			Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
 		}
 	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	match self.read()
+		{	Ok(v) => v.write_to_sink(out),
+			Err(e) => Err(io::Error::other(e.to_string())),
+		}
+	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for Mutex<T> where T: WriteToJson<W>
@@ -82,6 +326,13 @@ impl<W: io::Write, T> WriteToJson<W> for Mutex<T> where T: WriteToJson<W>
 			Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
 		}
 	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	match self.lock()
+		{	Ok(v) => v.write_to_sink(out),
+			Err(e) => Err(io::Error::other(e.to_string())),
+		}
+	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for Rc<T> where T: WriteToJson<W>
@@ -89,6 +340,10 @@ impl<W: io::Write, T> WriteToJson<W> for Rc<T> where T: WriteToJson<W>
 	{	let v: &T = &*self;
 		v.write_to_json(out)
 	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	T::write_to_sink(self, out)
+	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for Arc<T> where T: WriteToJson<W>
@@ -96,154 +351,131 @@ impl<W: io::Write, T> WriteToJson<W> for Arc<T> where T: WriteToJson<W>
 	{	let v: &T = &*self;
 		v.write_to_json(out)
 	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	T::write_to_sink(self, out)
+	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for Option<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
 	{	match *self
 		{	Some(ref v) => v.write_to_json(out),
-			None => write!(out, "null"),
+			None => out.write_all(b"null"),
+		}
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	match *self
+		{	Some(ref v) => v.write_to_sink(out),
+			None => out.null(),
 		}
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for Vec<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '[';
-		for item in self
-		{	write!(out, "{}", c)?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '['
-		{	write!(out, "[]")
-		}
-		else
-		{	write!(out, "]")
-		}
+	{	write_sequence(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_sequence(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for HashSet<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '[';
-		for item in self
-		{	write!(out, "{}", c)?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '['
-		{	write!(out, "[]")
-		}
-		else
-		{	write!(out, "]")
-		}
+	{	write_sequence(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_sequence(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for LinkedList<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '[';
-		for item in self
-		{	write!(out, "{}", c)?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '['
-		{	write!(out, "[]")
-		}
-		else
-		{	write!(out, "]")
-		}
+	{	write_sequence(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_sequence(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for VecDeque<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '[';
-		for item in self
-		{	write!(out, "{}", c)?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '['
-		{	write!(out, "[]")
-		}
-		else
-		{	write!(out, "]")
-		}
+	{	write_sequence(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_sequence(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for BTreeSet<T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '[';
-		for item in self
-		{	write!(out, "{}", c)?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '['
-		{	write!(out, "[]")
-		}
-		else
-		{	write!(out, "]")
-		}
+	{	write_sequence(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_sequence(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for HashMap<String, T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '{';
-		for (key, item) in self
-		{	write!(out, "{}\"{}\":", c, escape(key))?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '{'
-		{	write!(out, "{{}}")
-		}
-		else
-		{	write!(out, "}}")
-		}
+	{	write_map(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_map(out, self.iter())
 	}
 }
 
 impl<W: io::Write, T> WriteToJson<W> for BTreeMap<String, T> where T: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	let mut c = '{';
-		for (key, item) in self
-		{	write!(out, "{}\"{}\":", c, escape(key))?;
-			item.write_to_json(out)?;
-			c = ',';
-		}
-		if c == '{'
-		{	write!(out, "{{}}")
-		}
-		else
-		{	write!(out, "}}")
-		}
+	{	write_map(out, self.iter())
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	sink_map(out, self.iter())
 	}
 }
 
 impl<W: io::Write, A, B> WriteToJson<W> for (A, B) where A: WriteToJson<W>, B: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	write!(out, "[")?;
+	{	out.write_all(b"[")?;
 		self.0.write_to_json(out)?;
-		write!(out, ",")?;
+		out.write_all(b",")?;
 		self.1.write_to_json(out)?;
-		write!(out, "]")
+		out.write_all(b"]")
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.begin_array()?;
+		self.0.write_to_sink(out)?;
+		self.1.write_to_sink(out)?;
+		out.end_array()
 	}
 }
 
 impl<W: io::Write, A, B, C> WriteToJson<W> for (A, B, C) where A: WriteToJson<W>, B: WriteToJson<W>, C: WriteToJson<W>
 {	fn write_to_json(&self, out: &mut W) -> io::Result<()>
-	{	write!(out, "[")?;
+	{	out.write_all(b"[")?;
 		self.0.write_to_json(out)?;
-		write!(out, ",")?;
+		out.write_all(b",")?;
 		self.1.write_to_json(out)?;
-		write!(out, ",")?;
+		out.write_all(b",")?;
 		self.2.write_to_json(out)?;
-		write!(out, "]")
+		out.write_all(b"]")
+	}
+
+	fn write_to_sink(&self, out: &mut W) -> io::Result<()> where W: JsonSink
+	{	out.begin_array()?;
+		self.0.write_to_sink(out)?;
+		self.1.write_to_sink(out)?;
+		self.2.write_to_sink(out)?;
+		out.end_array()
 	}
 }
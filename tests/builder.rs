@@ -1,6 +1,6 @@
-//! ReaderBuilder and the configurable parsing limits (depth_limit, value_size_limit).
+//! ReaderBuilder and the configurable parsing options (depth_limit, value_size_limit, number_length_limit, input encoding).
 
-use nop_json::{Reader, ReaderBuilder, Value, TryFromJson, ValidateJson, DebugToJson};
+use nop_json::{Reader, ReaderBuilder, InputEncoding, Utf8Policy, Value, Decimal, TryFromJson, ValidateJson, DebugToJson};
 
 fn nested_array(depth: usize) -> String
 {	let mut s = String::with_capacity(depth * 2 + 1);
@@ -107,6 +107,45 @@ fn value_size_limit_on_blob()
 	assert!(err.to_string().contains("too large"), "{err}");
 }
 
+#[test]
+fn value_size_limit_on_encoded_blob()
+{	// more than 16 decoded bytes are not allowed
+	let json = format!("\"{}\"", "AAAA".repeat(6));
+	let err = ReaderBuilder::new().value_size_limit(16).build(json.bytes()).read_blob_base64().unwrap_err();
+	assert_eq!(err.to_string(), "$: Invalid JSON input: string or blob value is too large");
+	let json = format!("\"{}\"", "00".repeat(17));
+	assert!(ReaderBuilder::new().value_size_limit(16).build(json.bytes()).read_blob_hex().is_err());
+	let json = format!("\"{}\"", "00".repeat(16));
+	assert_eq!(ReaderBuilder::new().value_size_limit(16).build(json.bytes()).read_blob_hex().unwrap(), vec![0; 16]);
+
+	// piping is not limited
+	let json = format!("\"{}\"", "AAAA".repeat(100));
+	let mut data = Vec::new();
+	ReaderBuilder::new().value_size_limit(16).build(json.bytes()).pipe_blob_base64(&mut data).unwrap();
+	assert_eq!(data.len(), 300);
+}
+
+#[test]
+fn number_length_limit()
+{	// the default limit
+	let json = "7".repeat(1024);
+	assert_eq!(Reader::new(json.bytes()).read::<Value>().unwrap().to_string(), json);
+	let json = "7".repeat(1025);
+	let err = Reader::new(json.bytes()).read::<Value>().unwrap_err();
+	assert_eq!(err.to_string(), "$: Invalid JSON input: Number is too long");
+	assert!(Reader::new(format!("0.{}", json).bytes()).read::<Value>().is_err());
+	assert!(Reader::new(json.bytes()).read::<String>().is_err());
+	assert!(Reader::new(json.bytes()).read::<()>().is_err());
+	assert!(Reader::new(json.bytes()).read::<Decimal<2>>().is_err());
+	// floats take only the digits they need, so they're not limited
+	assert_eq!(Reader::new(json.bytes()).read::<f64>().unwrap(), json.parse::<f64>().unwrap());
+	// custom limit
+	let json = "[12345, 123456]";
+	let err = ReaderBuilder::new().number_length_limit(5).build(json.bytes()).read::<Vec<Value>>().unwrap_err();
+	assert_eq!(err.to_string(), "$[1]: Invalid JSON input: Number is too long");
+	assert_eq!(ReaderBuilder::new().number_length_limit(6).build(json.bytes()).read::<Value>().unwrap().to_string(), "[12345,123456]");
+}
+
 #[test]
 fn default_limits_allow_normal_input()
 {	// sanity: a derived struct with reasonable nesting parses fine with defaults
@@ -118,3 +157,66 @@ fn default_limits_allow_normal_input()
 	let o: Outer = Reader::new(r#"{"items": [{"v": 1}, {"v": 2}], "name": "ok"}"#.bytes()).read().unwrap();
 	assert_eq!(o, Outer {items: vec![Inner {v: 1}, Inner {v: 2}], name: "ok".to_string()});
 }
+
+#[test]
+fn input_encodings()
+{	let text = " {\"a\u{e9}\": [\"\u{20AC}\u{1F600}\", 1.5, true]} ";
+	let expected = Value::Object(vec![("a\u{e9}".to_string(), Value::Array(vec![Value::String("\u{20AC}\u{1F600}".to_string()), Value::Number(15, -1, false), Value::Bool(true)]))].into_iter().collect());
+	let utf16le: Vec<u8> = text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+	let utf16be: Vec<u8> = text.encode_utf16().flat_map(|c| c.to_be_bytes()).collect();
+	let utf32le: Vec<u8> = text.chars().flat_map(|c| (c as u32).to_le_bytes()).collect();
+	let utf32be: Vec<u8> = text.chars().flat_map(|c| (c as u32).to_be_bytes()).collect();
+	let boms: [&[u8]; 5] = [b"\xEF\xBB\xBF", b"\xFF\xFE", b"\xFE\xFF", b"\xFF\xFE\0\0", b"\0\0\xFE\xFF"];
+	let inputs = [(InputEncoding::Utf8, text.as_bytes().to_vec()), (InputEncoding::Utf16Le, utf16le), (InputEncoding::Utf16Be, utf16be), (InputEncoding::Utf32Le, utf32le), (InputEncoding::Utf32Be, utf32be)];
+	for ((encoding, input), bom) in inputs.iter().zip(boms)
+	{	let with_bom: Vec<u8> = bom.iter().chain(input.iter()).copied().collect();
+		// told encoding, with and without byte order mark
+		let mut reader = ReaderBuilder::new().encoding(*encoding).build(input.iter().copied());
+		assert_eq!(reader.read::<Value>().unwrap(), expected);
+		let mut reader = ReaderBuilder::new().encoding(*encoding).skip_bom(true).build(with_bom.iter().copied());
+		assert_eq!(reader.read::<Value>().unwrap(), expected);
+		let mut reader = ReaderBuilder::new().encoding(*encoding).skip_bom(true).build(input.iter().copied());
+		assert_eq!(reader.read::<Value>().unwrap(), expected);
+		// detected
+		let mut reader = ReaderBuilder::new().encoding(InputEncoding::Auto).build(input.iter().copied());
+		assert_eq!(reader.read::<Value>().unwrap(), expected, "{:?}", encoding);
+		let mut reader = ReaderBuilder::new().encoding(InputEncoding::Auto).build(with_bom.iter().copied());
+		assert_eq!(reader.read::<Value>().unwrap(), expected, "{:?} with BOM", encoding);
+	}
+
+	// short inputs are detected too
+	for (input, expected) in [(&b"1"[..], 1), (b"1\0", 1), (b"\x001", 1), (b"2\0\0\0", 2), (b"\0\0\x002", 2), (b"\xEF\xBB\xBF3", 3), (b"\xFF\xFE4\0", 4)]
+	{	let mut reader = ReaderBuilder::new().encoding(InputEncoding::Auto).build(input.iter().copied());
+		assert_eq!(reader.read::<i32>().unwrap(), expected);
+	}
+
+	// UTF-8 byte order mark is not skipped by default
+	assert!(Reader::new(b"\xEF\xBB\xBF1".iter().copied()).read::<i32>().is_err());
+
+	// lone surrogates and incomplete code units are invalid UTF-8, that Utf8Policy handles
+	let input: Vec<u8> = [b'"' as u16, 0xD800, b'a' as u16, b'"' as u16].iter().flat_map(|c| c.to_le_bytes()).collect();
+	let mut reader = ReaderBuilder::new().encoding(InputEncoding::Utf16Le).build(input.iter().copied());
+	assert_eq!(reader.read::<String>().unwrap_err().to_string(), "$: Invalid UTF-8 string");
+	let mut reader = ReaderBuilder::new().encoding(InputEncoding::Utf16Le).invalid_utf8(Utf8Policy::Replace).build(input.iter().copied());
+	assert_eq!(reader.read::<String>().unwrap(), "\u{FFFD}a");
+	let mut reader = ReaderBuilder::new().encoding(InputEncoding::Utf16Le).invalid_utf8(Utf8Policy::Preserve).build(input.iter().copied());
+	assert_eq!(reader.read_blob().unwrap(), b"\xED\xA0\x80a");
+	let mut reader = ReaderBuilder::new().encoding(InputEncoding::Utf32Be).build(b"\0\0\0\"\0\x11\0\0\0\0\0\"".iter().copied());
+	assert_eq!(reader.read_blob().unwrap(), b"\xFF");
+}
+
+#[test]
+fn unwrap_after_detecting_encoding()
+{	// only the bytes needed to detect encoding or byte order mark are read ahead, and the reader uses them, so nothing is lost
+	for (encoding, skip_bom, input) in
+	[	(InputEncoding::Utf8, false, &b"1 2 3"[..]),
+		(InputEncoding::Utf8, true, b"1 2 3"),
+		(InputEncoding::Utf8, true, b"\xEF\xBB\xBF1 2 3"),
+		(InputEncoding::Auto, false, b"1 2 3"),
+		(InputEncoding::Auto, false, b"\xEF\xBB\xBF1 2 3"),
+	]
+	{	let mut reader = ReaderBuilder::new().encoding(encoding).skip_bom(skip_bom).build(input.iter().copied());
+		assert_eq!(reader.read::<i32>().unwrap(), 1);
+		assert_eq!(reader.unwrap().collect::<Vec<u8>>(), b"2 3", "{:?} {:?}", encoding, input);
+	}
+}
@@ -0,0 +1,126 @@
+//! The fixed-point `Decimal` type: reading, rounding modes, writing, and conversions with `Value`.
+
+use nop_json::{Reader, ReaderBuilder, Decimal, Rounding, Value, DebugToJson, WriteToJson};
+use std::convert::TryFrom;
+
+fn read<T: nop_json::TryFromJson>(json: &str) -> std::io::Result<T>
+{	Reader::new(json.bytes()).read()
+}
+
+fn read_rounded<T: nop_json::TryFromJson>(json: &str, rounding: Rounding) -> std::io::Result<T>
+{	ReaderBuilder::new().decimal_rounding(rounding).build(json.bytes()).read()
+}
+
+#[test]
+fn read_exact()
+{	assert_eq!(read::<Decimal<2>>("19.99").unwrap().mantissa(), 1999);
+	assert_eq!(read::<Decimal<2>>("-19.9").unwrap().mantissa(), -1990);
+	assert_eq!(read::<Decimal<2>>("7").unwrap().mantissa(), 700);
+	assert_eq!(read::<Decimal<2>>("1.5e2").unwrap().mantissa(), 15000);
+	assert_eq!(read::<Decimal<2>>("1999e-2").unwrap().mantissa(), 1999);
+	assert_eq!(read::<Decimal<2>>("0.100000").unwrap().mantissa(), 10);
+	assert_eq!(read::<Decimal<0>>("123").unwrap().mantissa(), 123);
+	assert_eq!(read::<Decimal<4>>("0.0001").unwrap().mantissa(), 1);
+	// never passes through f64
+	assert_eq!(read::<Decimal<18>>("0.100000000000000001").unwrap().mantissa(), 100000000000000001);
+	assert_eq!(read::<Decimal<0>>("170141183460469231731687303715884105727").unwrap().mantissa(), i128::MAX);
+	assert_eq!(read::<Decimal<0>>("-170141183460469231731687303715884105728").unwrap().mantissa(), i128::MIN);
+	// coercion like in integers
+	assert_eq!(read::<Decimal<2>>("\"12.34\"").unwrap().mantissa(), 1234);
+	assert_eq!(read::<Decimal<2>>("true").unwrap().mantissa(), 100);
+	assert_eq!(read::<Decimal<2>>("null").unwrap().mantissa(), 0);
+	assert_eq!(read::<Vec<Decimal<2>>>("[1.25, 2]").unwrap(), vec![Decimal::new(125), Decimal::new(200)]);
+}
+
+#[test]
+fn read_errors()
+{	assert_eq!(read::<Decimal<2>>("0.125").unwrap_err().to_string(), "$: Number has more fractional digits than decimal scale allows");
+	assert_eq!(read::<Decimal<0>>("170141183460469231731687303715884105728").unwrap_err().to_string(), "$: Number is too big for decimal");
+	assert_eq!(read::<Decimal<2>>("1e40").unwrap_err().to_string(), "$: Number is too big for decimal");
+	assert_eq!(read::<Decimal<2>>("\"cheap\"").unwrap_err().to_string(), "$: Value must be number, not string");
+	assert_eq!(read::<Decimal<2>>("[]").unwrap_err().to_string(), "$: Value must be number, not array");
+	// zeros don't need to fit
+	assert_eq!(read::<Decimal<2>>("0e300").unwrap().mantissa(), 0);
+	assert_eq!(read::<Decimal<2>>("1.2300000000000000000000000000000000000000000000").unwrap().mantissa(), 123);
+}
+
+#[test]
+fn rounding_modes()
+{	let cases =
+	[	//  input       toward zero  half away  half even
+		("1.234",      123,         123,       123),
+		("1.235",      123,         124,       124),
+		("1.245",      124,         125,       124),
+		("1.2450001",  124,         125,       125),
+		("1.239",      123,         124,       124),
+		("-1.235",     -123,        -124,      -124),
+		("-1.245",     -124,        -125,      -124),
+		("0.005",      0,           1,         0),
+		("0.0051",     0,           1,         1),
+		("0.0009",     0,           0,         0),
+		("0.00000001", 0,           0,         0),
+	];
+	for (input, toward_zero, half_away, half_even) in cases
+	{	assert_eq!(read_rounded::<Decimal<2>>(input, Rounding::TowardZero).unwrap().mantissa(), toward_zero, "input was {input}");
+		assert_eq!(read_rounded::<Decimal<2>>(input, Rounding::HalfAwayFromZero).unwrap().mantissa(), half_away, "input was {input}");
+		assert_eq!(read_rounded::<Decimal<2>>(input, Rounding::HalfEven).unwrap().mantissa(), half_even, "input was {input}");
+		assert!(read_rounded::<Decimal<2>>(input, Rounding::Error).is_err(), "input was {input}");
+	}
+	// number longer than reader buffer
+	let input = format!("1.{}5", "0".repeat(200));
+	assert_eq!(read_rounded::<Decimal<2>>(&input, Rounding::HalfAwayFromZero).unwrap().mantissa(), 100);
+	let input = format!("0.00{}", "9".repeat(200));
+	assert_eq!(read_rounded::<Decimal<2>>(&input, Rounding::HalfEven).unwrap().mantissa(), 1);
+}
+
+#[test]
+fn write()
+{	let cases = [(1999, "19.99"), (-1990, "-19.90"), (5, "0.05"), (-5, "-0.05"), (0, "0.00"), (700, "7.00")];
+	for (mantissa, expected) in cases
+	{	let d = Decimal::<2>::new(mantissa);
+		assert_eq!(d.to_json_string(), expected);
+		assert_eq!(d.to_string(), expected);
+		assert_eq!(format!("{:?}", d), expected);
+		let mut out = Vec::new();
+		d.write_to_json(&mut out).unwrap();
+		assert_eq!(out, expected.as_bytes());
+		assert_eq!(read::<Decimal<2>>(expected).unwrap(), d);
+		assert_eq!(expected.parse::<Decimal<2>>(), Ok(d));
+	}
+	assert_eq!(Decimal::<0>::new(-42).to_string(), "-42");
+	assert_eq!(Decimal::<2>::new(i128::MIN).to_string(), "-1701411834604692317316873037158841057.28");
+	// write_to_json() writes the digits without going through fmt
+	fn written<const SCALE: u32>(d: Decimal<SCALE>) -> String
+	{	let mut out = Vec::new();
+		d.write_to_json(&mut out).unwrap();
+		String::from_utf8(out).unwrap()
+	}
+	assert_eq!(written(Decimal::<0>::new(-42)), "-42");
+	assert_eq!(written(Decimal::<2>::new(i128::MIN)), "-1701411834604692317316873037158841057.28");
+	assert_eq!(written(Decimal::<45>::new(-3)), format!("-0.{}3", "0".repeat(44)));
+	assert_eq!(written(Decimal::<45>::new(-3)), Decimal::<45>::new(-3).to_string());
+	assert_eq!("1.234".parse::<Decimal<2>>().unwrap_err().to_string(), "$: Number has more fractional digits than decimal scale allows");
+	assert_eq!("abc".parse::<Decimal<2>>().unwrap_err().to_string(), "$: String \"abc\" is not a valid number");
+}
+
+#[test]
+fn value_conversions()
+{	let v = Value::try_from(Decimal::<2>::new(1990)).unwrap();
+	assert_eq!(v, Value::Number(199, -1, false));
+	assert_eq!(v, read::<Value>("19.90").unwrap());
+	let v = Value::try_from(Decimal::<2>::new(-1999)).unwrap();
+	assert_eq!(v, Value::Number(1999, -2, true));
+	assert_eq!(Value::try_from(Decimal::<2>::new(0)).unwrap(), Value::Number(0, 0, false));
+	let v = Value::try_from(Decimal::<2>::new(i128::MIN)).unwrap();
+	assert_eq!(v.to_string(), "-1701411834604692317316873037158841057.28");
+	assert_eq!(Decimal::<2>::try_from(v).unwrap(), Decimal::new(i128::MIN));
+
+	assert_eq!(Decimal::<2>::try_from(Value::Number(1999, -2, false)), Ok(Decimal::new(1999)));
+	assert_eq!(Decimal::<2>::try_from(Value::String("0.5".to_string())), Ok(Decimal::new(50)));
+	assert_eq!(Decimal::<2>::try_from(Value::Number(1, -3, false)).unwrap_err().to_string(), "$: Number has more fractional digits than decimal scale allows");
+	assert_eq!(Decimal::<2>::from_value(Value::Number(1, -3, false), Rounding::HalfAwayFromZero), Ok(Decimal::new(0)));
+	assert_eq!(Value::try_from(Decimal::<40000>::new(1)).unwrap_err().to_string(), "$: Decimal scale 40000 is too big");
+	assert_eq!(Value::try_from(vec![Decimal::<40000>::new(1)]).unwrap_err().to_string(), "$[0]: Decimal scale 40000 is too big");
+	assert_eq!(Decimal::<2>::from_value(Value::Number(5, -3, true), Rounding::HalfAwayFromZero), Ok(Decimal::new(-1)));
+	assert_eq!(Decimal::<2>::try_from(Value::Array(vec![])).unwrap_err().to_string(), "$: Value must be number, not array");
+}
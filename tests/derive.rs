@@ -1,7 +1,7 @@
 //! `#[derive(TryFromJson, ValidateJson, DebugToJson)]` for structs and enums:
 //! field renaming, exclusion, ignoring, enum variant selection, validation, nesting and round-trips.
 
-use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson};
+use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson, WriteToJson, Formatting};
 use std::io;
 
 fn read<T: TryFromJson>(json: &str) -> io::Result<T>
@@ -34,6 +34,44 @@ fn struct_field_rename()
 	assert_eq!(u.to_json_string(), r#"{"id":1,"all_posts":[10,20]}"#);
 }
 
+#[test]
+fn error_path_quotes_keys_that_are_not_identifiers()
+{	#[derive(PartialEq, Default, TryFromJson, ValidateJson, DebugToJson)]
+	struct User
+	{	#[json("first name")] first_name: String,
+		#[json(ids)] ids: Vec<Vec<i32>>,
+	}
+	assert_eq!(read::<User>(r#"{"first name": []}"#).unwrap_err().to_string(), r#"$["first name"]: Value must be string, not array"#);
+	assert_eq!(read::<User>(r#"{"ids": [[1, {}]]}"#).unwrap_err().to_string(), r#"$.ids[0][1]: Invalid JSON input: value must be number, not object"#);
+}
+
+#[test]
+fn struct_binary_fields()
+{	#[derive(PartialEq, Default, TryFromJson, ValidateJson, DebugToJson, WriteToJson)]
+	struct Message
+	{	#[json(base64)] body: Vec<u8>,
+		#[json(digest, hex)] sha: Vec<u8>,
+		#[json("hex")] hex: Vec<u8>,
+	}
+	let m = Message {body: b"\x00\x80\xFF\xFB".to_vec(), sha: b"\x0A\xBC".to_vec(), hex: vec![1]};
+	let json = r#"{"body":"AID/+w==","digest":"0abc","hex":[1]}"#;
+	// the text path, and the sink path
+	assert_eq!(m.to_json_string(), json);
+	let mut out = Vec::new();
+	m.write_to_json(&mut out).unwrap();
+	assert_eq!(out, json.as_bytes());
+	assert_eq!(m.to_json_string_with(&Formatting::pretty()).unwrap(), "{\n  \"body\": \"AID/+w==\",\n  \"digest\": \"0abc\",\n  \"hex\": [\n    1\n  ]\n}");
+	assert_eq!(read::<Message>(json).unwrap(), m);
+	// URL-safe alphabet, no padding, and uppercase hex are accepted
+	assert_eq!(read::<Message>(r#"{"body": "AID_-w", "digest": "0ABC", "hex": [1]}"#).unwrap(), m);
+	// null and missing fields give empty data
+	assert_eq!(read::<Message>(r#"{"body": null}"#).unwrap(), Message::default());
+	// errors have the path
+	assert_eq!(read::<Message>(r#"{"body": "@"}"#).unwrap_err().to_string(), "$.body: Invalid base64 string");
+	assert_eq!(read::<Message>(r#"{"digest": "abc"}"#).unwrap_err().to_string(), "$.digest: Invalid hex string");
+	assert_eq!(read::<Message>(r#"{"digest": [1]}"#).unwrap_err().to_string(), "$.digest: Value must be string, not array");
+}
+
 #[test]
 fn struct_excluded_field_uses_default()
 {	#[derive(PartialEq, Default, TryFromJson, ValidateJson, DebugToJson)]
@@ -81,6 +119,59 @@ fn struct_missing_fields_use_default()
 	assert_eq!(c, Config {a: 0, b: 5, c: 0});
 }
 
+/// Doesn't implement `Default`.
+#[derive(PartialEq, Debug, TryFromJson, ValidateJson)]
+enum Mode
+{	#[json(fast)] Fast(i32),
+	#[json(slow)] Slow(i32),
+}
+
+fn default_port() -> u16
+{	8080
+}
+
+#[test]
+fn struct_field_default_path_and_literal()
+{	#[derive(PartialEq, Debug, TryFromJson, ValidateJson)]
+	struct Config
+	{	#[json(default = "default_port")] port: u16,
+		#[json(default = 3)] retries: u32,
+		#[json(default = 0.5)] ratio: f64,
+		#[json(default = true)] verbose: bool,
+		#[json(host, default = "String::from(\"localhost\")")] host_name: String,
+		#[json(default = "Mode::Slow(1)")] mode: Mode,
+		#[json("", default = 'x')] transient: char,
+	}
+	// missing fields
+	let c: Config = read("{}").unwrap();
+	assert_eq!(c, Config {port: 8080, retries: 3, ratio: 0.5, verbose: true, host_name: "localhost".to_string(), mode: Mode::Slow(1), transient: 'x'});
+	// fields in the input override the defaults
+	let c: Config = read(r#"{"port": 1, "retries": 0, "verbose": false, "host": "a", "mode": {"fast": 2}}"#).unwrap();
+	assert_eq!(c, Config {port: 1, retries: 0, ratio: 0.5, verbose: false, host_name: "a".to_string(), mode: Mode::Fast(2), transient: 'x'});
+}
+
+#[test]
+fn struct_container_default()
+{	#[derive(PartialEq, Debug, TryFromJson, ValidateJson)]
+	#[json(default)]
+	struct Config
+	{	port: u16,
+		mode: Mode,
+		#[json(default = 7)] level: u8,
+		#[json("")] comment: String,
+	}
+	impl Default for Config
+	{	fn default() -> Self
+		{	Config {port: 80, mode: Mode::Fast(5), level: 1, comment: "none".to_string()}
+		}
+	}
+	// missing fields are taken from Config::default(), and a field default takes precedence
+	let c: Config = read(r#"{"port": 443}"#).unwrap();
+	assert_eq!(c, Config {port: 443, mode: Mode::Fast(5), level: 7, comment: "none".to_string()});
+	let c: Config = read(r#"{"mode": {"slow": 1}, "level": 2}"#).unwrap();
+	assert_eq!(c, Config {port: 80, mode: Mode::Slow(1), level: 2, comment: "none".to_string()});
+}
+
 #[test]
 fn enum_typed_discriminator()
 {	#[derive(PartialEq, Default, TryFromJson, ValidateJson, DebugToJson)]
@@ -213,3 +304,55 @@ fn numeric_string_coercion_in_struct()
 	let it: Item = read(r#"{"id": "42", "name": "widget"}"#).unwrap();
 	assert_eq!(it, Item {id: 42, name: "widget".to_string()});
 }
+
+#[test]
+fn rename_all_case_styles()
+{	macro_rules! check
+	{	($style:literal, $account:literal, $logged_in:literal, $ping:literal) =>
+		{{	#[derive(PartialEq, TryFromJson, ValidateJson, DebugToJson, WriteToJson)]
+			#[json(rename_all = $style)]
+			struct Account {user_id: u32, http_status: u16, #[json(id)] account_id: u32}
+
+			#[derive(PartialEq, TryFromJson, ValidateJson, DebugToJson, WriteToJson)]
+			#[json(kind, rename_all = $style)]
+			enum Event
+			{	#[json(at)] UserLoggedIn(u32),
+				#[json(var = "out", at)] LoggedOut(u32),
+				Ping,
+			}
+
+			// writing: text path, io::Write path, sink path
+			let account = Account {user_id: 1, http_status: 200, account_id: 3};
+			assert_eq!(account.to_json_string(), $account);
+			let mut out = Vec::new();
+			account.write_to_json(&mut out).unwrap();
+			assert_eq!(out, $account.as_bytes());
+			assert_eq!(account.to_json_string_with(&Formatting::compact()).unwrap(), $account);
+			let events = [Event::UserLoggedIn(5), Event::LoggedOut(6), Event::Ping];
+			let json = [$logged_in, r#"{"kind":"out","at":6}"#, $ping];
+			for (event, json) in events.iter().zip(json)
+			{	assert_eq!(event.to_json_string(), json);
+				let mut out = Vec::new();
+				event.write_to_json(&mut out).unwrap();
+				assert_eq!(out, json.as_bytes());
+				assert_eq!(event.to_json_string_with(&Formatting::compact()).unwrap(), json);
+				// reading
+				assert_eq!(&read::<Event>(json).unwrap(), event);
+			}
+			// reading
+			assert_eq!(read::<Account>($account).unwrap(), account);
+			// Rust names are not accepted, unless they are the same
+			if $style != "lowercase"
+			{	assert!(read::<Account>(r#"{"user_id": 1, "http_status": 200}"#).is_err());
+			}
+			if $style != "PascalCase"
+			{	assert!(read::<Event>(r#"{"kind": "UserLoggedIn", "at": 5}"#).is_err());
+			}
+		}};
+	}
+	check!("camelCase", r#"{"userId":1,"httpStatus":200,"id":3}"#, r#"{"kind":"userLoggedIn","at":5}"#, r#"{"kind":"ping"}"#);
+	check!("PascalCase", r#"{"UserId":1,"HttpStatus":200,"id":3}"#, r#"{"kind":"UserLoggedIn","at":5}"#, r#"{"kind":"Ping"}"#);
+	check!("kebab-case", r#"{"user-id":1,"http-status":200,"id":3}"#, r#"{"kind":"user-logged-in","at":5}"#, r#"{"kind":"ping"}"#);
+	check!("SCREAMING_SNAKE_CASE", r#"{"USER_ID":1,"HTTP_STATUS":200,"id":3}"#, r#"{"kind":"USER_LOGGED_IN","at":5}"#, r#"{"kind":"PING"}"#);
+	check!("lowercase", r#"{"user_id":1,"http_status":200,"id":3}"#, r#"{"kind":"userloggedin","at":5}"#, r#"{"kind":"ping"}"#);
+}
@@ -72,6 +72,18 @@ fn skip_value_by_reading_unit()
 	assert!(reader.read::<i32>().is_err());
 }
 
+#[test]
+fn skip_objects_by_reading_unit()
+{	let mut reader = Reader::new(r#" {"a": 1} {"b": {"c": [{}, {"d": null}]}, "e": "f"} "next" {"g" 1} "#.bytes());
+	let _: () = reader.read().unwrap();
+	let _: () = reader.read().unwrap();
+	assert_eq!(reader.read::<String>().unwrap(), "next");
+	assert!(reader.read::<()>().is_err());
+	for json in [r#"{"a": 1,}"#, r#"{"a": 1 "b": 2}"#, r#"{"a": 1]"#]
+	{	assert!(Reader::new(json.bytes()).read::<()>().is_err(), "{}", json);
+	}
+}
+
 #[test]
 fn malformed_json_errors()
 {	assert!(read::<Vec<i32>>("[1, 2,").is_err());        // unterminated array
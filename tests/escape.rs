@@ -1,7 +1,8 @@
-//! The `escape` / `escape_bytes` helpers.
+//! The `escape` / `escape_bytes` helpers, and escaping profiles.
 
-use nop_json::{escape, escape_bytes};
+use nop_json::{escape, escape_bytes, escape_with, escape_bytes_with, write_blob, Escaping, Reader, Formatting, DebugToJson, JsonWriter};
 use std::borrow::Cow;
+use std::io::Write;
 
 #[test]
 fn no_special_chars_borrows()
@@ -62,3 +63,82 @@ fn escape_bytes_borrows_when_clean()
 		Cow::Owned(_) => panic!("high bytes alone should not trigger allocation"),
 	}
 }
+
+#[test]
+fn escaping_profiles()
+{	let s = "<a href='x'>&amp;</a> \u{2028}\u{2029} é€😀 \"\\\n";
+	assert_eq!(escape_with(s, Escaping::Minimal), escape(s));
+	assert_eq!(escape_with(s, Escaping::Html), "\\u003Ca href=\\u0027x\\u0027\\u003E\\u0026amp;\\u003C/a\\u003E \u{2028}\u{2029} é€😀 \\\"\\\\\\n");
+	assert_eq!(escape_with(s, Escaping::JavaScript), "\\u003Ca href=\\u0027x\\u0027\\u003E\\u0026amp;\\u003C/a\\u003E \\u2028\\u2029 é€😀 \\\"\\\\\\n");
+	assert_eq!(escape_with(s, Escaping::Ascii), "<a href='x'>&amp;</a> \\u2028\\u2029 \\u00E9\\u20AC\\uD83D\\uDE00 \\\"\\\\\\n");
+	// other characters that start with the same byte as U+2028
+	assert_eq!(escape_with("\u{2020}\u{20AC}", Escaping::JavaScript), "\u{2020}\u{20AC}");
+	// nothing to escape: no allocation
+	assert!(matches!(escape_with("plain é", Escaping::Html), Cow::Borrowed(_)));
+	// invalid UTF-8 is left as is
+	assert_eq!(escape_bytes_with(b"\xFF<\xE2\x80", Escaping::JavaScript), &b"\xFF\\u003C\xE2\x80"[..]);
+	assert_eq!(escape_bytes_with(b"\xFF\xC3\xA9", Escaping::Ascii), &b"\xFF\\u00E9"[..]);
+	// escaped strings read back to the original
+	for escaping in [Escaping::Minimal, Escaping::Ascii, Escaping::Html, Escaping::JavaScript]
+	{	let json = format!("\"{}\"", escape_with(s, escaping));
+		assert_eq!(Reader::new(json.bytes()).read::<String>().unwrap(), s);
+		assert_eq!(vec![s.to_string()].to_json_string_with(&Formatting::compact().escaping(escaping)).unwrap(), format!("[{}]", json));
+	}
+	assert_eq!(escape_bytes(b"\x01"), &b"\\u0001"[..]);
+}
+
+#[test]
+fn escaping_blobs_in_format_writer()
+{	let mut json = Vec::new();
+	write_blob(&mut json, b"\x80<\xFF\"\xC3\xA9").unwrap();
+	for (escaping, expected) in
+	[	(Escaping::Ascii, &b"\"\x80<\xFF\\\"\\u00E9\""[..]),
+		(Escaping::Html, &b"\"\x80\\u003C\xFF\\\"\xC3\xA9\""[..]),
+		(Escaping::JavaScript, &b"\"\x80\\u003C\xFF\\\"\xC3\xA9\""[..]),
+	]
+	{	let mut writer = Formatting::compact().escaping(escaping).build(Vec::new());
+		writer.write_all(&json).unwrap();
+		let out = writer.finish().unwrap();
+		assert_eq!(out, expected, "{:?}", escaping);
+		assert_eq!(Reader::new(out.into_iter()).read_blob().unwrap(), b"\x80<\xFF\"\xC3\xA9");
+	}
+	// escape sequences in the input are kept
+	let mut writer = Formatting::compact().escaping(Escaping::Ascii).build(Vec::new());
+	writer.write_all(b"[\"\\u00e9\\/\\n\\u2028\xFF\"]").unwrap();
+	assert_eq!(writer.finish().unwrap(), b"[\"\\u00e9\\/\\n\\u2028\xFF\"]");
+	let mut writer = Formatting::compact().escaping(Escaping::Html).build(Vec::new());
+	assert!(writer.write_all(br#""\x""#).is_err());
+}
+
+#[test]
+fn escaping_while_streaming()
+{	let s = "<b>é€😀\u{2028}\"\\\n";
+	let json = format!("{{\"{}\":[\"{}\"]}}", escape(s), escape(s));
+	for (escaping, sort_keys) in [(Escaping::Ascii, false), (Escaping::JavaScript, false), (Escaping::Html, true), (Escaping::Minimal, true)]
+	{	let expected = format!("{{\"{}\":[\"{}\"]}}", escape_with(s, escaping), escape_with(s, escaping));
+		// characters split between writes
+		let mut writer = Formatting::compact().escaping(escaping).sort_keys(sort_keys).build(Vec::new());
+		for c in json.bytes()
+		{	writer.write_all(&[c]).unwrap();
+		}
+		assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), expected, "{:?}", escaping);
+		// keys and values written with JsonWriter
+		let mut writer = JsonWriter::with_formatting(Vec::new(), Formatting::compact().escaping(escaping).sort_keys(sort_keys));
+		writer.begin_object().unwrap();
+		writer.key(s).unwrap();
+		writer.value(&vec![s.to_string()]).unwrap();
+		writer.end_object().unwrap();
+		assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), expected, "{:?}", escaping);
+	}
+	// truncated UTF-8 sequence is left as is
+	let mut writer = Formatting::compact().escaping(Escaping::Ascii).build(Vec::new());
+	writer.write_all(b"\"\xE2\x80\"").unwrap();
+	assert_eq!(writer.finish().unwrap(), b"\"\xE2\x80\"");
+	// escape sequences are checked
+	for json in [&br#""\u12G4""#[..], br#"["\x"]"#, br#""\u12""#]
+	{	let mut writer = Formatting::compact().escaping(Escaping::Ascii).build(Vec::new());
+		assert!(writer.write_all(json).and_then(|_| writer.finish().map(|_| ())).is_err());
+		let mut writer = Formatting::compact().build(Vec::new());
+		assert!(writer.write_all(json).and_then(|_| writer.finish().map(|_| ())).is_err());
+	}
+}
@@ -0,0 +1,217 @@
+//! Output formatting: pretty printing through `FormatWriter`, `to_json_string_with()` and `{:#?}`, and canonical JSON.
+
+use nop_json::{json, Reader, Value, DebugToJson, WriteToJson, JsonSink, Formatting, to_canonical_json};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::fmt;
+
+fn format(json: &str, formatting: Formatting) -> String
+{	let mut writer = formatting.build(Vec::new());
+	writer.write_all(json.as_bytes()).unwrap();
+	String::from_utf8(writer.finish().unwrap()).unwrap()
+}
+
+#[test]
+fn pretty_printing()
+{	let json = r#"{"a":[1,{"b":"x,y:\"[{"}],"c":{},"d":[],"e":[[]]}"#;
+	assert_eq!
+	(	format(json, Formatting::pretty()),
+		"{\n  \"a\": [\n    1,\n    {\n      \"b\": \"x,y:\\\"[{\"\n    }\n  ],\n  \"c\": {},\n  \"d\": [],\n  \"e\": [\n    []\n  ]\n}"
+	);
+	assert_eq!(format(json, Formatting::compact()), json);
+	assert_eq!(format("[1,2]", Formatting::pretty().indent_tabs(1).newline("\r\n")), "[\r\n\t1,\r\n\t2\r\n]");
+	assert_eq!(format(r#"{"a":1}"#, Formatting::pretty().space_after_colon(false).indent_spaces(0)), "{\n\"a\":1\n}");
+	// whitespace in input is ignored, and values in sequence go on separate lines
+	assert_eq!(format(" { \"a\" : [ 1 , 2 ] } 3 \"x\" ", Formatting::compact()), "{\"a\":[1,2]}\n3\n\"x\"");
+	// pretty output reads back to the same value
+	let v: Value = Reader::new(json.bytes()).read().unwrap();
+	let pretty = format(json, Formatting::pretty());
+	assert_eq!(Reader::new(pretty.bytes()).read::<Value>().unwrap(), v);
+}
+
+#[test]
+fn sorted_keys()
+{	let json = r#"{"b":{"z":1,"y":[{"k2":2,"k1":1}]},"a\u0041":null,"a":true}"#;
+	assert_eq!(format(json, Formatting::compact().sort_keys(true)), r#"{"a":true,"a\u0041":null,"b":{"y":[{"k1":1,"k2":2}],"z":1}}"#);
+	assert_eq!
+	(	format(json, Formatting::pretty().sort_keys(true)),
+		"{\n  \"a\": true,\n  \"a\\u0041\": null,\n  \"b\": {\n    \"y\": [\n      {\n        \"k1\": 1,\n        \"k2\": 2\n      }\n    ],\n    \"z\": 1\n  }\n}"
+	);
+}
+
+#[test]
+fn formatting_serialized_values()
+{	let mut map = HashMap::new();
+	map.insert("y".to_string(), vec![(1, "a".to_string())]);
+	map.insert("x".to_string(), vec![]);
+	let formatting = Formatting::pretty().sort_keys(true);
+	let expected = "{\n  \"x\": [],\n  \"y\": [\n    [\n      1,\n      \"a\"\n    ]\n  ]\n}";
+	assert_eq!(map.to_json_string_with(&formatting).unwrap(), expected);
+	let mut writer = formatting.build(Vec::new());
+	map.write_to_json(&mut writer).unwrap();
+	assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), expected);
+
+	let v = json!({"list": [1, 2]});
+	assert_eq!(format!("{:?}", v), r#"{"list":[1,2]}"#);
+	assert_eq!(format!("{:#?}", v), "{\n  \"list\": [\n    1,\n    2\n  ]\n}");
+	let mut tree = BTreeMap::new();
+	tree.insert("v".to_string(), v);
+	assert_eq!(tree.to_json_string_with(&Formatting::compact().space_after_colon(true)).unwrap(), r#"{"v": {"list": [1,2]}}"#);
+}
+
+#[test]
+fn formatting_derived_types()
+{	#[derive(DebugToJson, WriteToJson)]
+	struct Point {x: i32, y: i32}
+
+	#[derive(DebugToJson, WriteToJson)]
+	#[json(type)]
+	enum Shape
+	{	#[json(poly(points, "line color"))] Poly(Vec<Point>, Option<String>),
+		#[json(empty)] Empty,
+	}
+
+	let shape = Shape::Poly(vec![Point {x: 1, y: -2}], Some("red".to_string()));
+	assert_eq!(format!("{:?}", shape), r#"{"type":"poly","points":[{"x":1,"y":-2}],"line color":"red"}"#);
+	let expected = "{\n  \"type\": \"poly\",\n  \"points\": [\n    {\n      \"x\": 1,\n      \"y\": -2\n    }\n  ],\n  \"line color\": \"red\"\n}";
+	assert_eq!(format!("{:#?}", shape), expected);
+	// through the sink and as text, the result is the same
+	let mut writer = Formatting::pretty().build(Vec::new());
+	shape.write_to_sink(&mut writer).unwrap();
+	assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), expected);
+	let mut writer = Formatting::pretty().build(Vec::new());
+	shape.write_to_json(&mut writer).unwrap();
+	assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), expected);
+	assert_eq!(vec![Shape::Empty, Shape::Empty].to_json_string_with(&Formatting::pretty()).unwrap(), "[\n  {\n    \"type\": \"empty\"\n  },\n  {\n    \"type\": \"empty\"\n  }\n]");
+	assert_eq!(shape.to_json_string_with(&Formatting::compact().sort_keys(true).space_after_colon(true)).unwrap(), r#"{"line color": "red","points": [{"x": 1,"y": -2}],"type": "poly"}"#);
+}
+
+#[test]
+fn formatting_manual_impls()
+{	/// Produces JSON text, so it's formatted through `begin_raw()` and `end_raw()`.
+	struct Manual(&'static str);
+
+	impl DebugToJson for Manual
+	{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+		{	out.write_str(self.0)
+		}
+	}
+
+	impl fmt::Debug for Manual
+	{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+		{	self.debug_fmt(out)
+		}
+	}
+
+	let v = vec![(Manual("{\"b\":[1],\"a\":2}"), 5)];
+	assert_eq!(v.to_json_string_with(&Formatting::pretty().sort_keys(true)).unwrap(), "[\n  [\n    {\n      \"a\": 2,\n      \"b\": [\n        1\n      ]\n    },\n    5\n  ]\n]");
+	assert_eq!(format!("{:#?}", Manual("[1,2]")), "[\n  1,\n  2\n]");
+	// invalid JSON gives error, not unformatted output
+	for json in ["", "[1", "1 2", "[1]]", "{} {}", "01"]
+	{	assert_eq!(vec![Manual(json)].to_json_string_with(&Formatting::pretty()).unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", json);
+	}
+	assert!(fmt::Write::write_fmt(&mut String::new(), format_args!("{:#?}", Manual("[1,"))).is_err());
+
+	// sink calls in wrong order
+	let mut writer = Formatting::compact().build(Vec::new());
+	writer.begin_object().unwrap();
+	assert!(writer.null().is_err());
+	assert!(writer.end_array().is_err());
+	writer.key("k").unwrap();
+	assert!(writer.key("k").is_err());
+	assert!(writer.number(b"1a", 0, false).is_err());
+	writer.number(b"15", -1, true).unwrap();
+	writer.end_object().unwrap();
+	writer.number(b"", 0, false).unwrap();
+	assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), "{\"k\":-1.5}\n0");
+}
+
+#[test]
+fn invalid_input()
+{	for json in ["[1", "{\"a\":1", "[1,]", "]", "[}", "\"abc", "[1 2]", "[1,,2]", "[,1]", "{\"a\" 1}", "{\"a\":1 \"b\":2}", "{1:2}", "{\"a\"}", "[\"a\":1]", "[tru]", "[1x]", "[1]]", "@", "[01]", "[1.]", "[.5]", "[-]", "[1e]", "[1e+]", "[+1]", "[1.5.2]"]
+	{	let mut writer = Formatting::pretty().build(Vec::new());
+		let result = writer.write_all(json.as_bytes()).and_then(|_| writer.finish().map(|_| ()));
+		assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", json);
+	}
+	for json in ["[1 2]", "[true false]"]
+	{	let mut writer = Formatting::compact().build(Vec::new());
+		assert!(writer.write_all(json.as_bytes()).is_err(), "{}", json);
+	}
+}
+
+#[test]
+fn canonical_numbers()
+{	let cases =
+	[	("0", "0"), ("-0", "0"), ("0.0", "0"), ("1", "1"), ("-1.50", "-1.5"), ("1e2", "100"), ("100E-2", "1"),
+		("123456789012345678901", "123456789012345680000"), ("1e21", "1e+21"), ("1.5e21", "1.5e+21"), ("1e20", "100000000000000000000"),
+		("0.000001", "0.000001"), ("0.0000001", "1e-7"), ("-1.25e-10", "-1.25e-10"), ("0.1", "0.1"), ("333333333.33333329", "333333333.3333333"),
+		("4.50", "4.5"), ("2e-3", "0.002"), ("9007199254740993", "9007199254740992"), ("1.7976931348623157e308", "1.7976931348623157e+308"),
+		("5e-324", "5e-324"),
+	];
+	for (input, expected) in cases
+	{	assert_eq!(format(input, Formatting::canonical()), expected, "input was {}", input);
+	}
+	for input in ["1e400", "-1e309"]
+	{	let mut writer = Formatting::canonical().build(Vec::new());
+		assert!(writer.write_all(input.as_bytes()).and_then(|_| writer.finish().map(|_| ())).is_err(), "input was {}", input);
+	}
+}
+
+#[test]
+fn canonical_json()
+{	// the example from RFC 8785, section 3.2.2
+	let input = r#"{
+		"numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+		"string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+		"literals": [null, true, false]
+	}"#;
+	let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+	assert_eq!(format(input, Formatting::canonical()), expected);
+	assert_eq!(Reader::new(input.bytes()).read_canonical().unwrap(), expected);
+	let v: Value = Reader::new(input.bytes()).read().unwrap();
+	assert_eq!(to_canonical_json(&v).unwrap(), expected);
+
+	// keys are sorted by UTF-16 code units
+	let input = r#"{"€":"Euro Sign","\r":"Carriage Return","דּ":"Hebrew Letter Dalet With Dagesh","1":"One","😀":"Emoji: Grinning Face","\u0080":"Control","ö":"Latin Small Letter O With Diaeresis"}"#;
+	let canonical = Reader::new(input.bytes()).read_canonical().unwrap();
+	let order: Vec<usize> = ["Carriage Return", "One", "Control", "Latin Small", "Euro Sign", "Emoji", "Hebrew"].iter().map(|name| canonical.find(name).unwrap()).collect();
+	assert!(order.windows(2).all(|w| w[0] < w[1]), "{}", canonical);
+
+	// maps and other serializable types
+	let mut map = BTreeMap::new();
+	map.insert("b".to_string(), vec![0.1f64, 1e21]);
+	map.insert("a".to_string(), vec![]);
+	assert_eq!(to_canonical_json(&map).unwrap(), r#"{"a":[],"b":[0.1,1e+21]}"#);
+	// lone surrogate
+	assert!(Reader::new(r#" "\ud800" "#.bytes()).read_canonical().is_err());
+}
+
+#[test]
+fn canonical_json_while_streaming()
+{	#[derive(DebugToJson)]
+	struct Item {name: String, price: f64, tags: HashMap<String, u128>}
+
+	let mut tags = HashMap::new();
+	tags.insert("z".to_string(), u128::MAX);
+	tags.insert("\u{e9}".to_string(), 2);
+	tags.insert("a".to_string(), 1);
+	let item = Item {name: "\u{1F600}\t".to_string(), price: 1e-7, tags};
+	assert_eq!(to_canonical_json(&item).unwrap(), "{\"name\":\"\u{1F600}\\t\",\"price\":1e-7,\"tags\":{\"a\":1,\"z\":3.402823669209385e+38,\"\u{e9}\":2}}");
+
+	// errors have path of the value
+	let mut reader = Reader::new(r#"[[1], [2, 1e400]]"#.bytes());
+	assert_eq!(reader.read_canonical().unwrap_err().to_string(), "$[1][1]: Number is out of range for canonical JSON");
+	let mut reader = Reader::new(r#"{"a": [1, }"#.bytes());
+	assert!(reader.read_canonical().is_err());
+	// values in sequence
+	let mut reader = Reader::new(r#" [1.0, "\u0041\ud83d\ude00"] {"b": {}, "a": -0} "#.bytes());
+	assert_eq!(reader.read_canonical().unwrap(), "[1,\"A\u{1F600}\"]");
+	assert_eq!(reader.read_canonical().unwrap(), r#"{"a":0,"b":{}}"#);
+
+	// escape sequences in text are decoded, and lone surrogates are rejected
+	assert_eq!(format(r#"["\u0041\ud83d\ude00\/\u001F"]"#, Formatting::canonical()), "[\"A\u{1F600}/\\u001f\"]");
+	for json in [r#""\ud800""#, r#""\ude00""#, r#""\ud800\n""#, r#""\ud800\u0041""#, r#""\ud800x""#, r#""\u12""#]
+	{	let mut writer = Formatting::canonical().build(Vec::new());
+		assert!(writer.write_all(json.as_bytes()).and_then(|_| writer.finish().map(|_| ())).is_err(), "{}", json);
+	}
+}
@@ -0,0 +1,83 @@
+//! The `json!` macro: literals, nesting, interpolation of Rust expressions and computed keys.
+
+use nop_json::{json, Reader, Value};
+
+fn read(json: &str) -> Value
+{	Reader::new(json.bytes()).read().unwrap()
+}
+
+#[test]
+fn literals()
+{	assert_eq!(json!(null), Value::Null);
+	assert_eq!(json!(true), Value::Bool(true));
+	assert_eq!(json!(false), Value::Bool(false));
+	assert_eq!(json!(0), Value::Number(0, 0, false));
+	assert_eq!(json!(-12), Value::Number(12, 0, true));
+	assert_eq!(json!(1.5), Value::Number(15, -1, false));
+	assert_eq!(json!(-1.5e-20), read("-1.5e-20"));
+	assert_eq!(json!("hello"), Value::String("hello".to_string()));
+	assert_eq!(json!([]), Value::Array(vec![]));
+	assert_eq!(json!({}), Value::Object(Default::default()));
+	assert_eq!(json!(u128::MAX), read("340282366920938463463374607431768211455"));
+}
+
+#[test]
+fn nested()
+{	let v = json!
+	({	"a": 1,
+		"b": [true, null, -2.25, "s", [], {}, [1, [2]], {"x": null}],
+		"c": {"d": {"e": false}, "f": -0.5},
+		"": "",
+	});
+	assert_eq!(v, read(r#" {"a": 1, "b": [true, null, -2.25, "s", [], {}, [1, [2]], {"x": null}], "c": {"d": {"e": false}, "f": -0.5}, "": ""} "#));
+	assert_eq!(json!([null, false, {"a": [true]},]), read(r#" [null, false, {"a": [true]}] "#));
+}
+
+#[test]
+fn interpolation()
+{	let x = 10;
+	let name = String::from("John");
+	let key = "computed";
+	let inner = json!({"deep": [x]});
+	let list = vec![1u8, 2, 3];
+	let v = json!
+	({	"x": x,
+		"sum": x + 5,
+		"neg": -x,
+		"name": name.clone(),
+		"is_long": name.len() > 3,
+		(key): inner.clone(),
+		(format!("{}_{}", key, 2)): list,
+		"float": x as f64 / 4.0,
+		"nan": f64::NAN,
+		"items": [x, -x, name, inner],
+	});
+	assert_eq!(v["x"], Value::Number(10, 0, false));
+	assert_eq!(v["sum"], Value::Number(15, 0, false));
+	assert_eq!(v["neg"], Value::Number(10, 0, true));
+	assert_eq!(v["name"], Value::String("John".to_string()));
+	assert_eq!(v["is_long"], Value::Bool(true));
+	assert_eq!(v["computed"]["deep"][0], Value::Number(10, 0, false));
+	assert_eq!(v["computed_2"], read("[1, 2, 3]"));
+	assert_eq!(v["float"], read("2.5"));
+	assert_eq!(v["nan"], Value::String("NaN".to_string()));
+	assert_eq!(v["items"], read(r#" [10, -10, "John", {"deep": [10]}] "#));
+}
+
+#[test]
+fn long_literals()
+{	let v = json!
+	([	0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+		0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+		0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+	]);
+	assert_eq!(v.len(), 120);
+	let v = json!
+	({	"a0": 0, "a1": 1, "a2": 2, "a3": 3, "a4": 4, "a5": 5, "a6": 6, "a7": 7, "a8": 8, "a9": 9,
+		"b0": 0, "b1": 1, "b2": 2, "b3": 3, "b4": 4, "b5": 5, "b6": 6, "b7": 7, "b8": 8, "b9": 9,
+		"c0": 0, "c1": 1, "c2": 2, "c3": 3, "c4": 4, "c5": 5, "c6": 6, "c7": 7, "c8": 8, "c9": 9,
+		"d0": 0, "d1": 1, "d2": 2, "d3": 3, "d4": 4, "d5": 5, "d6": 6, "d7": 7, "d8": 8, "d9": 9,
+		"e0": 0, "e1": 1, "e2": 2, "e3": 3, "e4": 4, "e5": 5, "e6": 6, "e7": 7, "e8": 8, "e9": 9,
+	});
+	assert_eq!(v.len(), 50);
+}
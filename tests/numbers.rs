@@ -91,6 +91,25 @@ fn floats_basic()
 	assert!((n - 123e-7).abs() < 1e-16);
 }
 
+#[test]
+fn floats_correctly_rounded()
+{	// every input must give the same bits as the standard library parser
+	let cases =
+	[	"0.1", "0.3", "123e-7", "9007199254740993", "2.2250738585072011e-308", "1.7976931348623157e308",
+		"4.9406564584124654e-324", "0.000000000000000000000000000000000000001234567890123456789",
+		"123456789012345678901234567890123456789012345678901234567890e-30",
+	];
+	for input in cases
+	{	assert_eq!(read_one::<f64>(input).unwrap().to_bits(), input.parse::<f64>().unwrap().to_bits(), "input was {input}");
+		assert_eq!(read_one::<f32>(input).unwrap().to_bits(), input.parse::<f32>().unwrap().to_bits(), "input was {input}");
+	}
+	// more digits than are kept in memory
+	let input = format!("1.{}1e-5", "0".repeat(2000));
+	assert_eq!(read_one::<f64>(&input).unwrap().to_bits(), input.parse::<f64>().unwrap().to_bits());
+	let input = format!("3{}", "3".repeat(2000));
+	assert_eq!(read_one::<f64>(&input).unwrap().to_bits(), input.parse::<f64>().unwrap().to_bits());
+}
+
 #[test]
 fn floats_signed_zero()
 {	let mzero: f64 = read_one("\"-0\"").unwrap();
@@ -143,6 +162,8 @@ fn number_to_string_formatting()
 		("1000000000", "1000000000"),  // 10^9, stays expanded
 		("10000000000", "1e10"),       // 10^10, switches to exponent form
 		("-3000", "-3000"),
+		("-0.0123", "-0.0123"),
+		("123456789012", "123456789012"), // exponent notation wouldn't be shorter
 	];
 	for (input, expected) in cases
 	{	assert_eq!(read_one::<String>(input).unwrap(), expected, "input was {input}");
@@ -182,3 +203,61 @@ fn value_number_representation()
 	assert_eq!(read_one::<Value>("-1234.56e-1").unwrap(), Value::Number(123456, -3, true));
 	assert_eq!(read_one::<Value>("0").unwrap(), Value::Number(0, 0, false));
 }
+
+/// Numbers that don't fit `u64` mantissa are kept in `Value::BigNumber` with all their digits.
+#[test]
+fn big_numbers_round_trip()
+{	let digits = format!("{}1", "1234567890".repeat(20));
+	let cases =
+	[	digits.clone(),
+		format!("-{digits}"),
+		format!("{}.{}", &digits[.. 100], &digits[100 ..]),
+		format!("0.{digits}"),
+		format!("{digits}e-300"),
+		"18446744073709551616".to_string(),
+		"-170141183460469231731687303715884105728".to_string(),
+	];
+	for input in &cases
+	{	let value = read_one::<Value>(input).unwrap();
+		assert!(matches!(value, Value::BigNumber(..)), "input was {input}");
+		assert_eq!(&value.to_string(), input);
+		assert_eq!(&read_one::<String>(input).unwrap(), input);
+		assert_eq!(read_one::<Value>(&value.to_string()).unwrap(), value);
+		assert_eq!(&Value::parse_number(input).unwrap().to_string(), input);
+	}
+	// trailing zeros go to exponent
+	assert_eq!(read_one::<Value>("100000000000000000000000").unwrap(), Value::Number(1, 23, false));
+	assert_eq!(read_one::<String>(&format!("{digits}000")).unwrap(), format!("{digits}e3"));
+	assert!(Value::parse_number("1 2").is_err());
+	assert!(Value::parse_number("\"1\"").is_err());
+}
+
+#[test]
+fn big_numbers_to_and_from_primitives()
+{	use std::convert::{TryFrom, TryInto};
+	for n in [i128::MAX, i128::MIN, 0, -1, i64::MIN as i128, i64::MIN as i128 - 1]
+	{	let value = Value::try_from(n).unwrap();
+		assert_eq!(value.to_string(), n.to_string());
+		assert_eq!(i128::try_from(value).unwrap(), n);
+	}
+	let value = Value::try_from(u128::MAX).unwrap();
+	assert_eq!(value.to_string(), u128::MAX.to_string());
+	assert_eq!(u128::try_from(value.clone()).unwrap(), u128::MAX);
+	assert!(i128::try_from(value.clone()).is_err());
+	assert!(u64::try_from(value.clone()).is_err());
+	assert_eq!(f64::try_from(value).unwrap(), u128::MAX as f64);
+	// fractional part is truncated, like when reading integers
+	let value = read_one::<Value>("-1234567890123456789012345.6789").unwrap();
+	assert_eq!(i128::try_from(value.clone()).unwrap(), -1234567890123456789012345);
+	let f: f64 = value.try_into().unwrap();
+	assert_eq!(f, -1234567890123456789012345.6789);
+	// floats convert to the shortest digits and back
+	for f in [0.1f64, -1.5e-300, 123456.789, 0.0]
+	{	let value = Value::try_from(f).unwrap();
+		assert_eq!(value.to_string(), read_one::<String>(&format!("{f:e}")).unwrap());
+		assert_eq!(f64::try_from(value).unwrap().to_bits(), f.to_bits());
+	}
+	assert_eq!(Value::try_from(0.1f64).unwrap(), Value::Number(1, -1, false));
+	assert!(f64::try_from(Value::try_from(f64::NAN).unwrap()).unwrap().is_nan());
+	assert_eq!(f64::try_from(Value::try_from(f64::NEG_INFINITY).unwrap()).unwrap(), f64::NEG_INFINITY);
+}
@@ -0,0 +1,149 @@
+//! Patching `Value` documents: JSON Merge Patch (RFC 7396) and JSON Patch (RFC 6902).
+
+use nop_json::{json, Value};
+
+#[test]
+fn merge_patch_rfc_examples()
+{	// test cases from RFC 7396, Appendix A
+	let cases =
+	[	(json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+		(json!({"a": "b"}), json!({"b": "c"}), json!({"a": "b", "b": "c"})),
+		(json!({"a": "b"}), json!({"a": null}), json!({})),
+		(json!({"a": "b", "b": "c"}), json!({"a": null}), json!({"b": "c"})),
+		(json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+		(json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+		(json!({"a": {"b": "c"}}), json!({"a": {"b": "d", "c": null}}), json!({"a": {"b": "d"}})),
+		(json!({"a": [{"b": "c"}]}), json!({"a": [1]}), json!({"a": [1]})),
+		(json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+		(json!({"a": "b"}), json!(["c"]), json!(["c"])),
+		(json!({"a": "foo"}), json!(null), json!(null)),
+		(json!({"a": "foo"}), json!("bar"), json!("bar")),
+		(json!({"e": null}), json!({"a": 1}), json!({"e": null, "a": 1})),
+		(json!([1, 2]), json!({"a": "b", "c": null}), json!({"a": "b"})),
+		(json!({}), json!({"a": {"bb": {"ccc": null}}}), json!({"a": {"bb": {}}})),
+	];
+	for (target, patch, expected) in cases
+	{	let mut doc = target.clone();
+		doc.merge_patch(&patch);
+		assert_eq!(doc, expected, "target {target}, patch {patch}");
+	}
+}
+
+#[test]
+fn diff_merge_patch()
+{	let pairs =
+	[	(json!({"a": 1, "b": {"c": [1, 2], "d": "x"}, "e": true}), json!({"a": 1, "b": {"c": [1, 3]}, "f": {"g": 1}})),
+		(json!({"a": {"b": 1}}), json!({"a": 5})),
+		(json!({"a": 5}), json!({"a": {"b": 1}})),
+		(json!(5), json!({"a": 1})),
+		(json!({"a": 1}), json!([1])),
+		(json!(5), json!(5)),
+		(json!([1, 2]), json!([1, 2])),
+		(json!(null), json!("x")),
+	];
+	for (old, new) in pairs
+	{	let patch = Value::diff_merge_patch(&old, &new);
+		let mut doc = old.clone();
+		doc.merge_patch(&patch);
+		assert_eq!(doc, new, "old {old}, patch {patch}");
+	}
+	// minimal
+	assert_eq!(Value::diff_merge_patch(&json!({"a": 1, "b": {"c": 1, "d": 2}}), &json!({"a": 1, "b": {"c": 1, "d": 3}})), json!({"b": {"d": 3}}));
+	assert_eq!(Value::diff_merge_patch(&json!({"a": 1, "b": 2}), &json!({"a": 1.0, "b": 2})), json!({}));
+	assert_eq!(Value::diff_merge_patch(&json!({"a": 1, "b": 2}), &json!({"b": 2})), json!({"a": null}));
+}
+
+#[test]
+fn json_patch_rfc_examples()
+{	// examples from RFC 6902, Appendix A
+	let cases =
+	[	(json!({"foo": "bar"}), json!([{"op": "add", "path": "/baz", "value": "qux"}]), json!({"baz": "qux", "foo": "bar"})),
+		(json!({"foo": ["bar", "baz"]}), json!([{"op": "add", "path": "/foo/1", "value": "qux"}]), json!({"foo": ["bar", "qux", "baz"]})),
+		(json!({"baz": "qux", "foo": "bar"}), json!([{"op": "remove", "path": "/baz"}]), json!({"foo": "bar"})),
+		(json!({"foo": ["bar", "qux", "baz"]}), json!([{"op": "remove", "path": "/foo/1"}]), json!({"foo": ["bar", "baz"]})),
+		(json!({"baz": "qux", "foo": "bar"}), json!([{"op": "replace", "path": "/baz", "value": "boo"}]), json!({"baz": "boo", "foo": "bar"})),
+		(	json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+			json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+			json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+		),
+		(json!({"foo": ["all", "grass", "cows", "eat"]}), json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]), json!({"foo": ["all", "cows", "eat", "grass"]})),
+		(	json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+			json!([{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]),
+			json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+		),
+		(json!({"foo": "bar"}), json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]), json!({"foo": "bar", "child": {"grandchild": {}}})),
+		(json!({"foo": "bar"}), json!([{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]), json!({"foo": "bar", "baz": "qux"})),
+		(json!({"/": 9, "~1": 10}), json!([{"op": "test", "path": "/~01", "value": 10}]), json!({"/": 9, "~1": 10})),
+		(json!({"foo": ["bar"]}), json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]), json!({"foo": ["bar", ["abc", "def"]]})),
+		(json!({"foo": 1}), json!([{"op": "test", "path": "/foo", "value": 1.0}]), json!({"foo": 1})),
+		// more cases
+		(json!({"foo": 1}), json!([{"op": "add", "path": "", "value": [1]}]), json!([1])),
+		(json!({"foo": 1}), json!([{"op": "replace", "path": "", "value": null}]), json!(null)),
+		(json!({"a": {"b": 1}}), json!([{"op": "copy", "from": "/a", "path": "/c"}, {"op": "add", "path": "/c/d", "value": 2}]), json!({"a": {"b": 1}, "c": {"b": 1, "d": 2}})),
+		(json!({"a": 1}), json!([{"op": "move", "from": "/a", "path": "/a"}]), json!({"a": 1})),
+		(json!({"a": {"b": 1}}), json!([{"op": "move", "from": "/a", "path": "/ab"}]), json!({"ab": {"b": 1}})),
+		(json!([]), json!([]), json!([])),
+	];
+	for (target, patch, expected) in cases
+	{	let mut doc = target.clone();
+		doc.apply_patch(&patch).unwrap_or_else(|e| panic!("{e}; target {target}, patch {patch}"));
+		assert_eq!(doc, expected, "target {target}, patch {patch}");
+	}
+}
+
+#[test]
+fn json_patch_errors()
+{	let cases =
+	[	(json!({"foo": "bar"}), json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]), 0, "Path \"/baz\" doesn't exist"),
+		(json!({"baz": "qux"}), json!([{"op": "test", "path": "/baz", "value": "bar"}]), 0, "Test failed: value at \"/baz\" is \"qux\", not \"bar\""),
+		(json!({"foo": "bar"}), json!([{"op": "remove", "path": "/foo"}, {"op": "remove", "path": "/foo"}]), 1, "Path \"/foo\" doesn't exist"),
+		(json!({"foo": [1]}), json!([{"op": "add", "path": "/foo/2", "value": 1}]), 0, "Invalid array index in \"/foo/2\""),
+		(json!({"foo": [1]}), json!([{"op": "add", "path": "/foo/01", "value": 1}]), 0, "Invalid array index in \"/foo/01\""),
+		(json!({"foo": [1]}), json!([{"op": "remove", "path": "/foo/-"}]), 0, "Path \"/foo/-\" doesn't exist"),
+		(json!({"foo": [1]}), json!([{"op": "replace", "path": "/bar", "value": 1}]), 0, "Path \"/bar\" doesn't exist"),
+		(json!({"foo": 1}), json!([{"op": "add", "path": "/foo/x", "value": 1}]), 0, "Cannot add to number at \"/foo\""),
+		(json!({"a": {"b": 1}}), json!([{"op": "move", "from": "/a", "path": "/a/b/c"}]), 0, "Cannot move \"/a\" to it's child \"/a/b/c\""),
+		(json!({}), json!([{"op": "copy", "from": "/x", "path": "/y"}]), 0, "Path \"/x\" doesn't exist"),
+		(json!({}), json!([{"op": "add", "path": "/a", "value": 1}, {"op": "frobnicate", "path": "/a"}]), 1, "Unknown operation \"frobnicate\""),
+		(json!({}), json!([{"op": "add", "path": "/a"}]), 0, "Member \"value\" is missing"),
+		(json!({}), json!([{"path": "/a"}]), 0, "Member \"op\" is missing"),
+		(json!({}), json!([{"op": "add", "path": 1, "value": 1}]), 0, "Member \"path\" must be string"),
+		(json!({}), json!([{"op": "add", "path": "a", "value": 1}]), 0, "Invalid JSON Pointer \"a\""),
+		(json!({}), json!([5]), 0, "Operation must be object, not number"),
+		(json!({}), json!({"op": "add"}), 0, "Patch must be array, not object"),
+	];
+	for (target, patch, index, message) in cases
+	{	let mut doc = target.clone();
+		let error = doc.apply_patch(&patch).unwrap_err();
+		assert_eq!((error.index(), error.message()), (index, message), "target {target}, patch {patch}");
+		assert_eq!(error.to_string(), format!("Patch operation {index}: {message}"));
+		// atomic
+		assert_eq!(doc, target);
+	}
+}
+
+#[test]
+fn json_patch_diff()
+{	let pairs =
+	[	(json!({"a": 1, "b": {"c": [1, 2, 3], "d": "x"}, "e": true}), json!({"a": 1, "b": {"c": [1, 5]}, "f": {"g": 1}})),
+		(json!({"a": [1]}), json!({"a": [1, 2, 3, {"x": null}]})),
+		(json!({"a/b": {"~": 1}}), json!({"a/b": {"~": 2}, "~/": null})),
+		(json!([1, [2, [3]]]), json!([1, [2, [4], 5]])),
+		(json!(5), json!({"a": 1})),
+		(json!({"a": 1}), json!([1])),
+		(json!(null), json!(null)),
+		(json!([]), json!([[]])),
+	];
+	for (old, new) in pairs
+	{	let patch = Value::diff_patch(&old, &new);
+		let mut doc = old.clone();
+		doc.apply_patch(&patch).unwrap_or_else(|e| panic!("{e}; old {old}, patch {patch}"));
+		assert_eq!(doc, new, "old {old}, patch {patch}");
+	}
+	assert_eq!(Value::diff_patch(&json!({"a": [1, 2]}), &json!({"a": [1, 2.0]})), json!([]));
+	assert_eq!(Value::diff_patch(&json!(1), &json!(2)), json!([{"op": "replace", "path": "", "value": 2}]));
+	assert_eq!
+	(	Value::diff_patch(&json!({"a": [1, 2, 3]}), &json!({"a": [0]})),
+		json!([{"op": "replace", "path": "/a/0", "value": 0}, {"op": "remove", "path": "/a/2"}, {"op": "remove", "path": "/a/1"}])
+	);
+}
@@ -1,8 +1,8 @@
 //! The lower-level `Reader` API: reading whitespace-separated sequences, blobs,
-//! piping, the manual `read_object`/`read_object_use_buffer`/`read_array` helpers,
+//! writing blobs, piping, pulling strings through `io::Read`, the manual `read_object`/`read_object_use_buffer`/`read_array` helpers,
 //! error messages that carry the path into the document, and unwrapping the source.
 
-use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson, escape_bytes};
+use nop_json::{Reader, TryFromJson, ValidateJson, DebugToJson, WriteToJson, escape_bytes, write_blob, pipe_blob_from, Blob, write_blob_base64, write_blob_hex, encode_base64, encode_hex, Base64Alphabet};
 use std::io::{self, Write};
 
 #[test]
@@ -92,6 +92,131 @@ fn pipe_blob_chunks_across_buffer_boundary()
 	assert_eq!(sink.n_parts, (N_CHARS as f64 / READER_BUFFER_SIZE as f64).ceil() as usize);
 }
 
+#[test]
+fn write_blob_and_pipe_blob_from()
+{	let data: Vec<u8> = (0 .. 20_000).map(|i| (i % 256) as u8).collect();
+	let mut json = Vec::new();
+	write_blob(&mut json, &data).unwrap();
+	let mut reader = Reader::new(json.iter().copied());
+	assert_eq!(reader.read_blob().unwrap(), data);
+
+	// input that gives few bytes at a time
+	struct Chunks<'a> {data: &'a [u8]}
+	impl<'a> io::Read for Chunks<'a>
+	{	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+		{	let n = buf.len().min(self.data.len()).min(7);
+			buf[.. n].copy_from_slice(&self.data[.. n]);
+			self.data = &self.data[n ..];
+			Ok(n)
+		}
+	}
+	let mut piped = Vec::new();
+	pipe_blob_from(&mut piped, Chunks {data: &data}).unwrap();
+	assert_eq!(piped, json);
+	pipe_blob_from(&mut piped, &data[.. 0]).unwrap();
+	assert_eq!(&piped[json.len() ..], b"\"\"");
+}
+
+#[test]
+fn blob_wrapper()
+{	let blobs = vec![Blob(b"\x00\xFF".to_vec()), Blob::default()];
+	let mut json = Vec::new();
+	blobs.write_to_json(&mut json).unwrap();
+	assert_eq!(json, b"[\"\\u0000\xFF\",\"\"]");
+	let mut reader = Reader::new(json.into_iter());
+	assert_eq!(reader.read::<Vec<Blob>>().unwrap(), blobs);
+	let mut reader = Reader::new(" null ".bytes());
+	assert_eq!(reader.read::<Blob>().unwrap().len(), 0);
+}
+
+#[test]
+fn base64_and_hex_blobs()
+{	let data: Vec<u8> = (0 .. 1000).map(|i| (i * 7 % 256) as u8).collect();
+	for alphabet in [Base64Alphabet::Standard, Base64Alphabet::UrlSafe]
+	{	for padding in [true, false]
+		{	for len in [0, 1, 2, 3, 4, 5, 1000]
+			{	let mut json = Vec::new();
+				write_blob_base64(&mut json, &data[.. len], alphabet, padding).unwrap();
+				assert_eq!(json[1 .. json.len()-1], *encode_base64(&data[.. len], alphabet, padding).as_bytes());
+				let mut reader = Reader::new(json.iter().copied());
+				assert_eq!(reader.read_blob_base64().unwrap(), &data[.. len]);
+				let mut piped = Vec::new();
+				let mut reader = Reader::new(json.iter().copied());
+				reader.pipe_blob_base64(&mut piped).unwrap();
+				assert_eq!(piped, &data[.. len]);
+			}
+		}
+	}
+	assert_eq!(encode_base64(b"foobar", Base64Alphabet::Standard, true), "Zm9vYmFy");
+	assert_eq!(encode_base64(b"fooba", Base64Alphabet::Standard, true), "Zm9vYmE=");
+	assert_eq!(encode_base64(b"foob", Base64Alphabet::Standard, true), "Zm9vYg==");
+	assert_eq!(encode_base64(b"foob", Base64Alphabet::Standard, false), "Zm9vYg");
+
+	let mut json = Vec::new();
+	write_blob_hex(&mut json, &data).unwrap();
+	assert_eq!(json[1 .. json.len()-1], *encode_hex(&data).as_bytes());
+	let mut reader = Reader::new(json.iter().copied());
+	assert_eq!(reader.read_blob_hex().unwrap(), data);
+
+	// JSON escapes are decoded before base64, and null is empty data
+	let mut reader = Reader::new(r#" "\/w\u003D\u003d" null "#.bytes());
+	assert_eq!(reader.read_blob_base64().unwrap(), b"\xFF");
+	assert_eq!(reader.read_blob_base64().unwrap(), b"");
+
+	// invalid input
+	for input in [r#""Zm9vY""#, r#""Zm9v=""#, r#""Zm==Zm==""#, r#""Zg==="#, r#""Zm 9v""#, r#""Zm9vYg=""#]
+	{	let mut reader = Reader::new(input.bytes());
+		assert!(reader.read_blob_base64().is_err(), "{}", input);
+	}
+	let mut reader = Reader::new(r#" {"data": ["00", "0g"]} "#.bytes());
+	let err = reader.read_object(|reader, _key| reader.read_array(|reader| reader.read_blob_hex().map(|_| ())).map(|_| ())).unwrap_err();
+	assert!(err.to_string().ends_with("]: Invalid hex string"), "{err}");
+	let mut reader = Reader::new(" 12 ".bytes());
+	assert_eq!(reader.read_blob_hex().unwrap_err().to_string(), "$: Value must be string, not number");
+}
+
+#[test]
+fn string_reader()
+{	use std::io::Read;
+
+	let json = r#" "aé😀\"\n\\bA" "#;
+	let expected = "aé😀\"\n\\bA".as_bytes();
+	// any chunk size, even when decoded escape is split between reads
+	for chunk_size in 1 .. 6
+	{	let mut reader = Reader::new(json.bytes());
+		let mut string_reader = reader.string_reader().unwrap();
+		let mut data = Vec::new();
+		let mut chunk = vec![0u8; chunk_size];
+		loop
+		{	let n = string_reader.read(&mut chunk).unwrap();
+			if n == 0
+			{	break;
+			}
+			data.extend_from_slice(&chunk[.. n]);
+		}
+		assert_eq!(data, expected);
+	}
+
+	// dropping the adapter early skips the rest of the string
+	let mut reader = Reader::new(r#" "long string \"with\" escapes" null "last" 5 "#.bytes());
+	let mut string_reader = reader.string_reader().unwrap();
+	let mut data = [0u8; 4];
+	string_reader.read_exact(&mut data).unwrap();
+	assert_eq!(&data, b"long");
+	drop(string_reader);
+	let mut data = Vec::new();
+	reader.string_reader().unwrap().read_to_end(&mut data).unwrap();
+	assert!(data.is_empty());
+	drop(reader.string_reader().unwrap());
+	assert_eq!(reader.read::<i32>().unwrap(), 5);
+
+	let mut reader = Reader::new(" 5 ".bytes());
+	assert_eq!(reader.string_reader().err().unwrap().to_string(), "$: Value must be string, not number");
+	let mut reader = Reader::new(r#" "abc"#.bytes());
+	let mut data = Vec::new();
+	assert!(reader.string_reader().unwrap().read_to_end(&mut data).is_err());
+}
+
 #[test]
 fn manual_read_object()
 {	let mut reader = Reader::new(r#" {"x": 10, "y": "the y"} "#.bytes());
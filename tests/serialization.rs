@@ -1,7 +1,9 @@
 //! Serialization via `DebugToJson` / `to_json_string()` for scalars, floats
 //! (including Infinity/NaN), char escaping, containers, maps, tuples and `Value`.
 
-use nop_json::{Reader, Value, DebugToJson};
+use nop_json::{Reader, Value, DebugToJson, WriteToJson, JsonSink, ValueWriter, to_value, json};
+use std::{fmt, io};
+use std::io::Write;
 use std::collections::BTreeMap;
 
 #[test]
@@ -25,6 +27,73 @@ fn floats_finite()
 	assert_eq!((-3000.0f64).to_json_string(), "-3000");
 }
 
+#[test]
+fn floats_shortest_form()
+{	// huge and tiny magnitudes switch to exponent notation instead of printing all the zeroes
+	assert_eq!(1e300f64.to_json_string(), "1e300");
+	assert_eq!(1e-20f64.to_json_string(), "1e-20");
+	assert_eq!((-1.5e-20f64).to_json_string(), "-15e-21");
+	assert_eq!((1.0f64/3.0).to_json_string(), "0.3333333333333333");
+	assert_eq!((1.0f32/3.0).to_json_string(), "0.33333334");
+	assert_eq!(0.1f64.to_json_string(), "0.1");
+	assert_eq!((-0.001f64).to_json_string(), "-0.001");
+	assert_eq!(0.0f64.to_json_string(), "0");
+	assert_eq!((-0.0f64).to_json_string(), "-0");
+}
+
+#[test]
+fn floats_round_trip_bit_exact()
+{	let mut x = 0x2545F4914F6CDD1Du64;
+	for _ in 0 .. 20000
+	{	// xorshift, to visit all kinds of exponents and mantissas
+		x ^= x << 13;
+		x ^= x >> 7;
+		x ^= x << 17;
+		let f = f64::from_bits(x);
+		if f.is_finite()
+		{	let back: f64 = Reader::new(f.to_json_string().bytes()).read().unwrap();
+			assert_eq!(back.to_bits(), f.to_bits(), "for {:e}", f);
+		}
+		let f = f32::from_bits(x as u32);
+		if f.is_finite()
+		{	let back: f32 = Reader::new(f.to_json_string().bytes()).read().unwrap();
+			assert_eq!(back.to_bits(), f.to_bits(), "for {:e}", f);
+		}
+	}
+	for f in [f64::MAX, f64::MIN, f64::MIN_POSITIVE, 5e-324, 0.1+0.2]
+	{	let back: f64 = Reader::new(f.to_json_string().bytes()).read().unwrap();
+		assert_eq!(back.to_bits(), f.to_bits(), "for {:e}", f);
+	}
+}
+
+#[test]
+fn floats_edge_cases_round_trip()
+{	let cases =
+	[	(5e-324, "5e-324"), // smallest subnormal
+		(-5e-324, "-5e-324"),
+		(1e-323, "1e-323"),
+		(f64::from_bits(0x000FFFFFFFFFFFFF), "2225073858507201e-323"), // largest subnormal
+		(f64::MIN_POSITIVE, "22250738585072014e-324"),
+		(f64::MAX, "17976931348623157e292"),
+		(f64::MIN, "-17976931348623157e292"),
+	];
+	for (f, expected) in cases
+	{	assert_eq!(f.to_json_string(), expected);
+		let back: f64 = Reader::new(expected.bytes()).read().unwrap();
+		assert_eq!(back.to_bits(), f.to_bits(), "for {}", expected);
+	}
+	let cases =
+	[	(f32::from_bits(1), "1e-45"),
+		(f32::from_bits(0x007FFFFF), "11754942e-45"),
+		(f32::MAX, "34028235e31"),
+	];
+	for (f, expected) in cases
+	{	assert_eq!(f.to_json_string(), expected);
+		let back: f32 = Reader::new(expected.bytes()).read().unwrap();
+		assert_eq!(back.to_bits(), f.to_bits(), "for {}", expected);
+	}
+}
+
 #[test]
 fn floats_infinity_and_nan_are_quoted()
 {	// Regression: f64 Infinity used to be emitted unquoted (invalid JSON); NaN was never matched.
@@ -109,9 +178,119 @@ fn map_keys_are_escaped()
 #[test]
 fn value_display_and_debug()
 {	let v: Value = Reader::new(r#" {"a": [1, "two", null, true]} "#.bytes()).read().unwrap();
-	// HashMap-backed object with a single key is deterministic
 	assert_eq!(v.to_string(), "{\"a\":[1,\"two\",null,true]}");
 	assert_eq!(format!("{:?}", v), "{\"a\":[1,\"two\",null,true]}");
 	assert_eq!(Value::Null.to_string(), "null");
 	assert_eq!(Value::Array(vec![]).to_string(), "[]");
 }
+
+#[test]
+fn serialize_to_value()
+{	let mut map = BTreeMap::new();
+	map.insert("a\"\u{1F600}".to_string(), (0.1f64, u128::MAX, -7i8));
+	map.insert("b".to_string(), (f64::MAX, 1, 2));
+	let expected = json!({"a\"\u{1F600}": [0.1, u128::MAX, -7], "b": [f64::MAX, 1, 2]});
+	assert_eq!(to_value(&map).unwrap(), expected);
+	assert_eq!(map.to_value().unwrap(), expected);
+	assert_eq!(to_value(&Vec::<()>::new()).unwrap(), json!([]));
+	assert_eq!(to_value(&"\u{0}\t\\".to_string()).unwrap(), json!("\u{0}\t\\"));
+	// same as parsing
+	let text = r#" {"x": [1e400, -0.5, "\ud83d\ude00", {}, [[]], null, true, false], "y": {"z": 12345678901234567890123}} "#;
+	let mut writer = ValueWriter::new();
+	writer.write_all(text.as_bytes()).unwrap();
+	let parsed: Value = Reader::new(text.bytes()).read().unwrap();
+	assert_eq!(writer.finish().unwrap(), parsed);
+	// key order is kept
+	let mut writer = ValueWriter::new();
+	writer.write_all(br#"{"b":1,"a":2}"#).unwrap();
+	let v = writer.finish().unwrap();
+	assert_eq!(v.as_object().unwrap().keys().collect::<Vec<_>>(), vec!["b", "a"]);
+	assert_eq!(v.to_string(), r#"{"b":1,"a":2}"#);
+	// invalid input
+	for text in ["", "[1,]", "{\"a\" 1}", "[1", "1 2", "tru", "\"\\ud800\"", "{,}", "]"]
+	{	let mut writer = ValueWriter::new();
+		let result = writer.write_all(text.as_bytes()).and_then(|_| writer.finish());
+		assert!(result.is_err(), "{}", text);
+	}
+}
+
+/// Has only `write_to_json()` and `fmt()`, so it's passed to `JsonSink` as text.
+struct Manual(i32);
+
+impl<W: io::Write> WriteToJson<W> for Manual
+{	fn write_to_json(&self, out: &mut W) -> io::Result<()>
+	{	write!(out, "{{\"manual\": [{}, \"x\"]}}", self.0)
+	}
+}
+
+impl DebugToJson for Manual
+{	fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result
+	{	write!(out, "{{\"manual\": [{}, \"x\"]}}", self.0)
+	}
+}
+
+#[test]
+fn derived_types_to_value()
+{	#[derive(WriteToJson, DebugToJson)]
+	#[json(kind)]
+	enum Shape
+	{	#[json(circle(r))] Circle(f64),
+		#[json(dot)] Dot,
+	}
+	#[derive(WriteToJson, DebugToJson)]
+	struct Item
+	{	#[json("big id")] id: u128,
+		name: String,
+		shapes: Vec<Shape>,
+		extra: Manual,
+		#[json("")] transient: bool,
+	}
+	let item = Item {id: u128::MAX, name: "a\"b".to_string(), shapes: vec![Shape::Circle(0.1), Shape::Dot], extra: Manual(-7), transient: true};
+	let expected = json!({"big id": u128::MAX, "name": "a\"b", "shapes": [{"kind": "circle", "r": 0.1}, {"kind": "dot"}], "extra": {"manual": [-7, "x"]}});
+	assert_eq!(to_value(&item).unwrap(), expected);
+	assert_eq!(item.to_value().unwrap(), expected);
+	assert!(item.transient);
+	// key order is kept
+	assert_eq!(to_value(&item).unwrap().as_object().unwrap().keys().collect::<Vec<_>>(), vec!["big id", "name", "shapes", "extra"]);
+	assert_eq!(to_value(&vec![Manual(1), Manual(2)]).unwrap(), json!([{"manual": [1, "x"]}, {"manual": [2, "x"]}]));
+	assert_eq!((Manual(1), -0.0f64, 5u8).to_value().unwrap(), json!([{"manual": [1, "x"]}, -0.0, 5]));
+}
+
+#[test]
+fn value_writer_as_sink()
+{	let mut writer = ValueWriter::new();
+	writer.begin_array().unwrap();
+	writer.number(b"", 0, false).unwrap();
+	writer.number(b"123456789012345678901234567890", -2, true).unwrap();
+	writer.begin_object().unwrap();
+	writer.end_object().unwrap();
+	writer.begin_raw().unwrap();
+	writer.write_all(b" [1, {\"a\": null}] ").unwrap();
+	writer.end_raw().unwrap();
+	writer.begin_raw().unwrap();
+	writer.write_all(b"12").unwrap();
+	writer.write_all(b"3").unwrap();
+	writer.end_raw().unwrap();
+	writer.end_array().unwrap();
+	assert_eq!(writer.finish().unwrap().to_string(), "[0,-1234567890123456789012345678.90,{},[1,{\"a\":null}],123]");
+	// raw text must be exactly one value
+	for text in ["", "1, 2", "1]", "[1", "{} {}"]
+	{	let mut writer = ValueWriter::new();
+		writer.begin_array().unwrap();
+		writer.begin_raw().unwrap();
+		let result = writer.write_all(text.as_bytes()).and_then(|_| writer.end_raw());
+		assert!(result.is_err(), "{}", text);
+	}
+	// structure errors
+	let mut writer = ValueWriter::new();
+	writer.begin_object().unwrap();
+	assert!(writer.null().is_err());
+	assert!(writer.end_array().is_err());
+	writer.key("a").unwrap();
+	assert!(writer.end_object().is_err());
+	assert!(writer.number(b"1x", 0, false).is_err());
+	writer.bool(true).unwrap();
+	writer.end_object().unwrap();
+	assert!(writer.string("more").is_err());
+	assert_eq!(writer.finish().unwrap(), json!({"a": true}));
+}
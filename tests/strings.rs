@@ -1,5 +1,5 @@
 //! String reading: escapes, \u BMP escapes (1/2/3-byte), UTF-16 surrogate pairs,
-//! raw UTF-8 passthrough, read_char, read_bytes, and malformed-escape errors.
+//! raw UTF-8 passthrough, read_char, read_bytes, pipe_string, malformed-escape errors, and the invalid UTF-8 policy.
 //!
 //! JSON `\uXXXX` escapes are written as Rust `"\\uXXXX"` (the `\\` is one backslash byte).
 
@@ -132,3 +132,90 @@ fn malformed_escapes_error()
 	assert!(read_str("\"\\uDE00\"").is_err());            // lone low surrogate
 	assert!(read_str("\"unterminated").is_err());         // no closing quote
 }
+
+#[test]
+fn pipe_string_validates_utf8()
+{	// multibyte characters and escapes at every offset, so some of them are split between chunks
+	let text: String = (0 .. 500).map(|i| ["a", "é", "€", "😀", "\n", "\"", "ü"][i % 7]).collect();
+	let json = format!(" \"{}\\u00e9\\ud83d\\ude00\" ", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('ü', "\\u00FC"));
+	for prefix_len in 0 .. 4
+	{	let json = format!("{}{}", " ".repeat(prefix_len), json);
+		let mut out = Vec::new();
+		Reader::new(json.bytes()).pipe_string(&mut out).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), format!("{}é😀", text));
+	}
+
+	// scalars are converted like on reading String
+	let mut reader = Reader::new(" null true 1.5e3 ".bytes());
+	for expected in ["null", "true", "1500"]
+	{	let mut out = Vec::new();
+		reader.pipe_string(&mut out).unwrap();
+		assert_eq!(out, expected.as_bytes());
+	}
+
+	// invalid UTF-8: bad byte, truncated character before the closing quote, overlong encoding, and character split by escape
+	for input in [&b"\"ab\xFFcd\""[..], b"\"ab\xE2\x82\"", b"\"\xC0\xAF\"", b"\"\xE2\x82\\u0041\""]
+	{	let mut out = Vec::new();
+		let err = Reader::new(input.iter().copied()).pipe_string(&mut out).unwrap_err();
+		assert_eq!(err.to_string(), "$: Invalid UTF-8 string");
+	}
+	let mut out = Vec::new();
+	let err = Reader::new("[1]".bytes()).pipe_string(&mut out).unwrap_err();
+	assert_eq!(err.to_string(), "$: Value must be string, not array");
+}
+
+#[test]
+fn invalid_utf8_policy()
+{	use nop_json::{ReaderBuilder, Utf8Policy, Value};
+	let json: &[u8] = b" \"a\\uD83Db\" \"\\uD83D\\uD83D\\uDE00\" \"\\uDE00\\n\" \"\\uD83D\\\\\" \"x\xFFy\" {\"k\xC0\": \"\\uD800\"} \"\\uD800\" ";
+
+	let mut reader = ReaderBuilder::new().build(json.iter().copied());
+	assert_eq!(reader.read::<String>().unwrap_err().to_string(), "$: Invalid UTF-16 surrogate pair");
+
+	let mut reader = ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(json.iter().copied());
+	assert_eq!(reader.read::<String>().unwrap(), "a\u{FFFD}b");
+	assert_eq!(reader.read::<String>().unwrap(), "\u{FFFD}\u{1F600}"); // second high surrogate is read again, and makes a pair
+	assert_eq!(reader.read::<String>().unwrap(), "\u{FFFD}\n");
+	assert_eq!(reader.read::<String>().unwrap(), "\u{FFFD}\\");
+	assert_eq!(reader.read::<String>().unwrap(), "x\u{FFFD}y");
+	let mut obj = nop_json::Map::new();
+	obj.insert("k\u{FFFD}".to_string(), Value::String("\u{FFFD}".to_string()));
+	assert_eq!(reader.read::<Value>().unwrap(), Value::Object(obj));
+	assert_eq!(reader.read::<char>().unwrap(), '\u{FFFD}');
+
+	let mut reader = ReaderBuilder::new().invalid_utf8(Utf8Policy::Preserve).build(json.iter().copied());
+	assert_eq!(reader.read_blob().unwrap(), b"a\xED\xA0\xBDb");
+	assert_eq!(reader.read_blob().unwrap(), b"\xED\xA0\xBD\xF0\x9F\x98\x80");
+	assert_eq!(reader.read_blob().unwrap(), b"\xED\xB8\x80\n");
+	assert_eq!(reader.read::<String>().unwrap_err().to_string(), "$: Invalid UTF-8 string");
+	assert_eq!(reader.read_blob().unwrap(), b"x\xFFy");
+	assert!(reader.read::<Value>().is_err());
+
+	// pipe_string() and string_reader() follow the policy too
+	let mut out = Vec::new();
+	ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(b"\"\\uDFFF!\"".iter().copied()).pipe_string(&mut out).unwrap();
+	assert_eq!(out, "\u{FFFD}!".as_bytes());
+	let mut out = Vec::new();
+	ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(b"\"a\xFFb\"".iter().copied()).pipe_string(&mut out).unwrap();
+	assert_eq!(out, "a\u{FFFD}b".as_bytes());
+	// each invalid sequence gives one replacement character, like on reading String
+	for input in [&b"\"ab\xE2\x82\""[..], b"\"\xC0\xAF\"", b"\"\xE2\x82\\u0041\"", b"\"\xF0\x9F\x98a\xED\xA0\x80\""]
+	{	let mut out = Vec::new();
+		ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(input.iter().copied()).pipe_string(&mut out).unwrap();
+		let expected = ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(input.iter().copied()).read::<String>().unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), expected);
+	}
+	// invalid sequences split between chunks
+	for len in 0 .. 300
+	{	let mut input = format!("\"{}", "x".repeat(len)).into_bytes();
+		input.extend_from_slice(b"\xF0\x9F\x98\xE2\x82a\xFF\"");
+		let mut out = Vec::new();
+		ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(input.iter().copied()).pipe_string(&mut out).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), format!("{}\u{FFFD}\u{FFFD}a\u{FFFD}", "x".repeat(len)));
+	}
+	let mut out = String::new();
+	let mut reader = ReaderBuilder::new().invalid_utf8(Utf8Policy::Replace).build(b"\"\\uD800\\u0041\" 1".iter().copied());
+	std::io::Read::read_to_string(&mut reader.string_reader().unwrap(), &mut out).unwrap();
+	assert_eq!(out, "\u{FFFD}A");
+	assert_eq!(reader.read::<i32>().unwrap(), 1);
+}
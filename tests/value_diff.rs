@@ -0,0 +1,52 @@
+//! Structural diff of two `Value` documents and the report formatter.
+
+use nop_json::{json, Value, Change};
+
+#[test]
+fn changes()
+{	let old = json!({"a": {"b": [1, 2, {"c": "x"}]}, "d": null, "e": [], "f": 1.5, "g": {"h": 1}});
+	let new = json!({"a": {"b": [1.0, 3, {"c": "y"}, 4]}, "d": {}, "e": [[]], "f": "1.5", "i": true});
+	assert_eq!
+	(	Value::diff(&old, &new),
+		vec!
+		[	Change::Changed {path: "$.a.b[1]".to_string(), old: json!(2), new: json!(3)},
+			Change::Changed {path: "$.a.b[2].c".to_string(), old: json!("x"), new: json!("y")},
+			Change::Added {path: "$.a.b[3]".to_string(), value: json!(4)},
+			Change::TypeChanged {path: "$.d".to_string(), old: json!(null), new: json!({})},
+			Change::Added {path: "$.e[0]".to_string(), value: json!([])},
+			Change::TypeChanged {path: "$.f".to_string(), old: json!(1.5), new: json!("1.5")},
+			Change::Removed {path: "$.g".to_string(), value: json!({"h": 1})},
+			Change::Added {path: "$.i".to_string(), value: json!(true)},
+		]
+	);
+	assert_eq!(Value::diff(&old, &new)[2].path(), "$.a.b[3]");
+	assert_eq!(Value::diff(&new, &old).len(), 8);
+	assert_eq!(Value::diff(&old, &old), vec![]);
+	assert_eq!(Value::diff(&json!([1, 2]), &json!({"0": 1})), vec![Change::TypeChanged {path: "$".to_string(), old: json!([1, 2]), new: json!({"0": 1})}]);
+	assert_eq!(Value::diff(&json!([1, 2, 3]), &json!([1])).iter().map(|c| c.path()).collect::<Vec<_>>(), vec!["$[1]", "$[2]"]);
+	// numeric-aware
+	assert_eq!(Value::diff(&json!({"n": 100}), &json!({"n": 1e2})), vec![]);
+	assert_eq!(Value::diff(&Value::Number(10, 0, false), &Value::BigNumber("1".parse().unwrap(), 1, false)), vec![]);
+	// keys that are not identifiers
+	let old = json!({"first name": "a", "a.b": {"\"": [1]}, "1st": 1, "_ok$1": 1});
+	let new = json!({"first name": "b", "a.b": {"\"": [2]}, "1st": 2, "_ok$1": 2});
+	assert_eq!
+	(	Value::diff(&old, &new).iter().map(|c| c.path()).collect::<Vec<_>>(),
+		vec!["$[\"1st\"]", "$._ok$1", "$[\"a.b\"][\"\\\"\"][0]", "$[\"first name\"]"]
+	);
+}
+
+#[test]
+fn report()
+{	let old = json!({"a": [1, {"b": "x"}], "c": 1, "d": false});
+	let new = json!({"a": [1, {"b": "y"}, null], "c": "1", "e": {"f": [1]}});
+	let expected = concat!
+	(	"$.a[1].b: changed \"x\" -> \"y\"\n",
+		"$.a[2]: added null\n",
+		"$.c: type changed number 1 -> string \"1\"\n",
+		"$.d: removed false\n",
+		"$.e: added {\"f\":[1]}\n",
+	);
+	assert_eq!(Value::diff_report(&old, &new), expected);
+	assert_eq!(Value::diff_report(&old, &old), "");
+}
@@ -1,8 +1,8 @@
 //! The `Value` type: predicates, indexing, and `TryFrom`/`TryInto` conversions both directions.
 
-use nop_json::{Reader, Value};
+use nop_json::{json, Reader, ReaderBuilder, TryFromJson, Value, Map, Decimal, Rounding, ConversionError, Digits};
 use std::convert::TryInto;
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap, VecDeque};
 
 #[test]
 fn predicates()
@@ -117,7 +117,7 @@ fn from_str()
 
 #[test]
 fn index_by_key()
-{	let mut obj = HashMap::new();
+{	let mut obj = Map::new();
 	obj.insert("name".to_string(), Value::String("John".to_string()));
 	let v = Value::Object(obj);
 	assert_eq!(v["name"], Value::String("John".to_string()));
@@ -126,3 +126,334 @@ fn index_by_key()
 	// indexing a non-object -> Null
 	assert_eq!(Value::Null["whatever"], Value::Null);
 }
+
+#[test]
+fn object_key_order()
+{	let mut v: Value = Reader::new(r#" {"c": 1, "a": 2, "b": 3, "a": 4} "#.bytes()).read().unwrap();
+	assert_eq!(v.to_string(), r#"{"c":1,"a":4,"b":3}"#);
+	assert_eq!(v.remove("c"), Some(json!(1)));
+	v.insert("c", json!(5));
+	assert_eq!(v.to_string(), r#"{"a":4,"b":3,"c":5}"#);
+	let map = v.as_object().unwrap();
+	assert_eq!(map.get("b"), Some(&json!(3)));
+	assert_eq!(map.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+	// order doesn't matter in comparison
+	assert_eq!(v, json!({"c": 5, "b": 3, "a": 4}));
+}
+
+fn hash_of(v: &Value) -> u64
+{	use std::hash::{Hash, Hasher};
+	let mut hasher = std::collections::hash_map::DefaultHasher::new();
+	v.hash(&mut hasher);
+	hasher.finish()
+}
+
+#[test]
+fn numeric_equality()
+{	let read = |s: &str| -> Value {Reader::new(s.bytes()).read().unwrap()};
+	let groups =
+	[	vec![Value::Number(10, 0, false), Value::Number(1, 1, false), Value::Number(1000, -2, false), read("10.000"), read("1e1")],
+		vec![Value::Number(0, 0, false), Value::Number(0, 5, true), read("-0"), read("0.0e-7")],
+		vec![Value::Number(125, -1, true), Value::Number(12500, -3, true), read("-12.5")],
+		vec![read("123456789012345678901234567890"), Value::BigNumber("12345678901234567890123456789".parse().unwrap(), 1, false), Value::BigNumber("0123456789012345678901234567890000".parse().unwrap(), -3, false)],
+		vec![read("18446744073709551615"), Value::BigNumber("18446744073709551615".parse().unwrap(), 0, false)],
+	];
+	for (i, group) in groups.iter().enumerate()
+	{	for a in group
+		{	for b in group
+			{	assert_eq!(a, b);
+				assert_eq!(a.cmp(b), std::cmp::Ordering::Equal);
+				assert_eq!(hash_of(a), hash_of(b));
+			}
+			for other in groups.iter().skip(i + 1).flatten()
+			{	assert_ne!(a, other);
+			}
+		}
+	}
+	// equal documents compare equal
+	assert_eq!(read(r#" {"a": [1.0, 2e0], "b": {"c": 100}} "#), read(r#" {"b": {"c": 1e2}, "a": [1, 2]} "#));
+	assert_ne!(read("[1, 2]"), read("[2, 1]"));
+	assert_ne!(read("1"), read(r#" "1" "#));
+}
+
+#[test]
+fn big_number_digits_are_validated()
+{	for invalid in ["", "12a", "-1", "1.5", "1e3", " 1", "\u{0663}"]
+	{	assert!(invalid.parse::<Digits>().is_err(), "{:?}", invalid);
+		assert!(Digits::new(invalid.to_string()).is_err(), "{:?}", invalid);
+	}
+	assert_eq!("12a".parse::<Digits>().unwrap_err().to_string(), "$: \"12a\" is not a valid sequence of decimal digits");
+	let digits: Digits = "0123456789".parse().unwrap();
+	assert_eq!(digits.as_str(), "0123456789");
+	assert_eq!(&digits[.. 2], "01");
+	let v = Value::BigNumber(digits.clone(), 2, true);
+	assert_eq!(v.to_string(), "-12345678900");
+	assert_eq!(Reader::new(v.to_string().bytes()).read::<Value>().unwrap(), v);
+	assert_eq!(digits.into_string(), "0123456789");
+	// leading zeroes are not written
+	assert_eq!(Value::BigNumber("000".parse().unwrap(), 5, false).to_string(), "0");
+	assert_eq!(Value::BigNumber("0005".parse().unwrap(), -3, false).to_string(), "0.005");
+}
+
+#[test]
+fn ordering_and_hashing()
+{	use std::collections::{HashSet, BTreeSet};
+	let read = |s: &str| -> Value {Reader::new(s.bytes()).read().unwrap()};
+	let sorted: Vec<Value> =
+	[	"null", "false", "true", "-1e30", "-12.5", "-12.4", "-1", "-0.001", "0", "0.001", "0.0011", "0.9", "1", "1.5", "10",
+		"18446744073709551616", "1e30", r#""""#, r#""1""#, r#""a""#, r#""b""#, "[]", "[1]", "[1, 2]", "[2]", "{}", r#"{"a": 1}"#, r#"{"a": 2}"#, r#"{"a": 2, "b": 0}"#, r#"{"b": 0}"#,
+	].iter().map(|s| read(s)).collect();
+	for (i, a) in sorted.iter().enumerate()
+	{	for (j, b) in sorted.iter().enumerate()
+		{	assert_eq!(a.cmp(b), i.cmp(&j), "comparing {a} and {b}");
+		}
+	}
+	let mut shuffled = sorted.clone();
+	shuffled.reverse();
+	shuffled.sort();
+	assert_eq!(shuffled, sorted);
+
+	let set: HashSet<Value> = ["10", "1e1", "10.0", r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1.0}"#].iter().map(|s| read(s)).collect();
+	assert_eq!(set.len(), 2);
+	let set: BTreeSet<Value> = ["10", "1e1", "-0", "0"].iter().map(|s| read(s)).collect();
+	assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![Value::Number(0, 0, false), Value::Number(10, 0, false)]);
+}
+
+#[test]
+fn object_order_is_order_of_sorted_properties()
+{	let read = |s: &str| -> Value {Reader::new(s.bytes()).read().unwrap()};
+	let objects: Vec<Value> =
+	[	"{}", r#"{"a": 1}"#, r#"{"a": 1.0, "c": 0}"#, r#"{"c": 0, "a": 2}"#, r#"{"b": 0}"#, r#"{"b": 0, "a": 1}"#, r#"{"a": 1, "b": 1}"#,
+		r#"{"d": null, "b": 0, "a": 1}"#, r#"{"a": {"x": [1]}}"#, r#"{"a": {"x": [1], "y": 0}}"#, r#"{"a": {"y": 0}}"#, r#"{"c": 0}"#,
+	].iter().map(|s| read(s)).collect();
+	let sorted_properties = |v: &Value| -> Vec<(String, Value)>
+	{	let mut properties: Vec<(String, Value)> = v.as_object().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+		properties.sort_by(|a, b| a.0.cmp(&b.0));
+		properties
+	};
+	for a in &objects
+	{	for b in &objects
+		{	assert_eq!(a.cmp(b), sorted_properties(a).cmp(&sorted_properties(b)), "comparing {a} and {b}");
+		}
+	}
+	let mut obj = read(r#"{"b": 1, "c": 2}"#);
+	let before = obj.clone();
+	obj.as_object_mut().unwrap().insert("a".to_string(), Value::Number(3, 0, false));
+	assert!(obj < before);
+	obj.as_object_mut().unwrap().remove("a");
+	assert_eq!(obj.cmp(&before), std::cmp::Ordering::Equal);
+}
+
+#[test]
+fn accessors()
+{	let v: Value = Reader::new(r#" {"s": "str", "b": true, "i": -12, "u": 12e2, "f": 1.5, "big": 123456789012345678901234567890, "arr": [1, 2], "obj": {"k": null}} "#.bytes()).read().unwrap();
+	assert_eq!(v["s"].as_str(), Some("str"));
+	assert_eq!(v["b"].as_str(), None);
+	assert_eq!(v["b"].as_bool(), Some(true));
+	assert_eq!(v["s"].as_bool(), None);
+	assert_eq!(v["i"].as_i64(), Some(-12));
+	assert_eq!(v["i"].as_u64(), None);
+	assert_eq!(v["u"].as_u64(), Some(1200));
+	assert_eq!(v["f"].as_i64(), None);
+	assert_eq!(v["f"].as_f64(), Some(1.5));
+	assert_eq!(v["big"].as_u64(), None);
+	assert_eq!(v["big"].as_f64(), Some(123456789012345678901234567890.0));
+	assert_eq!(Value::Number(0, 0, true).as_u64(), Some(0));
+	assert_eq!(Value::Number(1, 30, false).as_i64(), None);
+	assert_eq!(v["s"].as_f64(), None);
+	assert_eq!(v["arr"].as_array().map(|a| a.len()), Some(2));
+	assert_eq!(v["obj"].as_object().map(|o| o.len()), Some(1));
+	assert_eq!(v["arr"].as_object(), None);
+	assert_eq!(v.len(), 8);
+	assert_eq!(v["arr"].len(), 2);
+	assert_eq!(v["s"].len(), 0);
+	assert!(v["s"].is_empty());
+
+	assert_eq!(v.get("arr").and_then(|a| a.get(1)), Some(&Value::Number(2, 0, false)));
+	assert_eq!(v.get("arr").and_then(|a| a.get(2)), None);
+	assert_eq!(v.get("obj".to_string()).and_then(|o| o.get("k")), Some(&Value::Null));
+	assert_eq!(v.get(0), None);
+	assert_eq!(v["arr"][0], Value::Number(1, 0, false));
+	assert_eq!(v["arr"][5], Value::Null);
+	assert_eq!(v["s"][0], Value::Null);
+}
+
+#[test]
+fn mutation()
+{	let mut v: Value = Reader::new(r#" {"arr": [1, 2, 3], "obj": {"k": "v"}} "#.bytes()).read().unwrap();
+	v["arr"][1] = Value::Bool(true);
+	*v.get_mut("obj").unwrap().get_mut("k").unwrap() = Value::Null;
+	v["new"]["deep"] = Value::Number(1, 0, false);
+	assert_eq!(v["new"]["deep"], Value::Number(1, 0, false));
+	assert!(v.as_array_mut().is_none());
+	v.as_object_mut().unwrap().remove("new");
+	assert_eq!(v, Reader::new(r#" {"arr": [1, true, 3], "obj": {"k": null}} "#.bytes()).read::<Value>().unwrap());
+
+	assert_eq!(v["arr"].remove(0), Some(Value::Number(1, 0, false)));
+	assert_eq!(v["arr"].remove(5), None);
+	assert_eq!(v["arr"].len(), 2);
+	assert_eq!(v.remove("obj"), Some(Value::Object(vec![("k".to_string(), Value::Null)].into_iter().collect())));
+	assert_eq!(v.remove("obj"), None);
+	let arr = v["arr"].take();
+	assert_eq!(arr.len(), 2);
+	assert_eq!(v["arr"], Value::Null);
+	assert_eq!(v.insert("arr", arr.clone()), Some(Value::Null));
+	assert_eq!(v, Value::Object(vec![("arr".to_string(), arr)].into_iter().collect()));
+}
+
+#[test]
+#[should_panic]
+fn index_mut_non_object_panics()
+{	let mut v = Value::Bool(true);
+	v["a"] = Value::Null;
+}
+
+#[test]
+#[should_panic]
+fn index_mut_out_of_bounds_panics()
+{	let mut v = Value::Array(vec![]);
+	v[0] = Value::Null;
+}
+
+#[test]
+fn json_pointer()
+{	let mut v: Value = Reader::new(r#" {"foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3, "g|h": 4, "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8, "01": 9} "#.bytes()).read().unwrap();
+	// examples from RFC 6901
+	assert_eq!(v.pointer(""), Some(&v.clone()));
+	assert_eq!(v.pointer("/foo"), Some(&Value::Array(vec![Value::String("bar".to_string()), Value::String("baz".to_string())])));
+	assert_eq!(v.pointer("/foo/0"), Some(&Value::String("bar".to_string())));
+	let cases = [("/", 0), ("/a~1b", 1), ("/c%d", 2), ("/e^f", 3), ("/g|h", 4), ("/i\\j", 5), ("/k\"l", 6), ("/ ", 7), ("/m~0n", 8), ("/01", 9)];
+	for (pointer, n) in cases
+	{	assert_eq!(v.pointer(pointer).and_then(|v| v.as_u64()), Some(n), "pointer was {pointer}");
+	}
+	assert_eq!(v.pointer("foo"), None);
+	assert_eq!(v.pointer("/foo/2"), None);
+	assert_eq!(v.pointer("/foo/01"), None);
+	assert_eq!(v.pointer("/foo/-"), None);
+	assert_eq!(v.pointer("/foo/0/x"), None);
+	assert_eq!(v.pointer("/nope"), None);
+
+	*v.pointer_mut("/foo/1").unwrap() = Value::Null;
+	assert_eq!(v["foo"][1], Value::Null);
+	assert!(v.pointer_mut("/foo/2").is_none());
+}
+
+#[derive(Debug, PartialEq)]
+struct Item
+{	name: String,
+	price: Decimal<2>,
+}
+
+impl TryFromJson for Item
+{	fn try_from_json<T>(reader: &mut Reader<T>) -> std::io::Result<Self> where T: Iterator<Item=u8>
+	{	let mut name = None;
+		let mut price = None;
+		reader.read_object_use_buffer
+		(	|reader|
+			{	match reader.get_key()
+				{	b"name" => name = reader.read_prop("name")?,
+					b"price" => price = reader.read_prop("price")?,
+					_ => return Err(reader.format_error_fmt(format_args!("Invalid property: {}", String::from_utf8_lossy(reader.get_key()))))
+				}
+				Ok(())
+			}
+		)?;
+		Ok(Item {name: name.unwrap_or_default(), price: price.unwrap_or_default()})
+	}
+}
+
+#[test]
+fn deserialize_from_value()
+{	let v = json!({"items": [{"name": "a\"b", "price": 19.9}, {"name": "c", "price": 1}], "total": 123456789012345678901234567890u128});
+	let items: Vec<Item> = v["items"].deserialize().unwrap();
+	assert_eq!(items, vec![Item {name: "a\"b".to_string(), price: Decimal::new(1990)}, Item {name: "c".to_string(), price: Decimal::new(100)}]);
+	let total: u128 = v["total"].deserialize().unwrap();
+	assert_eq!(total, 123456789012345678901234567890);
+	// same value back
+	let copy: Value = v.deserialize().unwrap();
+	assert_eq!(copy, v);
+	// errors have path from the value root
+	let v = json!([[], [{"name": "a", "price": 1}, {"name": "b", "price": 1.001}]]);
+	let error = v.deserialize::<Vec<Vec<Item>>>().unwrap_err();
+	assert_eq!(error.to_string(), "$[1][1].price: Number has more fractional digits than decimal scale allows");
+	let error = json!([{"name": "a", "color": "red"}]).deserialize::<Vec<Item>>().unwrap_err();
+	assert_eq!(error.to_string(), "$[0]: Invalid property: color");
+	// reader options
+	let price: Decimal<2> = json!(1.005).deserialize_with(ReaderBuilder::new().decimal_rounding(Rounding::HalfAwayFromZero)).unwrap();
+	assert_eq!(price, Decimal::new(101));
+}
+
+#[test]
+fn deserialize_without_text()
+{	// same conversions as from JSON text
+	let v = json!([null, true, false, 1500, -2.5, "3.5", "a\\\"b"]);
+	let ints: Vec<i32> = json!([null, true, false, 1500, -2.5]).deserialize().unwrap();
+	assert_eq!(ints, vec![0, 1, 0, 1500, -2]);
+	let floats: Vec<Option<f64>> = json!([null, true, 0, 1500, -2.5, "3.5"]).deserialize().unwrap();
+	assert_eq!(floats, vec![None, Some(1.0), Some(0.0), Some(1500.0), Some(-2.5), Some(3.5)]);
+	let strings: Vec<String> = v.deserialize().unwrap();
+	assert_eq!(strings, vec!["null", "true", "false", "1500", "-2.5", "3.5", "a\\\"b"]);
+	assert_eq!(json!(300).deserialize::<u8>().unwrap_err().to_string(), "$: Invalid JSON input: Number is too big");
+	assert_eq!(json!(-1).deserialize::<u32>().unwrap_err().to_string(), "$: Invalid JSON input: Number is too big");
+	assert_eq!(Value::parse_number("1e30").unwrap().deserialize::<i128>().unwrap(), 1_000_000_000_000_000_000_000_000_000_000);
+	assert_eq!(Value::parse_number("123456789012345678901234567890e-25").unwrap().deserialize::<f64>().unwrap(), 12345.678901234567);
+	// strings in the tree are not limited by value_size_limit
+	let long = "x".repeat(1000);
+	let s: String = Value::String(long.clone()).deserialize_with(ReaderBuilder::new().value_size_limit(10)).unwrap();
+	assert_eq!(s, long);
+}
+
+#[test]
+fn conversion_errors()
+{	let error = u8::try_from(json!(1000)).unwrap_err();
+	assert_eq!(error.path(), "$");
+	assert_eq!(error.message(), "Number 1000 doesn't fit u8");
+	assert_eq!(u32::try_from(json!(-1)).unwrap_err().message(), "Number -1 doesn't fit u32");
+	assert_eq!(i32::try_from(json!("abc")).unwrap_err().message(), "String \"abc\" is not a valid i32");
+	assert_eq!(f64::try_from(json!({})).unwrap_err().message(), "Value must be f64, not object");
+	assert_eq!(String::try_from(json!([])).unwrap_err().message(), "Value must be String, not array");
+	assert_eq!(<()>::try_from(json!(false)).unwrap_err().message(), "Value must be null, not boolean");
+	assert_eq!(char::try_from(json!("")).unwrap_err().message(), "Value must be char, not empty string");
+
+	// path within nested containers
+	let v = json!({"a": {"b": [[1, 2], [3, -4]]}});
+	let error = HashMap::<String, BTreeMap<String, Vec<Vec<u64>>>>::try_from(v).unwrap_err();
+	assert_eq!(error.to_string(), "$.a.b[1][1]: Number -4 doesn't fit u64");
+	let error = Vec::<(String, Option<i8>)>::try_from(json!([["a", null], ["b", 200]])).unwrap_err();
+	assert_eq!(error.to_string(), "$[1][1]: Number 200 doesn't fit i8");
+	assert_eq!(<(i32, i32)>::try_from(json!([1, 2, 3])).unwrap_err().message(), "Value must be array[2], not array[3]");
+	assert_eq!(Vec::<i32>::try_from(json!({})).unwrap_err().message(), "Value must be array, not object");
+
+	let error = Vec::<Vec<u8>>::try_from(json!([[1, "bad"]])).unwrap_err();
+	assert_eq!(error.to_string(), "$[0][1]: String \"bad\" is not a valid u8");
+	let error = HashMap::<String, Vec<u8>>::try_from(json!({"first name": [1, []]})).unwrap_err();
+	assert_eq!(error.to_string(), "$[\"first name\"][1]: Value must be u8, not array");
+
+	// path of errors in conversions to Value
+	let error = Value::try_from(HashMap::from([("a b".to_string(), vec![Decimal::<40000>::new(1)])])).unwrap_err();
+	assert_eq!(error.to_string(), "$[\"a b\"][0]: Decimal scale 40000 is too big");
+
+	// custom errors
+	let error = ConversionError::new("Must be positive").at_key("x").at_index(2);
+	assert_eq!(error.to_string(), "$[2].x: Must be positive");
+	let error = ConversionError::new("Must be positive").at_key("").at_key("a\"b");
+	assert_eq!(error.to_string(), "$[\"a\\\"b\"][\"\"]: Must be positive");
+}
+
+#[test]
+fn generic_conversions()
+{	let v = json!({"a": [1.5, null], "b": [u128::MAX as f64, -0.25]});
+	let map: HashMap<String, Vec<Option<f64>>> = v.clone().try_into().unwrap();
+	assert_eq!(map["a"], vec![Some(1.5), None]);
+	assert_eq!(Value::try_from(map).unwrap(), v);
+
+	let v = json!([[u128::MAX, i128::MIN, 0.1], [0, 0, 1e-3]]);
+	let items: VecDeque<(u128, i128, f32)> = v.clone().try_into().unwrap();
+	assert_eq!(items[0], (u128::MAX, i128::MIN, 0.1));
+	assert_eq!(Value::try_from(items).unwrap(), v);
+
+	let pair: (String, Vec<bool>) = json!(["x", [true]]).try_into().unwrap();
+	assert_eq!(Value::try_from(pair).unwrap(), json!(["x", [true]]));
+	assert_eq!(Option::<i32>::try_from(Value::Null), Ok(None));
+	assert_eq!(Value::try_from(Some(vec![Some(1), None])).unwrap(), json!([1, null]));
+}
@@ -1,7 +1,7 @@
 //! The `WriteToJson` trait: writing to an `io::Write` sink, derive, manual impl,
-//! and agreement with `DebugToJson::to_json_string()`.
+//! agreement with `DebugToJson::to_json_string()`, and step-by-step writing with `JsonWriter`.
 
-use nop_json::{WriteToJson, DebugToJson};
+use nop_json::{WriteToJson, DebugToJson, JsonWriter, Formatting, Value};
 use std::io;
 use std::collections::BTreeMap;
 
@@ -84,4 +84,139 @@ fn matches_debug_to_json()
 	assert_eq!(to_json(f64::INFINITY), f64::INFINITY.to_json_string());
 	assert_eq!(to_json('\t'), '\t'.to_json_string());
 	assert_eq!(to_json("a\"b".to_string()), "a\"b".to_string().to_json_string());
+	assert_eq!(to_json(i128::MIN), i128::MIN.to_json_string());
+	assert_eq!(to_json(u128::MAX), u128::MAX.to_json_string());
+	assert_eq!(to_json(i64::MIN), i64::MIN.to_json_string());
+	assert_eq!(to_json(0u128), "0");
+	assert_eq!(to_json(-0.0f64), (-0.0f64).to_json_string());
+	assert_eq!(to_json(1e-20f32), 1e-20f32.to_json_string());
+	assert_eq!(to_json(f32::NEG_INFINITY), f32::NEG_INFINITY.to_json_string());
+	let s = "\u{1}\u{1f} \\ \r\n\t\u{8}\u{c} € <&>".repeat(100);
+	assert_eq!(to_json(s.clone()), s.to_json_string());
+	let v = Value::Array(vec![Value::BigNumber("123456789012345678901234567890".parse().unwrap(), -40, true), Value::Number(15, 30, false), Value::String(s)]);
+	assert_eq!(to_json(v.clone()), v.to_json_string());
+}
+
+#[test]
+fn json_writer()
+{	let mut writer = JsonWriter::new(Vec::new());
+	writer.begin_array().unwrap();
+	writer.value(&1).unwrap();
+	writer.begin_object().unwrap();
+	writer.key("a\"b").unwrap();
+	writer.value(&vec![true, false]).unwrap();
+	writer.key("c").unwrap();
+	writer.begin_array().unwrap();
+	writer.end_array().unwrap();
+	writer.end_object().unwrap();
+	writer.raw(r#"{"x": [ null ]}"#).unwrap();
+	writer.end_array().unwrap();
+	let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+	assert_eq!(out, r#"[1,{"a\"b":[true,false],"c":[]},{"x":[null]}]"#);
+
+	// same settings as FormatWriter
+	let mut writer = JsonWriter::with_formatting(Vec::new(), Formatting::pretty().sort_keys(true));
+	writer.begin_object().unwrap();
+	writer.key("b").unwrap();
+	writer.value(&1).unwrap();
+	writer.key("a").unwrap();
+	writer.begin_array().unwrap();
+	writer.value(&"x".to_string()).unwrap();
+	writer.end_array().unwrap();
+	writer.end_object().unwrap();
+	let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+	assert_eq!(out, "{\n  \"a\": [\n    \"x\"\n  ],\n  \"b\": 1\n}");
+}
+
+#[test]
+fn json_writer_misuse()
+{	let mut writer = JsonWriter::new(Vec::new());
+	assert!(writer.key("a").is_err());
+	assert!(writer.end_array().is_err());
+	assert!(writer.end_object().is_err());
+	writer.begin_object().unwrap();
+	assert_eq!(writer.value(&1).unwrap_err().to_string(), "Value in object must follow a key");
+	assert!(writer.begin_array().is_err());
+	assert!(writer.end_array().is_err());
+	writer.key("a").unwrap();
+	assert!(writer.key("b").is_err());
+	assert!(writer.end_object().is_err());
+	writer.begin_array().unwrap();
+	assert!(writer.key("c").is_err());
+	assert!(writer.end_object().is_err());
+	writer.end_array().unwrap();
+	assert_eq!(writer.finish().unwrap_err().to_string(), "Not all arrays and objects are closed");
+
+	// failed calls write nothing
+	let mut writer = JsonWriter::new(Vec::new());
+	writer.begin_object().unwrap();
+	assert!(writer.value(&1).is_err());
+	assert!(writer.end_array().is_err());
+	writer.key("a").unwrap();
+	assert!(writer.end_object().is_err());
+	writer.value(&1).unwrap();
+	writer.end_object().unwrap();
+	assert_eq!(writer.finish().unwrap(), br#"{"a":1}"#);
+
+	// exactly one top-level value
+	let mut writer = JsonWriter::new(Vec::new());
+	writer.value(&1).unwrap();
+	assert_eq!(writer.value(&2).unwrap_err().to_string(), "Only one top-level value can be written");
+	assert!(writer.begin_array().is_err());
+	assert!(writer.raw("3").is_err());
+	assert_eq!(writer.finish().unwrap(), b"1");
+	assert_eq!(JsonWriter::new(Vec::new()).finish().unwrap_err().to_string(), "Nothing was written");
+	let mut writer = JsonWriter::new(Vec::new());
+	writer.begin_array().unwrap();
+	writer.end_array().unwrap();
+	assert!(writer.begin_object().is_err());
+	assert_eq!(writer.finish().unwrap(), b"[]");
+}
+
+#[test]
+fn json_writer_raw()
+{	let mut writer = JsonWriter::new(Vec::new());
+	writer.begin_array().unwrap();
+	for json in ["]", "", " ", "1 2", "[1", "{\"a\":}", "1,", "\"abc", "nul"]
+	{	assert_eq!(writer.raw(json).unwrap_err().kind(), io::ErrorKind::InvalidInput, "{}", json);
+	}
+	writer.raw(" 1 ").unwrap();
+	writer.raw("\"x\"").unwrap();
+	writer.begin_object().unwrap();
+	assert!(writer.raw("{}").is_err()); // no key
+	writer.key("a").unwrap();
+	writer.raw("[true]").unwrap();
+	writer.key("b").unwrap();
+	writer.raw("null").unwrap();
+	writer.end_object().unwrap();
+	writer.end_array().unwrap();
+	assert_eq!(writer.finish().unwrap(), br#"[1,"x",{"a":[true],"b":null}]"#);
+
+	let mut writer = JsonWriter::new(Vec::new());
+	writer.raw("{}").unwrap();
+	assert!(writer.raw("{}").is_err());
+	assert_eq!(writer.finish().unwrap(), b"{}");
+}
+
+#[test]
+fn json_writer_raw_is_reformatted()
+{	let mut writer = JsonWriter::with_formatting(Vec::new(), Formatting::pretty());
+	writer.begin_array().unwrap();
+	writer.raw(" [1, {\"a\" : \"b\"}]\n").unwrap();
+	assert!(writer.raw("[1]]").is_err());
+	writer.raw("\"x\"").unwrap();
+	writer.end_array().unwrap();
+	let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+	assert_eq!(out, "[\n  [\n    1,\n    {\n      \"a\": \"b\"\n    }\n  ],\n  \"x\"\n]");
+
+	// long values are checked without storing them
+	let long = format!("[\"{}\"]", "a".repeat(100_000));
+	let mut writer = JsonWriter::new(Vec::new());
+	writer.raw(&long).unwrap();
+	assert_eq!(writer.finish().unwrap(), long.as_bytes());
+
+	// the state changes only after a successful write
+	let mut writer = JsonWriter::new(Vec::new());
+	assert!(writer.raw(r#""\x""#).is_err());
+	assert_eq!(writer.finish().unwrap_err().to_string(), "Nothing was written");
 }